@@ -0,0 +1,36 @@
+extern crate eom;
+extern crate ndarray;
+
+use ndarray::*;
+use std::io::{stdout, BufWriter};
+
+use eom::io::*;
+use eom::traits::*;
+use eom::*;
+
+fn main() {
+    let n = 128;
+    let length = 100.0;
+    let dt = 0.01;
+    let eom = pde::KSE::new(n, length);
+    let mut teo = semi_implicit::DiagRK4::new(eom, dt);
+    let mut pair = pde::Pair::new(n);
+    pair.r = Array::linspace(0.0, length, n + 1)
+        .slice(s![..n])
+        .mapv(|x| (x / 16.0).cos() * (1.0 + (x / 16.0).sin()));
+    pair.r2c();
+    let ts = adaptor::time_series(pair.c.clone(), &mut teo);
+    let end_time = 100_000;
+    let interval = 100;
+    let names: Vec<_> = (0..n).map(|i| format!("u{}", i)).collect();
+    let mut csv = Csv::new(BufWriter::new(stdout())).header("time", &names);
+    for (t, v) in ts.take(end_time).enumerate() {
+        if t % interval != 0 {
+            continue;
+        }
+        pair.c.assign(&v);
+        pair.c2r();
+        csv.write_state(t as f64 * dt, &pair.r).unwrap();
+    }
+    csv.finish().unwrap();
+}
@@ -44,4 +44,9 @@ fn main() {
         arr1(&[1.0, 0.0, 0.0]),
         "diag_rk4.csv",
     );
+    check_accuracy(
+        semi_implicit::ETDRK4::new(l63.clone(), 1.0),
+        arr1(&[1.0, 0.0, 0.0]),
+        "etdrk4.csv",
+    );
 }
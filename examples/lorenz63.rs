@@ -1,9 +1,11 @@
 extern crate eom;
 extern crate ndarray;
 
+use eom::io::*;
 use eom::traits::*;
 use eom::*;
 use ndarray::arr1;
+use std::io::{stdout, BufWriter};
 
 fn main() {
     let dt = 0.01;
@@ -11,8 +13,8 @@ fn main() {
     let mut teo = explicit::RK4::new(eom, dt);
     let ts = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo);
     let end_time = 10000;
-    println!("time,x,y,z");
-    for (t, v) in ts.take(end_time).enumerate() {
-        println!("{},{},{},{}", t as f64 * dt, v[0], v[1], v[2]);
-    }
+    let out = BufWriter::new(stdout());
+    let mut csv = Csv::new(out).header("time", &["x", "y", "z"]);
+    csv.write_all(ts.timed().take(end_time)).unwrap();
+    csv.finish().unwrap();
 }
@@ -10,10 +10,11 @@ import matplotlib.pyplot as plt
 from mpl_toolkits.mplot3d import Axes3D
 
 
-def main(name):
+def main(name, steps):
     result_fn = name + ".csv"
     with open(result_fn, "w") as f:
-        check_call(["cargo", "run", "--release", "--bin", name], stdout=f)
+        check_call(["cargo", "run", "--release", "--features", "cli", "--bin", "eom", "--",
+                    name, "--steps", str(steps)], stdout=f)
     data = pd.read_csv(result_fn).set_index("time")[1:]
     fig = plt.figure()
     ax = fig.add_subplot(111, projection="3d")
@@ -25,5 +26,5 @@ def main(name):
 
 
 if __name__ == '__main__':
-    main("lorenz63")
-    main("roessler")
+    main("lorenz63", 10000)
+    main("roessler", 50000)
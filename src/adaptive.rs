@@ -0,0 +1,386 @@
+//! adaptive time-step schemes using embedded Runge-Kutta pairs
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::{Float, One, Zero};
+use std::marker::PhantomData;
+
+use super::dense::cubic_hermite;
+use super::tangent::Variational;
+use super::traits::*;
+
+/// Butcher tableau of an embedded Runge-Kutta pair
+pub trait Tableau: Clone {
+    /// lower order of the pair, which determines the step-size control
+    const ORDER: i32;
+    /// nodes
+    const C: &'static [f64];
+    /// Runge-Kutta matrix (lower triangular part)
+    const A: &'static [&'static [f64]];
+    /// weights of the propagated solution
+    const B: &'static [f64];
+    /// weights of the error estimate, i.e. `B` minus the embedded weights
+    const E: &'static [f64];
+}
+
+/// Dormand-Prince 5(4) pair
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DormandPrince54;
+
+impl Tableau for DormandPrince54 {
+    const ORDER: i32 = 4;
+    const C: &'static [f64] = &[0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+    const A: &'static [&'static [f64]] = &[
+        &[],
+        &[1.0 / 5.0],
+        &[3.0 / 40.0, 9.0 / 40.0],
+        &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+        &[
+            19372.0 / 6561.0,
+            -25360.0 / 2187.0,
+            64448.0 / 6561.0,
+            -212.0 / 729.0,
+        ],
+        &[
+            9017.0 / 3168.0,
+            -355.0 / 33.0,
+            46732.0 / 5247.0,
+            49.0 / 176.0,
+            -5103.0 / 18656.0,
+        ],
+        &[
+            35.0 / 384.0,
+            0.0,
+            500.0 / 1113.0,
+            125.0 / 192.0,
+            -2187.0 / 6784.0,
+            11.0 / 84.0,
+        ],
+    ];
+    const B: &'static [f64] = &[
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+        0.0,
+    ];
+    const E: &'static [f64] = &[
+        71.0 / 57600.0,
+        0.0,
+        -71.0 / 16695.0,
+        71.0 / 1920.0,
+        -17253.0 / 339200.0,
+        22.0 / 525.0,
+        -1.0 / 40.0,
+    ];
+}
+
+/// Cash-Karp 5(4) pair
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CashKarp45;
+
+impl Tableau for CashKarp45 {
+    const ORDER: i32 = 4;
+    const C: &'static [f64] = &[0.0, 1.0 / 5.0, 3.0 / 10.0, 3.0 / 5.0, 1.0, 7.0 / 8.0];
+    const A: &'static [&'static [f64]] = &[
+        &[],
+        &[1.0 / 5.0],
+        &[3.0 / 40.0, 9.0 / 40.0],
+        &[3.0 / 10.0, -9.0 / 10.0, 6.0 / 5.0],
+        &[-11.0 / 54.0, 5.0 / 2.0, -70.0 / 27.0, 35.0 / 27.0],
+        &[
+            1631.0 / 55296.0,
+            175.0 / 512.0,
+            575.0 / 13824.0,
+            44275.0 / 110592.0,
+            253.0 / 4096.0,
+        ],
+    ];
+    const B: &'static [f64] = &[
+        37.0 / 378.0,
+        0.0,
+        250.0 / 621.0,
+        125.0 / 594.0,
+        0.0,
+        512.0 / 1771.0,
+    ];
+    const E: &'static [f64] = &[
+        37.0 / 378.0 - 2825.0 / 27648.0,
+        0.0,
+        250.0 / 621.0 - 18575.0 / 48384.0,
+        125.0 / 594.0 - 13525.0 / 55296.0,
+        -277.0 / 14336.0,
+        512.0 / 1771.0 - 1.0 / 4.0,
+    ];
+}
+
+/// Bogacki-Shampine 3(2) pair
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BogackiShampine32;
+
+impl Tableau for BogackiShampine32 {
+    const ORDER: i32 = 2;
+    const C: &'static [f64] = &[0.0, 1.0 / 2.0, 3.0 / 4.0, 1.0];
+    const A: &'static [&'static [f64]] = &[
+        &[],
+        &[1.0 / 2.0],
+        &[0.0, 3.0 / 4.0],
+        &[2.0 / 9.0, 1.0 / 3.0, 4.0 / 9.0],
+    ];
+    const B: &'static [f64] = &[2.0 / 9.0, 1.0 / 3.0, 4.0 / 9.0, 0.0];
+    const E: &'static [f64] = &[
+        2.0 / 9.0 - 7.0 / 24.0,
+        1.0 / 3.0 - 1.0 / 4.0,
+        4.0 / 9.0 - 1.0 / 3.0,
+        -1.0 / 8.0,
+    ];
+}
+
+/// Dormand-Prince 5(4) scheme
+pub type DormandPrince<F> = Embedded<F, DormandPrince54>;
+/// Cash-Karp 5(4) scheme
+pub type CashKarp<F> = Embedded<F, CashKarp45>;
+/// Bogacki-Shampine 3(2) scheme
+pub type BogackiShampine<F> = Embedded<F, BogackiShampine32>;
+
+/// Embedded Runge-Kutta scheme with step-size control
+///
+/// `iterate` proceeds by one accepted step. Rejected steps are retried with a smaller time-step,
+/// and `get_dt` returns the proposal for the next step while `last_dt` returns the accepted one.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Embedded<F: ExplicitT, T: Tableau> {
+    f: F,
+    dt: <F::Scalar as Scalar>::Real,
+    last_dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    atol: <F::Scalar as Scalar>::Real,
+    rtol: <F::Scalar as Scalar>::Real,
+    accepted: usize,
+    rejected: usize,
+    x: Array<F::Scalar, F::Dim>,
+    y: Array<F::Scalar, F::Dim>,
+    e: Array<F::Scalar, F::Dim>,
+    k: Vec<Array<F::Scalar, F::Dim>>,
+    tableau: PhantomData<T>,
+}
+
+impl<A: Scalar, F: ExplicitT<Scalar = A>, T: Tableau> TimeStep for Embedded<F, T> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+
+    fn last_dt(&self) -> Self::Time {
+        self.last_dt
+    }
+}
+
+impl<A: Scalar, F: ExplicitT<Scalar = A>, T: Tableau> AdaptiveTimeStep for Embedded<F, T> {
+    fn get_tolerance(&self) -> (Self::Time, Self::Time) {
+        (self.atol, self.rtol)
+    }
+
+    fn set_tolerance(&mut self, atol: Self::Time, rtol: Self::Time) {
+        self.atol = atol;
+        self.rtol = rtol;
+    }
+
+    fn accepted_steps(&self) -> usize {
+        self.accepted
+    }
+
+    fn rejected_steps(&self) -> usize {
+        self.rejected
+    }
+}
+
+impl<F: ExplicitT, T: Tableau> Scheme for Embedded<F, T> {
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        let x = Array::zeros(f.model_size());
+        let y = Array::zeros(f.model_size());
+        let e = Array::zeros(f.model_size());
+        let k = T::B.iter().map(|_| Array::zeros(f.model_size())).collect();
+        Self {
+            f,
+            dt,
+            last_dt: Zero::zero(),
+            t: Zero::zero(),
+            atol: F::Scalar::real(1e-8),
+            rtol: F::Scalar::real(1e-6),
+            accepted: 0,
+            rejected: 0,
+            x,
+            y,
+            e,
+            k,
+            tableau: PhantomData,
+        }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: ExplicitT, T: Tableau> ModelSpec for Embedded<F, T> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: ExplicitT, T: Tableau> Embedded<F, T> {
+    /// Evaluate all stages with time-step `dt` starting from `self.x`
+    fn stages(&mut self, dt: <F::Scalar as Scalar>::Real) {
+        for (i, (a, &ci)) in T::A.iter().zip(T::C.iter()).enumerate() {
+            let (prev, next) = self.k.split_at_mut(i);
+            let ki = &mut next[0];
+            ki.zip_mut_with(&self.x, |k, x| *k = *x);
+            for (kj, &aij) in prev.iter().zip(a.iter()) {
+                if aij == 0.0 {
+                    continue;
+                }
+                let c = dt * F::Scalar::real(aij);
+                Zip::from(&mut *ki)
+                    .and(kj)
+                    .apply(|k, &kj| *k += kj.mul_real(c));
+            }
+            self.f.rhs_t(self.t + dt * F::Scalar::real(ci), ki);
+        }
+    }
+
+    /// Propagated solution into `self.y`, and returns the scaled error norm
+    fn estimate(&mut self, dt: <F::Scalar as Scalar>::Real) -> <F::Scalar as Scalar>::Real {
+        let zero = <F::Scalar as Scalar>::Real::zero();
+        self.y.zip_mut_with(&self.x, |y, x| *y = *x);
+        for (k, &b) in self.k.iter().zip(T::B.iter()) {
+            if b == 0.0 {
+                continue;
+            }
+            let c = dt * F::Scalar::real(b);
+            Zip::from(&mut self.y)
+                .and(k)
+                .apply(|y, &k| *y += k.mul_real(c));
+        }
+        self.e.fill(F::Scalar::zero());
+        for (k, &e) in self.k.iter().zip(T::E.iter()) {
+            if e == 0.0 {
+                continue;
+            }
+            let c = dt * F::Scalar::real(e);
+            Zip::from(&mut self.e)
+                .and(k)
+                .apply(|err, &k| *err += k.mul_real(c));
+        }
+        let (atol, rtol) = (self.atol, self.rtol);
+        let mut sum = zero;
+        Zip::from(&self.e)
+            .and(&self.x)
+            .and(&self.y)
+            .apply(|&e, &x, &y| {
+                let sc = atol + rtol * x.abs().max(y.abs());
+                sum += (e.abs() / sc).square();
+            });
+        Float::sqrt(sum / F::Scalar::real(self.x.len()))
+    }
+
+    /// Factor for the next time-step from the scaled error norm
+    fn factor(err: <F::Scalar as Scalar>::Real) -> <F::Scalar as Scalar>::Real {
+        let fac_min = F::Scalar::real(0.2);
+        let fac_max = F::Scalar::real(5.0);
+        if err.is_nan() {
+            return fac_min;
+        }
+        if err.is_zero() {
+            return fac_max;
+        }
+        let safety = F::Scalar::real(0.9);
+        let exp = F::Scalar::real(1.0 / (T::ORDER + 1) as f64);
+        (safety * Float::powf(err.recip(), exp))
+            .max(fac_min)
+            .min(fac_max)
+    }
+}
+
+impl<F: ExplicitT, T: Tableau> TimeEvolution for Embedded<F, T> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let one = <F::Scalar as Scalar>::Real::one();
+        self.x.zip_mut_with(x, |buf, x| *buf = *x);
+        loop {
+            let dt = self.dt;
+            self.stages(dt);
+            let err = self.estimate(dt);
+            // accept anyway when the time-step underflows, e.g. the state contains NaN
+            if err <= one || Float::abs(dt) <= <F::Scalar as Scalar>::Real::epsilon() {
+                x.zip_mut_with(&self.y, |x, y| *x = *y);
+                self.last_dt = dt;
+                self.t += dt;
+                self.accepted += 1;
+                self.dt = dt * Self::factor(err);
+                return x;
+            }
+            self.rejected += 1;
+            self.dt = dt * Self::factor(err).min(one);
+        }
+    }
+}
+
+impl<F: ExplicitT, T: Tableau> DenseOutput for Embedded<F, T> {
+    /// cubic Hermite interpolation
+    ///
+    /// The derivative at the end of the step is reused from the last stage
+    /// for FSAL (first same as last) pairs, e.g. Dormand-Prince and Bogacki-Shampine.
+    fn interpolate<S>(&mut self, t: Self::Time, x: &mut ArrayBase<S, Self::Dim>)
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let n = T::B.len();
+        let fsal = T::C[n - 1] == 1.0 && T::A[n - 1] == &T::B[..n - 1];
+        if fsal {
+            self.e.zip_mut_with(&self.k[n - 1], |e, k| *e = *k);
+        } else {
+            self.e.zip_mut_with(&self.y, |e, y| *e = *y);
+            self.f.rhs_t(self.t, &mut self.e);
+        }
+        let h = self.last_dt;
+        let s = (t - (self.t - h)) / h;
+        cubic_hermite(s, h, &self.x, &self.k[0], &self.y, &self.e, x);
+    }
+}
+
+impl<F: ExplicitJacobian<Dim = Ix1>, T: Tableau> TangentScheme for Embedded<F, T> {
+    type Tangent = Embedded<Variational<F>, T>;
+}
@@ -1,9 +1,11 @@
 //! Utilities for adopting to Rust fashon
 
+use super::error::Result;
 use super::traits::*;
 use ndarray::*;
 use ndarray_linalg::*;
-use num_traits::FromPrimitive;
+use num_traits::{Float, FromPrimitive, Zero};
+use std::time::{Duration, Instant};
 
 /// Test time accuracy of equation of motion
 pub fn accuracy<A, D, Sc>(
@@ -18,12 +20,14 @@ where
     D: Dimension,
     Sc: Scheme<Scalar = A, Dim = D, Time = A::Real>,
 {
+    let t0 = teo.time();
     let data: Vec<_> = (0..num_scale)
         .map(|n| {
             let rate = 2_usize.pow(n);
             let dt = dt_base / A::real(rate as f64);
             let t = step_base * rate;
             teo.set_dt(dt);
+            teo.set_time(t0);
             (dt, iterate(&mut teo, init.clone(), t))
         })
         .collect();
@@ -36,6 +40,44 @@ where
         .collect()
 }
 
+/// Fallible version of [accuracy], which fails when the state becomes NaN or infinity
+///
+/// [accuracy]: fn.accuracy.html
+pub fn try_accuracy<A, D, Sc>(
+    mut teo: Sc,
+    init: Array<A, D>,
+    dt_base: A::Real,
+    step_base: usize,
+    num_scale: u32,
+) -> Result<Vec<(Sc::Time, A::Real)>>
+where
+    A: Scalar + Lapack,
+    D: Dimension,
+    Sc: Scheme<Scalar = A, Dim = D, Time = A::Real>,
+{
+    let t0 = teo.time();
+    let data = (0..num_scale)
+        .map(|n| {
+            let rate = 2_usize.pow(n);
+            let dt = dt_base / A::real(rate as f64);
+            let t = step_base * rate;
+            teo.set_dt(dt);
+            teo.set_time(t0);
+            let mut x = init.clone();
+            teo.try_iterate_n(&mut x, t)?;
+            Ok((dt, x))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(data
+        .windows(2)
+        .map(|w| {
+            let dt = w[0].0;
+            let dev = (&w[1].1 - &w[0].1).norm();
+            (dt, dev)
+        })
+        .collect())
+}
+
 /// Iterate equation of motion by `step` at once
 pub fn iterate<S, TEO>(
     teo: &mut TEO,
@@ -50,6 +92,85 @@ where
     x0
 }
 
+/// Summary of [integrate_to]
+///
+/// [integrate_to]: fn.integrate_to.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report<T> {
+    /// simulation time at the end
+    pub time: T,
+    /// number of `iterate` calls, i.e. accepted steps for adaptive schemes
+    pub steps: usize,
+    /// wall-clock time spent
+    pub elapsed: Duration,
+}
+
+/// Iterate equation of motion until the simulation time reaches `t_end` exactly
+///
+/// The last step is shortened by `set_dt` to end at `t_end`, and the time-step
+/// before the shortening is restored afterward. Adaptive schemes may take more steps
+/// than expected when the shortened step is rejected.
+///
+/// ```rust
+/// use eom::*;
+/// use eom::traits::*;
+/// use ndarray::arr1;
+/// let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.03);
+/// let mut x = arr1(&[1.0, 0.0, 0.0]);
+/// let report = adaptor::integrate_to(&mut teo, &mut x, 1.0);
+/// assert_eq!(report.time, 1.0);
+/// assert_eq!(report.steps, 34);
+/// assert_eq!(teo.get_dt(), 0.03);
+/// ```
+pub fn integrate_to<TEO, S>(
+    teo: &mut TEO,
+    x: &mut ArrayBase<S, TEO::Dim>,
+    t_end: TEO::Time,
+) -> Report<TEO::Time>
+where
+    TEO: TimeEvolution,
+    S: DataMut<Elem = TEO::Scalar>,
+{
+    let start = Instant::now();
+    let t0 = teo.time();
+    assert!(
+        t_end >= t0,
+        "Final time must not be before the current time"
+    );
+    assert!(
+        teo.get_dt() > TEO::Time::zero(),
+        "Time-step must be positive for integration"
+    );
+    // remainders smaller than this are regarded as rounding errors
+    let tol = TEO::Time::epsilon()
+        * TEO::Time::from_usize(16).unwrap()
+        * (Float::abs(t0) + Float::abs(t_end) + Float::abs(teo.get_dt()));
+    let mut steps = 0;
+    let mut saved = None;
+    loop {
+        let rest = t_end - teo.time();
+        if rest <= tol {
+            break;
+        }
+        let dt = teo.get_dt();
+        if dt >= rest - tol {
+            saved.get_or_insert(dt);
+            teo.set_dt(rest);
+        }
+        teo.iterate(x);
+        steps += 1;
+    }
+    if let Some(dt) = saved {
+        teo.set_dt(dt);
+    }
+    teo.set_time(t_end);
+    Report {
+        time: t_end,
+        steps,
+        elapsed: start.elapsed(),
+    }
+}
+
 /// An iterator generated by [time_series] for the time-series of the given EoM.
 ///
 /// [time_series]: fn.time_series.html
@@ -111,6 +232,30 @@ where
     pub fn iterate(&mut self) {
         self.teo.iterate(&mut self.state);
     }
+
+    /// Current simulation time of the EoM
+    pub fn time(&self) -> TEO::Time {
+        self.teo.time()
+    }
+
+    /// Convert into an iterator yielding `(time, state)` pairs
+    ///
+    /// The time is the simulation time kept by `TimeEvolution`,
+    /// and thus it is correct also for adaptive schemes with uneven time-steps.
+    ///
+    /// ```rust
+    /// use eom::*;
+    /// use eom::traits::*;
+    /// use ndarray::arr1;
+    /// let mut teo = adaptive::DormandPrince::new(ode::Lorenz63::default(), 0.01);
+    /// let ts = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo);
+    /// for (t, v) in ts.timed().take(100) {
+    ///     println!("{},{},{},{}", t, v[0], v[1], v[2]);
+    /// }
+    /// ```
+    pub fn timed(self) -> Timed<'a, S, TEO> {
+        Timed { ts: self }
+    }
 }
 
 impl<'a, S, TEO> Iterator for TimeSeries<'a, S, TEO>
@@ -125,6 +270,104 @@ where
     }
 }
 
+/// An iterator of `(time, state)` pairs generated by [TimeSeries::timed]
+///
+/// [TimeSeries::timed]: struct.TimeSeries.html#method.timed
+pub struct Timed<'a, S, TEO>
+where
+    S: DataMut<Elem = TEO::Scalar> + Data + RawDataClone,
+    TEO: TimeEvolution + 'a,
+{
+    ts: TimeSeries<'a, S, TEO>,
+}
+
+impl<'a, S, TEO> Iterator for Timed<'a, S, TEO>
+where
+    S: DataMut<Elem = TEO::Scalar> + Data + RawDataClone,
+    TEO: TimeEvolution,
+{
+    type Item = (TEO::Time, ArrayBase<S, TEO::Dim>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ts.iterate();
+        Some((self.ts.time(), self.ts.state.clone()))
+    }
+}
+
+/// An iterator generated by [sample] for states at the given output times
+///
+/// [sample]: fn.sample.html
+pub struct Sample<'a, TEO, I>
+where
+    TEO: DenseOutput + 'a,
+{
+    state: Array<TEO::Scalar, TEO::Dim>,
+    teo: &'a mut TEO,
+    times: I,
+    stepped: bool,
+}
+
+/// Generate an iterator of `(time, state)` at the output times `times` using dense output
+///
+/// The output times must be non-decreasing and not before the current time of the EoM.
+/// The EoM proceeds by its own time-step, which must be positive,
+/// and the states between steps are interpolated.
+///
+/// ```rust
+/// use eom::*;
+/// use eom::traits::*;
+/// use ndarray::arr1;
+/// let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+/// let times = vec![0.0, 0.005, 0.013, 0.5, 1.0];
+/// for (t, v) in adaptor::sample(arr1(&[1.0, 0.0, 0.0]), &mut teo, times) {
+///     println!("{},{},{},{}", t, v[0], v[1], v[2]);
+/// }
+/// ```
+pub fn sample<'a, TEO, I>(
+    x0: Array<TEO::Scalar, TEO::Dim>,
+    teo: &'a mut TEO,
+    times: I,
+) -> Sample<'a, TEO, I::IntoIter>
+where
+    TEO: DenseOutput,
+    I: IntoIterator<Item = TEO::Time>,
+{
+    assert!(
+        teo.get_dt() > TEO::Time::zero(),
+        "Time-step must be positive for sampling"
+    );
+    Sample {
+        state: x0,
+        teo,
+        times: times.into_iter(),
+        stepped: false,
+    }
+}
+
+impl<'a, TEO, I> Iterator for Sample<'a, TEO, I>
+where
+    TEO: DenseOutput,
+    I: Iterator<Item = TEO::Time>,
+{
+    type Item = (TEO::Time, Array<TEO::Scalar, TEO::Dim>);
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.times.next()?;
+        while self.teo.time() < t {
+            self.teo.iterate(&mut self.state);
+            self.stepped = true;
+        }
+        if t == self.teo.time() {
+            return Some((t, self.state.clone()));
+        }
+        assert!(
+            self.stepped && t >= self.teo.time() - self.teo.last_dt(),
+            "Output times must be non-decreasing and after the initial time"
+        );
+        let mut x = self.state.clone();
+        self.teo.interpolate(t, &mut x);
+        Some((t, x))
+    }
+}
+
 /// An N-step iterator generated by [nstep]
 ///
 /// [nstep]: fn.nstep.html
@@ -132,6 +375,7 @@ where
 pub struct NStep<TEO: TimeEvolution> {
     teo: TEO,
     n: usize,
+    last_dt: TEO::Time,
 }
 
 /// Generate an iterator which iterate EoM N-step at once
@@ -143,7 +387,11 @@ pub struct NStep<TEO: TimeEvolution> {
 /// let nstep = adaptor::nstep(teo, 10);
 /// ```
 pub fn nstep<TEO: TimeEvolution>(teo: TEO, n: usize) -> NStep<TEO> {
-    NStep { teo, n }
+    NStep {
+        teo,
+        n,
+        last_dt: TEO::Time::zero(),
+    }
 }
 
 impl<TEO: TimeEvolution> ModelSpec for NStep<TEO> {
@@ -165,9 +413,21 @@ impl<TEO: TimeEvolution> TimeStep for NStep<TEO> {
     fn set_dt(&mut self, dt: Self::Time) {
         self.teo.set_dt(dt / TEO::Time::from_usize(self.n).unwrap());
     }
+
+    fn last_dt(&self) -> Self::Time {
+        self.last_dt
+    }
 }
 
 impl<TEO: TimeEvolution> TimeEvolution for NStep<TEO> {
+    fn time(&self) -> Self::Time {
+        self.teo.time()
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.teo.set_time(t);
+    }
+
     fn iterate<'a, S>(
         &mut self,
         x: &'a mut ArrayBase<S, TEO::Dim>,
@@ -175,9 +435,429 @@ impl<TEO: TimeEvolution> TimeEvolution for NStep<TEO> {
     where
         S: DataMut<Elem = TEO::Scalar>,
     {
+        self.last_dt = TEO::Time::zero();
         for _ in 0..self.n {
             self.teo.iterate(x);
+            self.last_dt += self.teo.last_dt();
+        }
+        x
+    }
+}
+
+/// Whether the time evolution continues after an observer is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+/// Observer called after each step by [Observed]
+///
+/// The arguments are the simulation time, the number of steps taken so far
+/// (starting from 1), and the state after the step.
+/// Closures `FnMut(T, usize, ArrayView<A, D>) -> Control` are observers,
+/// and tuples or `Vec`s of observers call all of them in order.
+///
+/// [Observed]: struct.Observed.html
+pub trait Observer<A, D: Dimension, T> {
+    fn observe(&mut self, t: T, step: usize, x: ArrayView<A, D>) -> Control;
+}
+
+impl<A, D, T, F> Observer<A, D, T> for F
+where
+    D: Dimension,
+    F: FnMut(T, usize, ArrayView<A, D>) -> Control,
+{
+    fn observe(&mut self, t: T, step: usize, x: ArrayView<A, D>) -> Control {
+        self(t, step, x)
+    }
+}
+
+impl<A, D: Dimension, T: Copy, O: Observer<A, D, T>> Observer<A, D, T> for Vec<O> {
+    fn observe(&mut self, t: T, step: usize, x: ArrayView<A, D>) -> Control {
+        let mut c = Control::Continue;
+        for o in self.iter_mut() {
+            if o.observe(t, step, x.view()) == Control::Stop {
+                c = Control::Stop;
+            }
+        }
+        c
+    }
+}
+
+macro_rules! impl_observer_tuple {
+    ($($i:tt: $o:ident),*) => {
+        impl<A, D: Dimension, T: Copy, $($o: Observer<A, D, T>),*> Observer<A, D, T> for ($($o,)*) {
+            fn observe(&mut self, t: T, step: usize, x: ArrayView<A, D>) -> Control {
+                let mut c = Control::Continue;
+                $(
+                    if self.$i.observe(t, step, x.view()) == Control::Stop {
+                        c = Control::Stop;
+                    }
+                )*
+                c
+            }
+        }
+    };
+}
+
+impl_observer_tuple!(0: O0, 1: O1);
+impl_observer_tuple!(0: O0, 1: O1, 2: O2);
+impl_observer_tuple!(0: O0, 1: O1, 2: O2, 3: O3);
+impl_observer_tuple!(0: O0, 1: O1, 2: O2, 3: O3, 4: O4);
+
+/// Time evolution calling observers after each step, generated by [observe]
+///
+/// Once an observer returns `Control::Stop`, `iterate` does nothing
+/// until `resume` is called.
+///
+/// [observe]: fn.observe.html
+#[derive(Debug, Clone)]
+pub struct Observed<TEO: TimeEvolution, O> {
+    teo: TEO,
+    observer: O,
+    step: usize,
+    stopped: bool,
+}
+
+/// Wrap EoM to call observers after each step
+///
+/// ```rust
+/// use eom::*;
+/// use eom::traits::*;
+/// use eom::adaptor::{Control, Record, StopIf};
+/// use ndarray::arr1;
+/// let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+/// let stop = StopIf::new(|_t, x: ndarray::ArrayView1<f64>| x[2] > 30.0);
+/// let mut obs = adaptor::observe(teo, (Record::every(10), stop));
+/// let mut x = arr1(&[1.0, 0.0, 0.0]);
+/// let steps = obs.run(&mut x, 10000);
+/// assert!(obs.is_stopped());
+/// assert_eq!(obs.observer().0.data().len(), steps / 10);
+/// ```
+pub fn observe<TEO, O>(teo: TEO, observer: O) -> Observed<TEO, O>
+where
+    TEO: TimeEvolution,
+    O: Observer<TEO::Scalar, TEO::Dim, TEO::Time>,
+{
+    Observed {
+        teo,
+        observer,
+        step: 0,
+        stopped: false,
+    }
+}
+
+impl<TEO, O> Observed<TEO, O>
+where
+    TEO: TimeEvolution,
+    O: Observer<TEO::Scalar, TEO::Dim, TEO::Time>,
+{
+    /// Number of steps taken so far
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Whether an observer has stopped the time evolution
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Continue the time evolution after an observer stopped it
+    pub fn resume(&mut self) {
+        self.stopped = false;
+    }
+
+    /// Reset the step count and resume
+    pub fn reset(&mut self) {
+        self.step = 0;
+        self.stopped = false;
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    pub fn inner(&self) -> &TEO {
+        &self.teo
+    }
+
+    pub fn inner_mut(&mut self) -> &mut TEO {
+        &mut self.teo
+    }
+
+    /// Decompose into the EoM and the observers
+    pub fn into_parts(self) -> (TEO, O) {
+        (self.teo, self.observer)
+    }
+
+    /// Iterate until an observer stops or `max_step` steps, and returns the number of steps taken
+    ///
+    /// Unlike `TimeEvolution::iterate`, this does not require the observers to be `Clone`.
+    pub fn run<S>(&mut self, x: &mut ArrayBase<S, TEO::Dim>, max_step: usize) -> usize
+    where
+        S: DataMut<Elem = TEO::Scalar>,
+    {
+        let mut n = 0;
+        while n < max_step && !self.stopped {
+            self.advance(x);
+            n += 1;
+        }
+        n
+    }
+
+    fn advance<S>(&mut self, x: &mut ArrayBase<S, TEO::Dim>)
+    where
+        S: DataMut<Elem = TEO::Scalar>,
+    {
+        if self.stopped {
+            return;
+        }
+        self.teo.iterate(x);
+        self.step += 1;
+        let t = self.teo.time();
+        if self.observer.observe(t, self.step, x.view()) == Control::Stop {
+            self.stopped = true;
         }
+    }
+}
+
+impl<TEO: TimeEvolution, O: Clone> ModelSpec for Observed<TEO, O> {
+    type Scalar = TEO::Scalar;
+    type Dim = TEO::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.teo.model_size()
+    }
+}
+
+impl<TEO: TimeEvolution, O> TimeStep for Observed<TEO, O> {
+    type Time = TEO::Time;
+
+    fn get_dt(&self) -> Self::Time {
+        self.teo.get_dt()
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.teo.set_dt(dt);
+    }
+
+    fn last_dt(&self) -> Self::Time {
+        self.teo.last_dt()
+    }
+}
+
+impl<TEO, O> TimeEvolution for Observed<TEO, O>
+where
+    TEO: TimeEvolution,
+    O: Observer<TEO::Scalar, TEO::Dim, TEO::Time> + Clone,
+{
+    fn time(&self) -> Self::Time {
+        self.teo.time()
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.teo.set_time(t);
+    }
+
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, TEO::Dim>,
+    ) -> &'a mut ArrayBase<S, TEO::Dim>
+    where
+        S: DataMut<Elem = TEO::Scalar>,
+    {
+        self.advance(x);
         x
     }
 }
+
+/// Observer recording `(time, state)` into `Vec`
+#[derive(Debug, Clone)]
+pub struct Record<A, D: Dimension, T> {
+    every: usize,
+    data: Vec<(T, Array<A, D>)>,
+}
+
+impl<A, D: Dimension, T> Default for Record<A, D, T> {
+    fn default() -> Self {
+        Self::every(1)
+    }
+}
+
+impl<A, D: Dimension, T> Record<A, D, T> {
+    /// Record every step
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record every `n` steps
+    pub fn every(n: usize) -> Self {
+        assert!(n > 0, "Recording interval must be positive");
+        Record {
+            every: n,
+            data: Vec::new(),
+        }
+    }
+
+    pub fn data(&self) -> &[(T, Array<A, D>)] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<(T, Array<A, D>)> {
+        self.data
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+impl<A: Clone, D: Dimension, T> Observer<A, D, T> for Record<A, D, T> {
+    fn observe(&mut self, t: T, step: usize, x: ArrayView<A, D>) -> Control {
+        if step % self.every != 0 {
+            return Control::Continue;
+        }
+        self.data.push((t, x.to_owned()));
+        Control::Continue
+    }
+}
+
+/// Observer accumulating the mean and variance of each component
+///
+/// This uses Welford's online algorithm, and the variance of a complex component
+/// is the mean of `|x - mean|^2`.
+#[derive(Debug, Clone)]
+pub struct Statistics<A: Scalar, D: Dimension> {
+    count: usize,
+    mean: Option<Array<A, D>>,
+    m2: Option<Array<A::Real, D>>,
+}
+
+impl<A: Scalar, D: Dimension> Default for Statistics<A, D> {
+    fn default() -> Self {
+        Statistics {
+            count: 0,
+            mean: None,
+            m2: None,
+        }
+    }
+}
+
+impl<A: Scalar, D: Dimension> Statistics<A, D> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of observed states
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Mean of the observed states, `None` before the first step
+    pub fn mean(&self) -> Option<&Array<A, D>> {
+        self.mean.as_ref()
+    }
+
+    /// Unbiased variance of the observed states, `None` before the second step
+    pub fn variance(&self) -> Option<Array<A::Real, D>> {
+        if self.count < 2 {
+            return None;
+        }
+        let n = A::real(self.count - 1);
+        self.m2.as_ref().map(|m2| m2.mapv(|m| m / n))
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<A: Scalar, D: Dimension, T> Observer<A, D, T> for Statistics<A, D> {
+    fn observe(&mut self, _t: T, _step: usize, x: ArrayView<A, D>) -> Control {
+        self.count += 1;
+        match (&mut self.mean, &mut self.m2) {
+            (Some(mean), Some(m2)) => {
+                let n = A::real(self.count);
+                Zip::from(mean).and(m2).and(&x).apply(|m, m2, &x| {
+                    let d = x - *m;
+                    *m += d.div_real(n);
+                    *m2 += (d.conj() * (x - *m)).re();
+                });
+            }
+            _ => {
+                self.mean = Some(x.to_owned());
+                self.m2 = Some(Array::zeros(x.raw_dim()));
+            }
+        }
+        Control::Continue
+    }
+}
+
+/// Observer stopping the time evolution when the predicate `f(t, x)` holds
+#[derive(Debug, Clone)]
+pub struct StopIf<F> {
+    f: F,
+    stopped_at: Option<usize>,
+}
+
+impl<F> StopIf<F> {
+    pub fn new(f: F) -> Self {
+        StopIf {
+            f,
+            stopped_at: None,
+        }
+    }
+
+    /// Step at which the predicate held
+    pub fn stopped_at(&self) -> Option<usize> {
+        self.stopped_at
+    }
+}
+
+impl<A, D, T, F> Observer<A, D, T> for StopIf<F>
+where
+    D: Dimension,
+    F: FnMut(T, ArrayView<A, D>) -> bool,
+{
+    fn observe(&mut self, t: T, step: usize, x: ArrayView<A, D>) -> Control {
+        if (self.f)(t, x) {
+            self.stopped_at = Some(step);
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    }
+}
+
+/// Observer stopping the time evolution when the state contains NaN or infinity
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NonFinite {
+    stopped_at: Option<usize>,
+}
+
+impl NonFinite {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Step at which a non-finite value is found
+    pub fn stopped_at(&self) -> Option<usize> {
+        self.stopped_at
+    }
+}
+
+impl<A: Scalar, D: Dimension, T> Observer<A, D, T> for NonFinite {
+    fn observe(&mut self, _t: T, step: usize, x: ArrayView<A, D>) -> Control {
+        if x.iter().all(|x| x.abs().is_finite()) {
+            Control::Continue
+        } else {
+            self.stopped_at = Some(step);
+            Control::Stop
+        }
+    }
+}
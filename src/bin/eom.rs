@@ -0,0 +1,448 @@
+//! Command-line interface to run the built-in models (requires the `cli` feature)
+//!
+//! ```text
+//! eom lorenz63 --steps 10000 > lorenz63.csv
+//! eom lorenz96 --n 40 --f 8 --output exponents --skip 10000 --steps 100000
+//! eom goy-shell --dt 1e-5 --steps 10000000 --stride 100 > goy.csv
+//! eom lorenz63 --output clv --steps 100000 > clv.csv
+//! ```
+//!
+//! The trajectory is written as CSV with the `time` column followed by the components
+//! (`x{i}.re,x{i}.im` for the real and imaginary parts of complex models as in `eom::io::Csv`),
+//! or in the other formats of `eom::io` by `--format`.
+//! Lyapunov exponents are written as CSV of one row `l0,l1,...`,
+//! and CLVs as CSV of the inner products `v{i}v{j}` between the vectors at each step.
+
+use clap::{value_t, App, AppSettings, Arg, ArgMatches, SubCommand};
+use lapack_src as _;
+use ndarray::*;
+use ndarray_linalg::*;
+use num_complex::Complex64 as c64;
+use std::error::Error;
+use std::fs::File;
+use std::io::{stdout, BufWriter, Write};
+use std::process::exit;
+
+use eom::dynamic::Registry;
+use eom::io::*;
+use eom::traits::*;
+use eom::*;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Scalar types of the built-in models
+trait Value: Element + Lapack + Scalar<Real = f64> {
+    /// Parse the initial condition, real and imaginary parts alternate for complex
+    fn from_parts(parts: &[f64], n: usize) -> Result<Array1<Self>>;
+    /// Inner product of two CLVs, its absolute value for complex
+    fn inner(a: ArrayView1<Self>, b: ArrayView1<Self>) -> f64;
+}
+
+impl Value for f64 {
+    fn from_parts(parts: &[f64], n: usize) -> Result<Array1<Self>> {
+        if parts.len() != n {
+            return Err(format!("Initial condition needs {} numbers", n).into());
+        }
+        Ok(arr1(parts))
+    }
+
+    fn inner(a: ArrayView1<Self>, b: ArrayView1<Self>) -> f64 {
+        a.dot(&b)
+    }
+}
+
+impl Value for c64 {
+    fn from_parts(parts: &[f64], n: usize) -> Result<Array1<Self>> {
+        if parts.len() != 2 * n {
+            return Err(format!("Initial condition needs {} numbers as re,im pairs", 2 * n).into());
+        }
+        Ok(parts.chunks(2).map(|c| c64::new(c[0], c[1])).collect())
+    }
+
+    fn inner(a: ArrayView1<Self>, b: ArrayView1<Self>) -> f64 {
+        a.iter()
+            .zip(b.iter())
+            .map(|(a, b)| a.conj() * b)
+            .sum::<c64>()
+            .norm()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Output {
+    Trajectory,
+    Exponents,
+    Clv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Npy,
+    Npz,
+    Chunked,
+}
+
+/// Settings common to all models
+struct Config {
+    scheme: Option<String>,
+    dt: f64,
+    steps: usize,
+    stride: usize,
+    skip: usize,
+    init: Option<Vec<f64>>,
+    output: Output,
+    format: Format,
+    out: Option<String>,
+    alpha: f64,
+}
+
+impl Config {
+    fn from_matches(m: &ArgMatches) -> Result<Self> {
+        let init = match m.value_of("init") {
+            Some(s) => Some(
+                s.split(',')
+                    .map(|v| v.trim().parse::<f64>())
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|e| format!("Invalid initial condition: {}", e))?,
+            ),
+            None => None,
+        };
+        let output = match m.value_of("output").unwrap() {
+            "trajectory" => Output::Trajectory,
+            "exponents" => Output::Exponents,
+            _ => Output::Clv,
+        };
+        let format = match m.value_of("format").unwrap() {
+            "csv" => Format::Csv,
+            "npy" => Format::Npy,
+            "npz" => Format::Npz,
+            _ => Format::Chunked,
+        };
+        let stride = value_t!(m, "stride", usize)?;
+        if stride == 0 {
+            return Err("Stride must be positive".into());
+        }
+        Ok(Config {
+            scheme: m.value_of("scheme").map(|s| s.to_string()),
+            dt: value_t!(m, "dt", f64)?,
+            steps: value_t!(m, "steps", usize)?,
+            stride,
+            skip: value_t!(m, "skip", usize)?,
+            init,
+            output,
+            format,
+            out: m.value_of("out").map(|s| s.to_string()),
+            alpha: value_t!(m, "alpha", f64)?,
+        })
+    }
+
+    fn writer(&self) -> Result<Box<dyn Write>> {
+        Ok(match &self.out {
+            Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+            None => Box::new(BufWriter::new(stdout())),
+        })
+    }
+
+    /// File for the formats which rewrite their headers
+    fn file(&self) -> Result<File> {
+        match &self.out {
+            Some(path) => Ok(File::create(path)?),
+            None => Err(format!("--out is required for {:?} format", self.format).into()),
+        }
+    }
+}
+
+/// Run the model `f` by the scheme chosen from `registry`
+fn run<F>(
+    f: F,
+    registry: Registry<F>,
+    default_scheme: &str,
+    names: Vec<String>,
+    x0: Array1<F::Scalar>,
+    cfg: &Config,
+) -> Result<()>
+where
+    F: ModelSpec<Dim = Ix1> + 'static,
+    F::Scalar: Value,
+{
+    let name = cfg.scheme.as_deref().unwrap_or(default_scheme);
+    let mut teo = registry.build(name, f, cfg.dt).map_err(|e| {
+        let names: Vec<_> = registry.names().collect();
+        format!("{} (available: {})", e, names.join(", "))
+    })?;
+    let mut x = match &cfg.init {
+        Some(parts) => F::Scalar::from_parts(parts, x0.len())?,
+        None => x0,
+    };
+    teo.try_iterate_n(&mut x, cfg.skip)?;
+
+    match cfg.output {
+        Output::Trajectory => {
+            let stride = cfg.stride;
+            let ts = adaptor::time_series(x, &mut teo).timed();
+            let ts = ts
+                .take(cfg.steps)
+                .enumerate()
+                .filter(|(i, _)| (i + 1) % stride == 0)
+                .map(|(_, tx)| tx);
+            match cfg.format {
+                Format::Csv => {
+                    let mut w = Csv::new(cfg.writer()?).header("time", &names);
+                    w.write_all(ts)?;
+                    w.finish()?;
+                }
+                Format::Npy => {
+                    let mut w = Npy::new(BufWriter::new(cfg.file()?))?;
+                    w.write_all(ts)?;
+                    w.finish()?;
+                }
+                Format::Npz => {
+                    let mut w = Npz::new(BufWriter::new(cfg.file()?))?;
+                    w.write_all(ts)?;
+                    w.finish()?;
+                }
+                Format::Chunked => {
+                    let mut w = Chunked::new(cfg.writer()?);
+                    w.write_all(ts)?;
+                    w.finish()?;
+                }
+            }
+        }
+        Output::Exponents => {
+            let l = lyapunov::try_exponents(teo, x, cfg.alpha, cfg.steps)?;
+            let mut w = cfg.writer()?;
+            let header: Vec<_> = (0..l.len()).map(|i| format!("l{}", i)).collect();
+            writeln!(w, "{}", header.join(","))?;
+            let values: Vec<_> = l.iter().map(|l| l.to_string()).collect();
+            writeln!(w, "{}", values.join(","))?;
+            w.flush()?;
+        }
+        Output::Clv => {
+            let clv = lyapunov::try_vectors(teo, x, cfg.alpha, cfg.steps)?;
+            let n = clv.first().map(|c| c.1.ncols()).unwrap_or(0);
+            let pairs: Vec<_> = (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                .collect();
+            let mut w = cfg.writer()?;
+            let header: Vec<_> = pairs.iter().map(|(i, j)| format!("v{}v{}", i, j)).collect();
+            writeln!(w, "{}", header.join(","))?;
+            for (k, (_x, v, _f)) in clv.iter().enumerate() {
+                if (k + 1) % cfg.stride != 0 {
+                    continue;
+                }
+                let values: Vec<_> = pairs
+                    .iter()
+                    .map(|&(i, j)| F::Scalar::inner(v.column(i), v.column(j)).to_string())
+                    .collect();
+                writeln!(w, "{}", values.join(","))?;
+            }
+            w.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn param(m: &ArgMatches, name: &str, default: f64) -> Result<f64> {
+    match m.value_of(name) {
+        Some(v) => v
+            .parse()
+            .map_err(|e| format!("Invalid --{}: {}", name, e).into()),
+        None => Ok(default),
+    }
+}
+
+fn size_param(m: &ArgMatches, name: &str, default: usize) -> Result<usize> {
+    match m.value_of(name) {
+        Some(v) => v
+            .parse()
+            .map_err(|e| format!("Invalid --{}: {}", name, e).into()),
+        None => Ok(default),
+    }
+}
+
+fn xyz() -> Vec<String> {
+    vec!["x".into(), "y".into(), "z".into()]
+}
+
+fn dispatch(matches: &ArgMatches) -> Result<()> {
+    let (model, m) = matches.subcommand();
+    let m = m.unwrap();
+    let cfg = Config::from_matches(m)?;
+    match model {
+        "lorenz63" => {
+            let d = ode::Lorenz63::default();
+            let f = ode::Lorenz63::new(
+                param(m, "p", d.p)?,
+                param(m, "r", d.r)?,
+                param(m, "b", d.b)?,
+            );
+            let registry = Registry::new()
+                .with_explicit()
+                .with_semi_implicit()
+                .with_implicit();
+            run(f, registry, "rk4", xyz(), arr1(&[1.0, 0.0, 0.0]), &cfg)
+        }
+        "lorenz96" => {
+            let d = ode::Lorenz96::default();
+            let f = ode::Lorenz96 {
+                f: param(m, "f", d.f)?,
+                n: size_param(m, "n", d.n)?,
+            };
+            if f.n < 2 {
+                return Err("Lorenz96 needs two or more variables".into());
+            }
+            let names = (0..f.n).map(|i| format!("x{}", i)).collect();
+            // small perturbation of the fixed point
+            let mut x0 = Array::from_elem(f.n, f.f);
+            x0[0] += 0.01;
+            let registry = Registry::new().with_explicit().with_implicit();
+            run(f, registry, "rk4", names, x0, &cfg)
+        }
+        "roessler" => {
+            let d = ode::Roessler::default();
+            let f = ode::Roessler {
+                a: param(m, "a", d.a)?,
+                b: param(m, "b", d.b)?,
+                c: param(m, "c", d.c)?,
+            };
+            let registry = Registry::new().with_explicit().with_implicit();
+            run(f, registry, "rk4", xyz(), arr1(&[1.0, 0.0, 0.0]), &cfg)
+        }
+        "goy-shell" => {
+            let d = ode::GoyShell::default();
+            let f = ode::GoyShell {
+                size: size_param(m, "size", d.size)?,
+                nu: param(m, "nu", d.nu)?,
+                e: param(m, "e", d.e)?,
+                k0: param(m, "k0", d.k0)?,
+                f: param(m, "f", d.f)?,
+                f_idx: size_param(m, "f-idx", d.f_idx)?,
+            };
+            if f.size < 3 {
+                return Err("GOY shell model needs three or more shells".into());
+            }
+            if f.f_idx >= f.size {
+                return Err("Forced shell must be one of the shells".into());
+            }
+            let names = (0..f.size).map(|i| format!("x{}", i)).collect();
+            let mut x0 = Array::zeros(f.size);
+            for i in 2..7.min(f.size) {
+                x0[i] = c64::new(1.0, 0.0);
+            }
+            let registry = Registry::new().with_semi_implicit();
+            run(f, registry, "diag_rk4", names, x0, &cfg)
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn param_arg<'a, 'b>(name: &'a str, help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name(name)
+        .long(name)
+        .takes_value(true)
+        .value_name("VALUE")
+        .help(help)
+}
+
+fn app<'a, 'b>() -> App<'a, 'b> {
+    let common = vec![
+        Arg::with_name("scheme")
+            .long("scheme")
+            .takes_value(true)
+            .help("Name of the scheme, e.g. rk4, dormand_prince, diag_rk4, radau_iia [default: rk4, diag_rk4 for goy-shell]"),
+        Arg::with_name("dt")
+            .long("dt")
+            .takes_value(true)
+            .default_value("0.01")
+            .help("Time-step"),
+        Arg::with_name("steps")
+            .long("steps")
+            .takes_value(true)
+            .default_value("10000")
+            .help("Number of steps"),
+        Arg::with_name("stride")
+            .long("stride")
+            .takes_value(true)
+            .default_value("1")
+            .help("Write every n-th step"),
+        Arg::with_name("skip")
+            .long("skip")
+            .takes_value(true)
+            .default_value("0")
+            .help("Number of transient steps discarded before the output"),
+        Arg::with_name("init")
+            .long("init")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+            .help("Initial condition separated by commas, re,im pairs for complex models"),
+        Arg::with_name("output")
+            .long("output")
+            .takes_value(true)
+            .possible_values(&["trajectory", "exponents", "clv"])
+            .default_value("trajectory")
+            .help("Quantity to be written"),
+        Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["csv", "npy", "npz", "chunked"])
+            .default_value("csv")
+            .help("Format of the trajectory"),
+        Arg::with_name("out")
+            .long("out")
+            .short("o")
+            .takes_value(true)
+            .help("Output file [default: stdout]"),
+        Arg::with_name("alpha")
+            .long("alpha")
+            .takes_value(true)
+            .default_value("1e-7")
+            .help("Size of the perturbation for Lyapunov analysis"),
+    ];
+    App::new("eom")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Run the built-in models of eom")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("lorenz63")
+                .about("Lorenz 63 model")
+                .args(&common)
+                .arg(param_arg("p", "Prandtl number [default: 10]"))
+                .arg(param_arg("r", "Rayleigh number [default: 28]"))
+                .arg(param_arg("b", "Aspect ratio [default: 8/3]")),
+        )
+        .subcommand(
+            SubCommand::with_name("lorenz96")
+                .about("Lorenz 96 model")
+                .args(&common)
+                .arg(param_arg("f", "Forcing [default: 8]"))
+                .arg(param_arg("n", "Number of variables [default: 40]")),
+        )
+        .subcommand(
+            SubCommand::with_name("roessler")
+                .about("Roessler system")
+                .args(&common)
+                .arg(param_arg("a", "[default: 0.2]"))
+                .arg(param_arg("b", "[default: 0.2]"))
+                .arg(param_arg("c", "[default: 5.7]")),
+        )
+        .subcommand(
+            SubCommand::with_name("goy-shell")
+                .about("GOY shell model")
+                .args(&common)
+                .arg(param_arg("size", "Number of shells [default: 27]"))
+                .arg(param_arg("nu", "Viscosity [default: 1e-9]"))
+                .arg(param_arg("e", "Energy transfer parameter [default: 0.5]"))
+                .arg(param_arg("k0", "Smallest wavenumber [default: 0.0625]"))
+                .arg(param_arg("f", "Forcing [default: 5e-3]"))
+                .arg(param_arg("f-idx", "Forced shell [default: 4]")),
+        )
+}
+
+fn main() {
+    let matches = app().get_matches();
+    if let Err(e) = dispatch(&matches) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
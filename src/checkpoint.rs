@@ -0,0 +1,73 @@
+//! Checkpoint and restart of time evolution (requires the `serde` feature)
+//!
+//! A [Checkpoint] captures the scheme, i.e. the model parameters, time-step, current time
+//! and the internal buffers of the scheme, together with the state.
+//! Stochastic schemes are restored at the same position of the noise stream
+//! when they use the default generator [ChaChaRng];
+//! other generators must implement `Serialize` and `Deserialize` themselves.
+//! Resuming from a checkpoint reproduces the uninterrupted run bitwise
+//! as long as the serialization format keeps floating point numbers exactly.
+//!
+//! [Checkpoint]: struct.Checkpoint.html
+//! [ChaChaRng]: ../stochastic/struct.ChaChaRng.html
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::traits::*;
+//! use eom::checkpoint::Checkpoint;
+//! use ndarray::*;
+//! let mut teo = explicit::RK4::new(ode::Lorenz96::default(), 0.01);
+//! let mut x = Array::from_shape_fn(teo.model_size(), |i| (i as f64).sin());
+//! teo.iterate_n(&mut x, 100);
+//!
+//! let json = serde_json::to_string(&Checkpoint::new(&teo, &x)).unwrap();
+//! let cp: Checkpoint<explicit::RK4<ode::Lorenz96>> = serde_json::from_str(&json).unwrap();
+//! let (mut teo, mut x) = cp.restore();
+//! teo.iterate_n(&mut x, 100);
+//! ```
+
+use ndarray::*;
+use serde::{Deserialize, Serialize};
+
+use crate::traits::*;
+
+/// Snapshot of a scheme and a state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "TEO: Serialize, TEO::Scalar: Serialize, TEO::Dim: Serialize",
+    deserialize = "TEO: Deserialize<'de>, TEO::Scalar: Deserialize<'de>, TEO::Dim: Deserialize<'de>"
+))]
+pub struct Checkpoint<TEO: ModelSpec> {
+    /// scheme including the model, time-step and current time
+    pub scheme: TEO,
+    /// state at the current time of the scheme
+    pub state: Array<TEO::Scalar, TEO::Dim>,
+}
+
+impl<TEO: TimeEvolution> Checkpoint<TEO> {
+    /// Capture the scheme and the state `x` at the current time
+    pub fn new<S>(teo: &TEO, x: &ArrayBase<S, TEO::Dim>) -> Self
+    where
+        S: Data<Elem = TEO::Scalar>,
+    {
+        Checkpoint {
+            scheme: teo.clone(),
+            state: x.to_owned(),
+        }
+    }
+
+    /// Time of the checkpoint
+    pub fn time(&self) -> TEO::Time {
+        self.scheme.time()
+    }
+
+    /// Time-step of the scheme
+    pub fn dt(&self) -> TEO::Time {
+        self.scheme.get_dt()
+    }
+
+    /// Scheme and state to resume the time evolution
+    pub fn restore(self) -> (TEO, Array<TEO::Scalar, TEO::Dim>) {
+        (self.scheme, self.state)
+    }
+}
@@ -0,0 +1,99 @@
+//! Continuous interpolation of the solution within a step
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::One;
+
+/// Cubic Hermite interpolant of a step from `(t0, x0)` to `(t1, x1)`
+///
+/// This uses the states and their time derivatives `f0, f1` at both ends,
+/// and is accurate to the third order in the step size.
+#[derive(Debug, Clone)]
+pub struct Hermite<A: Scalar, D: Dimension> {
+    t0: A::Real,
+    t1: A::Real,
+    x0: Array<A, D>,
+    x1: Array<A, D>,
+    f0: Array<A, D>,
+    f1: Array<A, D>,
+}
+
+impl<A: Scalar, D: Dimension> Hermite<A, D> {
+    pub fn new(
+        t0: A::Real,
+        x0: Array<A, D>,
+        f0: Array<A, D>,
+        t1: A::Real,
+        x1: Array<A, D>,
+        f1: Array<A, D>,
+    ) -> Self {
+        Hermite {
+            t0,
+            t1,
+            x0,
+            x1,
+            f0,
+            f1,
+        }
+    }
+
+    /// Time range `(t0, t1)` of the step
+    pub fn range(&self) -> (A::Real, A::Real) {
+        (self.t0, self.t1)
+    }
+
+    /// Interpolated state at `t`
+    pub fn eval(&self, t: A::Real) -> Array<A, D> {
+        let mut x = Array::zeros(self.x0.raw_dim());
+        self.eval_into(t, &mut x);
+        x
+    }
+
+    /// Write the interpolated state at `t` into `x`
+    pub fn eval_into<S>(&self, t: A::Real, x: &mut ArrayBase<S, D>)
+    where
+        S: DataMut<Elem = A>,
+    {
+        let h = self.t1 - self.t0;
+        cubic_hermite(
+            (t - self.t0) / h,
+            h,
+            &self.x0,
+            &self.f0,
+            &self.x1,
+            &self.f1,
+            x,
+        );
+    }
+}
+
+/// Cubic Hermite interpolation at `t0 + s h` of the step of size `h` into `x`
+pub(crate) fn cubic_hermite<A, S, D>(
+    s: A::Real,
+    h: A::Real,
+    x0: &Array<A, D>,
+    f0: &Array<A, D>,
+    x1: &Array<A, D>,
+    f1: &Array<A, D>,
+    x: &mut ArrayBase<S, D>,
+) where
+    A: Scalar,
+    S: DataMut<Elem = A>,
+    D: Dimension,
+{
+    let one = A::Real::one();
+    let two = one + one;
+    let three = two + one;
+    let h00 = (one + two * s) * (one - s) * (one - s);
+    let h01 = s * s * (three - two * s);
+    let h10 = s * (one - s) * (one - s) * h;
+    let h11 = s * s * (s - one) * h;
+    Zip::from(x)
+        .and(x0)
+        .and(x1)
+        .and(f0)
+        .and(f1)
+        .apply(|x, &x0, &x1, &f0, &f1| {
+            *x = x0.mul_real(h00) + x1.mul_real(h01) + f0.mul_real(h10) + f1.mul_real(h11);
+        });
+}
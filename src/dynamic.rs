@@ -0,0 +1,291 @@
+//! Object-safe interface of time-evolution operators to choose schemes at runtime
+//!
+//! `TimeEvolution` cannot be a trait object since `iterate` is generic over the storage.
+//! [DynTimeEvolution] operates on `ArrayViewMut` instead, and is implemented for every
+//! `TimeEvolution`. The boxed trait object [BoxedScheme] implements `TimeEvolution` in turn,
+//! and thus can be used with adaptors and Lyapunov analysis.
+//!
+//! [DynTimeEvolution]: trait.DynTimeEvolution.html
+//! [BoxedScheme]: type.BoxedScheme.html
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::traits::*;
+//! use eom::dynamic::Registry;
+//! use ndarray::arr1;
+//! let registry = Registry::new().with_explicit().with_semi_implicit();
+//! for name in &["euler", "rk4", "diag_rk4"] {
+//!     let mut teo = registry.build(name, ode::Lorenz63::default(), 0.01).unwrap();
+//!     let x = adaptor::iterate(&mut teo, arr1(&[1.0, 0.0, 0.0]), 100);
+//!     println!("{}: {}", name, x);
+//! }
+//! ```
+
+use ndarray::*;
+use ndarray_linalg::*;
+
+use crate::error::{Error, Result};
+use crate::traits::*;
+use crate::*;
+
+/// Object-safe version of `TimeEvolution`
+///
+/// The methods are prefixed by `dyn_` to avoid the ambiguity with `TimeEvolution`.
+/// Use them through `TimeEvolution` implemented for [BoxedScheme].
+///
+/// [BoxedScheme]: type.BoxedScheme.html
+pub trait DynTimeEvolution<A: Scalar, D: Dimension> {
+    fn dyn_model_size(&self) -> D::Pattern;
+    fn dyn_time(&self) -> A::Real;
+    fn dyn_set_time(&mut self, t: A::Real);
+    fn dyn_get_dt(&self) -> A::Real;
+    fn dyn_set_dt(&mut self, dt: A::Real);
+    fn dyn_last_dt(&self) -> A::Real;
+    fn dyn_iterate(&mut self, x: ArrayViewMut<A, D>);
+    fn dyn_try_iterate(&mut self, x: ArrayViewMut<A, D>) -> Result<()>;
+    fn dyn_clone(&self) -> BoxedScheme<A, D>;
+}
+
+/// Boxed time-evolution operator
+pub type BoxedScheme<A, D> = Box<dyn DynTimeEvolution<A, D>>;
+
+impl<A, D, TEO> DynTimeEvolution<A, D> for TEO
+where
+    A: Scalar,
+    D: Dimension,
+    TEO: TimeEvolution<Scalar = A, Dim = D, Time = A::Real> + 'static,
+{
+    fn dyn_model_size(&self) -> D::Pattern {
+        self.model_size()
+    }
+
+    fn dyn_time(&self) -> A::Real {
+        self.time()
+    }
+
+    fn dyn_set_time(&mut self, t: A::Real) {
+        self.set_time(t)
+    }
+
+    fn dyn_get_dt(&self) -> A::Real {
+        self.get_dt()
+    }
+
+    fn dyn_set_dt(&mut self, dt: A::Real) {
+        self.set_dt(dt)
+    }
+
+    fn dyn_last_dt(&self) -> A::Real {
+        self.last_dt()
+    }
+
+    fn dyn_iterate(&mut self, mut x: ArrayViewMut<A, D>) {
+        self.iterate(&mut x);
+    }
+
+    fn dyn_try_iterate(&mut self, mut x: ArrayViewMut<A, D>) -> Result<()> {
+        self.try_iterate(&mut x)?;
+        Ok(())
+    }
+
+    fn dyn_clone(&self) -> BoxedScheme<A, D> {
+        Box::new(self.clone())
+    }
+}
+
+// `(**self)` dispatches to the boxed scheme. `self.dyn_*` would call the blanket impl
+// for the box itself, which calls back this impl.
+impl<A: Scalar, D: Dimension> Clone for BoxedScheme<A, D> {
+    fn clone(&self) -> Self {
+        (**self).dyn_clone()
+    }
+}
+
+impl<A: Scalar, D: Dimension> ModelSpec for BoxedScheme<A, D> {
+    type Scalar = A;
+    type Dim = D;
+    fn model_size(&self) -> D::Pattern {
+        (**self).dyn_model_size()
+    }
+}
+
+impl<A: Scalar, D: Dimension> TimeStep for BoxedScheme<A, D> {
+    type Time = A::Real;
+
+    fn get_dt(&self) -> A::Real {
+        (**self).dyn_get_dt()
+    }
+
+    fn set_dt(&mut self, dt: A::Real) {
+        (**self).dyn_set_dt(dt)
+    }
+
+    fn last_dt(&self) -> A::Real {
+        (**self).dyn_last_dt()
+    }
+}
+
+impl<A: Scalar, D: Dimension> TimeEvolution for BoxedScheme<A, D> {
+    fn time(&self) -> A::Real {
+        (**self).dyn_time()
+    }
+
+    fn set_time(&mut self, t: A::Real) {
+        (**self).dyn_set_time(t)
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, D>) -> &'a mut ArrayBase<S, D>
+    where
+        S: DataMut<Elem = A>,
+    {
+        (**self).dyn_iterate(x.view_mut());
+        x
+    }
+
+    fn try_iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, D>) -> Result<&'a mut ArrayBase<S, D>>
+    where
+        S: DataMut<Elem = A>,
+    {
+        (**self).dyn_try_iterate(x.view_mut())?;
+        Ok(x)
+    }
+}
+
+/// Constructor of a boxed scheme from a model and time-step
+pub type Constructor<F> = fn(
+    F,
+    <<F as ModelSpec>::Scalar as Scalar>::Real,
+) -> BoxedScheme<<F as ModelSpec>::Scalar, <F as ModelSpec>::Dim>;
+
+/// Table of schemes for the model `F` looked up by name
+///
+/// The schemes available depend on the traits the model implements,
+/// and are added by `with_explicit`, `with_semi_implicit`, `with_implicit` and `with_symplectic`.
+#[derive(Clone)]
+pub struct Registry<F: ModelSpec> {
+    entries: Vec<(String, Constructor<F>)>,
+}
+
+impl<F: ModelSpec> Default for Registry<F> {
+    fn default() -> Self {
+        Registry {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<F: ModelSpec> Registry<F> {
+    /// Empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a scheme, or replace the scheme of the same name
+    pub fn register(&mut self, name: &str, constructor: Constructor<F>) -> &mut Self {
+        match self.entries.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = constructor,
+            None => self.entries.push((name.to_string(), constructor)),
+        }
+        self
+    }
+
+    fn with(mut self, entries: &[(&str, Constructor<F>)]) -> Self {
+        for &(name, constructor) in entries {
+            self.register(name, constructor);
+        }
+        self
+    }
+
+    /// Names of the registered schemes
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Build the scheme of `name` for the model `f` with the time-step `dt`
+    pub fn build(
+        &self,
+        name: &str,
+        f: F,
+        dt: <F::Scalar as Scalar>::Real,
+    ) -> Result<BoxedScheme<F::Scalar, F::Dim>> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, constructor)| constructor(f, dt))
+            .ok_or_else(|| Error::UnknownScheme(name.to_string()))
+    }
+}
+
+impl<F: ExplicitT + 'static> Registry<F> {
+    /// Add `euler`, `heun`, `rk4`, `dormand_prince`, `cash_karp` and `bogacki_shampine`
+    pub fn with_explicit(self) -> Self {
+        self.with(&[
+            ("euler", |f, dt| Box::new(explicit::Euler::new(f, dt))),
+            ("heun", |f, dt| Box::new(explicit::Heun::new(f, dt))),
+            ("rk4", |f, dt| Box::new(explicit::RK4::new(f, dt))),
+            ("dormand_prince", |f, dt| {
+                Box::new(adaptive::DormandPrince::new(f, dt))
+            }),
+            ("cash_karp", |f, dt| {
+                Box::new(adaptive::CashKarp::new(f, dt))
+            }),
+            ("bogacki_shampine", |f, dt| {
+                Box::new(adaptive::BogackiShampine::new(f, dt))
+            }),
+        ])
+    }
+}
+
+impl<F: SemiImplicitT + 'static> Registry<F> {
+    /// Add `diag_rk4`, `etd1`, `etdrk2` and `etdrk4`
+    pub fn with_semi_implicit(self) -> Self {
+        self.with(&[
+            ("diag_rk4", |f, dt| {
+                Box::new(semi_implicit::DiagRK4::new(f, dt))
+            }),
+            ("etd1", |f, dt| Box::new(semi_implicit::ETD1::new(f, dt))),
+            ("etdrk2", |f, dt| {
+                Box::new(semi_implicit::ETDRK2::new(f, dt))
+            }),
+            ("etdrk4", |f, dt| {
+                Box::new(semi_implicit::ETDRK4::new(f, dt))
+            }),
+        ])
+    }
+}
+
+impl<F> Registry<F>
+where
+    F: ExplicitT<Dim = Ix1> + 'static,
+    F::Scalar: Lapack,
+{
+    /// Add `backward_euler`, `bdf2`, ..., `bdf5` and `radau_iia`
+    pub fn with_implicit(self) -> Self {
+        use crate::implicit::*;
+        self.with(&[
+            ("backward_euler", |f, dt| {
+                Box::new(BackwardEuler::<F>::new(f, dt))
+            }),
+            ("bdf2", |f, dt| Box::new(BDF2::<F>::new(f, dt))),
+            ("bdf3", |f, dt| Box::new(BDF3::<F>::new(f, dt))),
+            ("bdf4", |f, dt| Box::new(BDF4::<F>::new(f, dt))),
+            ("bdf5", |f, dt| Box::new(BDF5::<F>::new(f, dt))),
+            ("radau_iia", |f, dt| Box::new(RadauIIA::<F>::new(f, dt))),
+        ])
+    }
+}
+
+impl<F: Hamiltonian + 'static> Registry<F> {
+    /// Add `stormer_verlet`, `yoshida4`, `yoshida6` and `forest_ruth`
+    pub fn with_symplectic(self) -> Self {
+        use crate::symplectic::*;
+        self.with(&[
+            ("stormer_verlet", |f, dt| {
+                Box::new(StormerVerlet::new(f, dt))
+            }),
+            ("yoshida4", |f, dt| Box::new(Yoshida4::new(f, dt))),
+            ("yoshida6", |f, dt| Box::new(Yoshida6::new(f, dt))),
+            ("forest_ruth", |f, dt| Box::new(ForestRuth::new(f, dt))),
+        ])
+    }
+}
@@ -0,0 +1,201 @@
+//! Ensemble of trajectories evolved together
+//!
+//! The states of the members are stored as rows of an `Array2`.
+//! With the `rayon` feature, the members are advanced in parallel.
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::traits::*;
+//! use eom::ensemble::Ensemble;
+//! use ndarray::*;
+//! let teo = explicit::RK4::new(ode::Lorenz96::default(), 0.01);
+//! let n = teo.model_size();
+//! // 100 members slightly perturbed from each other
+//! let x0 = Array::from_shape_fn((100, n), |(m, i)| (i as f64).sin() + 1e-3 * m as f64);
+//! let mut ens = Ensemble::new(teo, x0);
+//! ens.iterate_n(100);
+//! println!("spread = {}", ens.spread());
+//! ```
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::Zero;
+
+use crate::traits::*;
+
+/// Ensemble of trajectories of the same time-evolution operator
+///
+/// Each member owns a clone of the scheme,
+/// thus members of adaptive schemes may have different time-steps.
+#[derive(Debug, Clone)]
+pub struct Ensemble<TEO: ModelSpec> {
+    teo: Vec<TEO>,
+    x: Array2<TEO::Scalar>,
+}
+
+impl<TEO> Ensemble<TEO>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+{
+    /// Ensemble whose members start from the rows of `x0`, which must have one row at least
+    pub fn new(teo: TEO, x0: Array2<TEO::Scalar>) -> Self {
+        assert!(x0.nrows() > 0, "Ensemble must have one member at least");
+        assert_eq!(
+            x0.ncols(),
+            teo.model_size(),
+            "Each row of the ensemble must be a state"
+        );
+        let x = x0.as_standard_layout().into_owned();
+        let teo = vec![teo; x.nrows()];
+        Ensemble { teo, x }
+    }
+
+    /// Number of members
+    pub fn len(&self) -> usize {
+        self.x.nrows()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// States of the members as rows
+    pub fn states(&self) -> &Array2<TEO::Scalar> {
+        &self.x
+    }
+
+    pub fn states_mut(&mut self) -> ArrayViewMut2<'_, TEO::Scalar> {
+        self.x.view_mut()
+    }
+
+    pub fn into_states(self) -> Array2<TEO::Scalar> {
+        self.x
+    }
+
+    /// State of the `i`-th member
+    pub fn member(&self, i: usize) -> ArrayView1<'_, TEO::Scalar> {
+        self.x.row(i)
+    }
+
+    /// Scheme of the `i`-th member
+    pub fn scheme(&self, i: usize) -> &TEO {
+        &self.teo[i]
+    }
+
+    /// Simulation time of the first member
+    pub fn time(&self) -> TEO::Time {
+        self.teo[0].time()
+    }
+
+    /// Ensemble mean
+    pub fn mean(&self) -> Array1<TEO::Scalar> {
+        mean(&self.x)
+    }
+
+    /// Ensemble spread
+    pub fn spread(&self) -> <TEO::Scalar as Scalar>::Real {
+        spread(&self.x)
+    }
+
+    /// Ensemble covariance matrix
+    pub fn covariance(&self) -> Array2<TEO::Scalar> {
+        covariance(&self.x)
+    }
+}
+
+#[cfg(not(feature = "rayon"))]
+impl<TEO> Ensemble<TEO>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+{
+    /// Advance all members by one step
+    pub fn iterate(&mut self) -> &Array2<TEO::Scalar> {
+        for (teo, mut x) in self.teo.iter_mut().zip(self.x.outer_iter_mut()) {
+            teo.iterate(&mut x);
+        }
+        &self.x
+    }
+
+    /// Advance all members by `n` steps
+    pub fn iterate_n(&mut self, n: usize) -> &Array2<TEO::Scalar> {
+        for (teo, mut x) in self.teo.iter_mut().zip(self.x.outer_iter_mut()) {
+            teo.iterate_n(&mut x, n);
+        }
+        &self.x
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<TEO> Ensemble<TEO>
+where
+    TEO: TimeEvolution<Dim = Ix1> + Send,
+    TEO::Scalar: Send,
+{
+    /// Advance all members by one step in parallel
+    pub fn iterate(&mut self) -> &Array2<TEO::Scalar> {
+        self.iterate_n(1)
+    }
+
+    /// Advance all members by `n` steps in parallel
+    pub fn iterate_n(&mut self, n: usize) -> &Array2<TEO::Scalar> {
+        use rayon::prelude::*;
+        let size = self.x.ncols();
+        if size > 0 {
+            // the states are kept in the standard layout in `new`
+            let x = self.x.as_slice_mut().unwrap();
+            x.par_chunks_mut(size)
+                .zip(self.teo.par_iter_mut())
+                .for_each(|(x, teo)| {
+                    teo.iterate_n(&mut aview_mut1(x), n);
+                });
+        }
+        &self.x
+    }
+}
+
+/// Mean of the rows of `x`
+pub fn mean<A, S>(x: &ArrayBase<S, Ix2>) -> Array1<A>
+where
+    A: Scalar,
+    S: Data<Elem = A>,
+{
+    let n = A::real(x.nrows() as f64);
+    x.sum_axis(Axis(0)).mapv_into(|a| a.div_real(n))
+}
+
+/// Unbiased covariance matrix of the rows of `x`
+///
+/// The `(i, j)` component is `sum_m (x_mi - m_i) (x_mj - m_j)^* / (N - 1)`
+/// for `N` members with the mean `m`.
+pub fn covariance<A, S>(x: &ArrayBase<S, Ix2>) -> Array2<A>
+where
+    A: Scalar,
+    S: Data<Elem = A>,
+{
+    assert!(x.nrows() > 1, "Covariance needs two or more members");
+    let dev = x - &mean(x);
+    let n = A::real((x.nrows() - 1) as f64);
+    dev.t()
+        .dot(&dev.mapv(|a| a.conj()))
+        .mapv_into(|a| a.div_real(n))
+}
+
+/// Spread of the rows of `x`, i.e. the square root of the trace of the covariance per component
+pub fn spread<A, S>(x: &ArrayBase<S, Ix2>) -> A::Real
+where
+    A: Scalar,
+    S: Data<Elem = A>,
+{
+    assert!(x.nrows() > 1, "Spread needs two or more members");
+    let m = mean(x);
+    let sq = x
+        .outer_iter()
+        .map(|row| {
+            Zip::from(&row)
+                .and(&m)
+                .fold(A::Real::zero(), |acc, &a, &b| acc + (a - b).square())
+        })
+        .fold(A::Real::zero(), |acc, s| acc + s);
+    let n = A::real(((x.nrows() - 1) * x.ncols()) as f64);
+    (sq / n).sqrt()
+}
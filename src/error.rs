@@ -0,0 +1,64 @@
+//! Error type of fallible operations
+
+use ndarray_linalg::error::LinalgError;
+use std::fmt;
+
+use crate::implicit::NewtonError;
+
+/// Error in time evolution and Lyapunov analysis
+#[derive(Debug)]
+pub enum Error {
+    /// the state contains NaN or infinity at the time
+    NonFinite { time: f64 },
+    /// failure of linear algebra, e.g. QR decomposition or triangular solve
+    Linalg(LinalgError),
+    /// failure of the Newton iteration in implicit schemes
+    Newton(NewtonError),
+    /// no scheme of the name is registered
+    UnknownScheme(String),
+    /// failure of reading or writing the buffer, e.g. in the checkpointed CLV
+    Io(std::io::Error),
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NonFinite { time } => write!(f, "State is not finite at t = {}", time),
+            Error::Linalg(e) => write!(f, "Linear algebra failed: {}", e),
+            Error::Newton(e) => write!(f, "{}", e),
+            Error::UnknownScheme(name) => write!(f, "Unknown scheme: {}", name),
+            Error::Io(e) => write!(f, "I/O failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::NonFinite { .. } | Error::UnknownScheme(_) => None,
+            Error::Linalg(e) => Some(e),
+            Error::Newton(e) => Some(e),
+            Error::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<LinalgError> for Error {
+    fn from(e: LinalgError) -> Self {
+        Error::Linalg(e)
+    }
+}
+
+impl From<NewtonError> for Error {
+    fn from(e: NewtonError) -> Self {
+        Error::Newton(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,414 @@
+//! Detection of zero crossings of event functions during time evolution
+//!
+//! An event is a scalar function `g(t, x)` of the time and state.
+//! After each step, the sign of `g` at both ends of the step is compared,
+//! and a crossing is located by the Illinois method on an interpolant of the step.
+//! [detect] uses the cubic Hermite interpolant (see [dense::Hermite]) of explicit schemes,
+//! and [detect_states] uses the cubic polynomial interpolating the last four states
+//! for any `TimeEvolution`, e.g. semi-implicit, implicit and stochastic schemes or adaptors.
+//!
+//! [detect]: fn.detect.html
+//! [detect_states]: fn.detect_states.html
+//! [dense::Hermite]: ../dense/struct.Hermite.html
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::traits::*;
+//! use eom::event::*;
+//! use ndarray::*;
+//! let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+//! // upward crossings of the plane z = 27
+//! let section = Event::new(|_t, x: ArrayView1<f64>| x[2] - 27.0).direction(Direction::Rising);
+//! for oc in event::detect(arr1(&[1.0, 0.0, 0.0]), &mut teo, vec![section]).until(10.0) {
+//!     assert!((oc.x[2] - 27.0).abs() < 1e-6);
+//! }
+//! ```
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::Float;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use crate::dense::Hermite;
+use crate::poincare::{weights, POINTS};
+use crate::traits::*;
+
+/// Direction of zero crossings to be detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// both directions
+    Both,
+    /// `g` changes from negative to non-negative
+    Rising,
+    /// `g` changes from positive to non-positive
+    Falling,
+}
+
+impl Direction {
+    pub(crate) fn matches<T: Float>(self, g0: T, g1: T) -> bool {
+        let rising = g0 < T::zero() && g1 >= T::zero();
+        let falling = g0 > T::zero() && g1 <= T::zero();
+        match self {
+            Direction::Both => rising || falling,
+            Direction::Rising => rising,
+            Direction::Falling => falling,
+        }
+    }
+}
+
+type EventFn<'g, A, D> =
+    Box<dyn FnMut(<A as Scalar>::Real, ArrayView<A, D>) -> <A as Scalar>::Real + 'g>;
+
+/// Event function `g(t, x)` with its direction filter and terminal flag
+pub struct Event<'g, A: Scalar, D: Dimension> {
+    g: EventFn<'g, A, D>,
+    direction: Direction,
+    terminal: bool,
+}
+
+impl<'g, A: Scalar, D: Dimension> Event<'g, A, D> {
+    /// Non-terminal event detected in both directions
+    pub fn new<G>(g: G) -> Self
+    where
+        G: FnMut(A::Real, ArrayView<A, D>) -> A::Real + 'g,
+    {
+        Event {
+            g: Box::new(g),
+            direction: Direction::Both,
+            terminal: false,
+        }
+    }
+
+    /// Detect crossings only in the given direction
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Stop the time evolution at the first occurrence of this event
+    pub fn terminal(mut self, terminal: bool) -> Self {
+        self.terminal = terminal;
+        self
+    }
+
+    fn value(&mut self, t: A::Real, x: ArrayView<A, D>) -> A::Real {
+        (self.g)(t, x)
+    }
+}
+
+/// Occurrence of an event yielded by [Detector]
+///
+/// [Detector]: struct.Detector.html
+#[derive(Debug, Clone)]
+pub struct Occurrence<A: Scalar, D: Dimension> {
+    /// index of the event in the list given to [detect](fn.detect.html)
+    pub event: usize,
+    /// time of the crossing
+    pub t: A::Real,
+    /// interpolated state at the crossing
+    pub x: Array<A, D>,
+}
+
+/// Time derivative `f(t, x)` written into `x`, used for the Hermite interpolant
+type Derivative<TEO> = fn(
+    &mut TEO,
+    <TEO as TimeStep>::Time,
+    &mut Array<<TEO as ModelSpec>::Scalar, <TEO as ModelSpec>::Dim>,
+);
+
+/// Last states `(t_j, x_j)` for the Lagrange interpolation
+type Points<A, D> = VecDeque<(<A as Scalar>::Real, Array<A, D>)>;
+
+/// Interpolant of the last step
+enum Interpolant<A: Scalar, D: Dimension> {
+    Hermite(Hermite<A, D>),
+    /// Lagrange polynomial of the last states `(t_j, x_j)`
+    Lagrange(Vec<A::Real>, Vec<Array<A, D>>),
+}
+
+impl<A: Scalar, D: Dimension> Interpolant<A, D> {
+    fn eval(&self, t: A::Real) -> Array<A, D> {
+        match self {
+            Interpolant::Hermite(h) => h.eval(t),
+            Interpolant::Lagrange(ts, xs) => {
+                let mut x = Array::zeros(xs[0].raw_dim());
+                for (w, xj) in weights(ts, t).into_iter().zip(xs) {
+                    x.scaled_add(A::from_real(w), xj);
+                }
+                x
+            }
+        }
+    }
+}
+
+/// An iterator of event occurrences generated by [detect] or [detect_states]
+///
+/// [detect]: fn.detect.html
+/// [detect_states]: fn.detect_states.html
+pub struct Detector<'a, 'g, TEO>
+where
+    TEO: TimeEvolution,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    teo: &'a mut TEO,
+    x: Array<TEO::Scalar, TEO::Dim>,
+    derivative: Option<Derivative<TEO>>,
+    fx: Option<Array<TEO::Scalar, TEO::Dim>>,
+    points: Points<TEO::Scalar, TEO::Dim>,
+    events: Vec<Event<'g, TEO::Scalar, TEO::Dim>>,
+    values: Vec<TEO::Time>,
+    tol: TEO::Time,
+    t_end: Option<TEO::Time>,
+    pending: VecDeque<Occurrence<TEO::Scalar, TEO::Dim>>,
+    finished: bool,
+}
+
+fn rhs<TEO>(teo: &mut TEO, t: TEO::Time, x: &mut Array<TEO::Scalar, TEO::Dim>)
+where
+    TEO: Scheme,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+    TEO::Core: ExplicitT,
+{
+    teo.core_mut().rhs_t(t, x);
+}
+
+/// Integrate EoM from `x0` and detect zero crossings of `events`
+///
+/// Crossings are located on the cubic Hermite interpolant using the time derivatives of the model.
+/// The iterator yields occurrences in the order of time, and finishes after a terminal event.
+/// Without a terminal event, limit the time range by [Detector::until].
+///
+/// [Detector::until]: struct.Detector.html#method.until
+pub fn detect<'a, 'g, TEO>(
+    x0: Array<TEO::Scalar, TEO::Dim>,
+    teo: &'a mut TEO,
+    events: Vec<Event<'g, TEO::Scalar, TEO::Dim>>,
+) -> Detector<'a, 'g, TEO>
+where
+    TEO: Scheme,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+    TEO::Core: ExplicitT,
+{
+    Detector::new(x0, teo, events, Some(rhs::<TEO>))
+}
+
+/// Integrate any `TimeEvolution` from `x0` and detect zero crossings of `events`
+///
+/// This works also with semi-implicit, implicit and stochastic schemes and adaptors,
+/// since crossings are located on the cubic polynomial interpolating the last four states
+/// as in [poincare](../poincare/index.html).
+/// Otherwise the same as [detect](fn.detect.html).
+pub fn detect_states<'a, 'g, TEO>(
+    x0: Array<TEO::Scalar, TEO::Dim>,
+    teo: &'a mut TEO,
+    events: Vec<Event<'g, TEO::Scalar, TEO::Dim>>,
+) -> Detector<'a, 'g, TEO>
+where
+    TEO: TimeEvolution,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    Detector::new(x0, teo, events, None)
+}
+
+impl<'a, 'g, TEO> Detector<'a, 'g, TEO>
+where
+    TEO: TimeEvolution,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    fn new(
+        x0: Array<TEO::Scalar, TEO::Dim>,
+        teo: &'a mut TEO,
+        mut events: Vec<Event<'g, TEO::Scalar, TEO::Dim>>,
+        derivative: Option<Derivative<TEO>>,
+    ) -> Self {
+        let t = teo.time();
+        let values = events.iter_mut().map(|e| e.value(t, x0.view())).collect();
+        let mut points = VecDeque::with_capacity(POINTS);
+        points.push_back((t, x0.clone()));
+        Detector {
+            teo,
+            x: x0,
+            derivative,
+            fx: None,
+            points,
+            events,
+            values,
+            tol: TEO::Scalar::real(1e-12),
+            t_end: None,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Stop the integration when the time reaches `t_end`
+    ///
+    /// The last step may go beyond `t_end`, but events after `t_end` are not reported.
+    pub fn until(mut self, t_end: TEO::Time) -> Self {
+        self.t_end = Some(t_end);
+        self
+    }
+
+    /// Absolute tolerance of the crossing time (default `1e-12`)
+    pub fn tolerance(mut self, tol: TEO::Time) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Current state of the integration
+    ///
+    /// After a terminal event, this is the state at the event.
+    pub fn state(&self) -> &Array<TEO::Scalar, TEO::Dim> {
+        &self.x
+    }
+
+    /// Current simulation time
+    pub fn time(&self) -> TEO::Time {
+        self.teo.time()
+    }
+
+    /// Whether the integration has been finished by a terminal event or `until`
+    pub fn is_finished(&self) -> bool {
+        self.finished && self.pending.is_empty()
+    }
+
+    /// Take one step and return the interpolant of it
+    fn advance(&mut self) -> Interpolant<TEO::Scalar, TEO::Dim> {
+        let t0 = self.teo.time();
+        let rhs = match self.derivative {
+            Some(rhs) => rhs,
+            None => {
+                self.teo.iterate(&mut self.x);
+                if self.points.len() == POINTS {
+                    self.points.pop_front();
+                }
+                self.points.push_back((self.teo.time(), self.x.clone()));
+                let (ts, xs) = self.points.iter().cloned().unzip();
+                return Interpolant::Lagrange(ts, xs);
+            }
+        };
+        let x0 = self.x.clone();
+        let f0 = match self.fx.take() {
+            Some(f) => f,
+            None => {
+                let mut f = x0.clone();
+                rhs(self.teo, t0, &mut f);
+                f
+            }
+        };
+        self.teo.iterate(&mut self.x);
+        let t1 = self.teo.time();
+        let x1 = self.x.clone();
+        let mut f1 = x1.clone();
+        rhs(self.teo, t1, &mut f1);
+        self.fx = Some(f1.clone());
+        Interpolant::Hermite(Hermite::new(t0, x0, f0, t1, x1, f1))
+    }
+
+    /// Take one step and push the events found in it
+    fn step(&mut self) {
+        let t0 = self.teo.time();
+        if let Some(t_end) = self.t_end {
+            if t0 >= t_end {
+                self.finished = true;
+                return;
+            }
+        }
+        let interpolant = self.advance();
+        let t1 = self.teo.time();
+
+        let tol = self.tol;
+        let mut found = Vec::new();
+        for (i, (e, g0)) in self
+            .events
+            .iter_mut()
+            .zip(self.values.iter_mut())
+            .enumerate()
+        {
+            let g1 = e.value(t1, self.x.view());
+            if e.direction.matches(*g0, g1) {
+                let tc = illinois(
+                    |t| e.value(t, interpolant.eval(t).view()),
+                    t0,
+                    *g0,
+                    t1,
+                    g1,
+                    tol,
+                );
+                found.push((tc, i));
+            }
+            *g0 = g1;
+        }
+        // event functions returning NaN on the interpolant give no crossing time
+        found.retain(|(t, _)| t.is_finite());
+        found.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        if let Some(t_end) = self.t_end {
+            found.retain(|&(t, _)| t <= t_end);
+            if t1 >= t_end {
+                self.finished = true;
+            }
+        }
+        if let Some(k) = found.iter().position(|&(_, i)| self.events[i].terminal) {
+            // events at the same time as the terminal one are also reported
+            let tc = found[k].0;
+            found.retain(|&(t, _)| t <= tc);
+            self.x = interpolant.eval(tc);
+            self.teo.set_time(tc);
+            self.fx = None;
+            self.points.clear();
+            self.points.push_back((tc, self.x.clone()));
+            self.finished = true;
+        }
+        for (t, event) in found {
+            let x = interpolant.eval(t);
+            self.pending.push_back(Occurrence { event, t, x });
+        }
+    }
+}
+
+impl<'a, 'g, TEO> Iterator for Detector<'a, 'g, TEO>
+where
+    TEO: TimeEvolution,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    type Item = Occurrence<TEO::Scalar, TEO::Dim>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !self.finished {
+            self.step();
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Find a root of `g` in `[a, b]` where `g(a) = ga` and `g(b) = gb` have different signs
+///
+/// The Illinois method is a regula falsi where the function value at the retained end
+/// is halved to avoid the one-sided convergence.
+pub(crate) fn illinois<T, G>(mut g: G, mut a: T, mut ga: T, mut b: T, mut gb: T, tol: T) -> T
+where
+    T: Float,
+    G: FnMut(T) -> T,
+{
+    if gb.is_zero() {
+        return b;
+    }
+    for _ in 0..100 {
+        if (b - a).abs() <= tol {
+            break;
+        }
+        let c = b - gb * (b - a) / (gb - ga);
+        let gc = g(c);
+        if gc.is_zero() {
+            return c;
+        }
+        if gc.signum() != gb.signum() {
+            a = b;
+            ga = gb;
+        } else {
+            ga = ga / (T::one() + T::one());
+        }
+        b = c;
+        gb = gc;
+    }
+    b
+}
@@ -1,17 +1,29 @@
 //! explicit schemes
 
+use super::tangent::Variational;
 use super::traits::*;
 use ndarray::*;
 use ndarray_linalg::*;
+use num_traits::Zero;
 
 #[derive(Debug, Clone)]
-pub struct Euler<F: Explicit> {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Euler<F: ExplicitT> {
     f: F,
     dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
     x: Array<F::Scalar, F::Dim>,
+    k1: Array<F::Scalar, F::Dim>,
 }
 
-impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Euler<F> {
+impl<A: Scalar, F: ExplicitT<Scalar = A>> TimeStep for Euler<F> {
     type Time = A::Real;
 
     fn get_dt(&self) -> Self::Time {
@@ -23,11 +35,13 @@ impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Euler<F> {
     }
 }
 
-impl<F: Explicit> Scheme for Euler<F> {
+impl<F: ExplicitT> Scheme for Euler<F> {
     type Core = F;
     fn new(f: F, dt: Self::Time) -> Self {
         let x = Array::zeros(f.model_size());
-        Self { f, dt, x }
+        let k1 = Array::zeros(f.model_size());
+        let t = Zero::zero();
+        Self { f, dt, t, x, k1 }
     }
     fn core(&self) -> &Self::Core {
         &self.f
@@ -37,7 +51,7 @@ impl<F: Explicit> Scheme for Euler<F> {
     }
 }
 
-impl<F: Explicit> ModelSpec for Euler<F> {
+impl<F: ExplicitT> ModelSpec for Euler<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
     fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
@@ -45,29 +59,49 @@ impl<F: Explicit> ModelSpec for Euler<F> {
     }
 }
 
-impl<F: Explicit> TimeEvolution for Euler<F> {
+impl<F: ExplicitT> TimeEvolution for Euler<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
     fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
     where
         S: DataMut<Elem = Self::Scalar>,
     {
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
-        let fx = self.f.rhs(x);
+        let fx = self.f.rhs_t(self.t, x);
+        self.k1.zip_mut_with(fx, |buf, k1| *buf = *k1);
         Zip::from(&mut *fx).and(&self.x).apply(|vfx, vx| {
             *vfx = *vx + vfx.mul_real(self.dt);
         });
+        self.t += self.dt;
         fx
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct Heun<F: Explicit> {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Heun<F: ExplicitT> {
     f: F,
     dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
     x: Array<F::Scalar, F::Dim>,
     k1: Array<F::Scalar, F::Dim>,
+    k2: Array<F::Scalar, F::Dim>,
 }
 
-impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Heun<F> {
+impl<A: Scalar, F: ExplicitT<Scalar = A>> TimeStep for Heun<F> {
     type Time = A::Real;
 
     fn get_dt(&self) -> Self::Time {
@@ -79,12 +113,21 @@ impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for Heun<F> {
     }
 }
 
-impl<F: Explicit> Scheme for Heun<F> {
+impl<F: ExplicitT> Scheme for Heun<F> {
     type Core = F;
     fn new(f: F, dt: Self::Time) -> Self {
         let x = Array::zeros(f.model_size());
         let k1 = Array::zeros(f.model_size());
-        Self { f, dt, x, k1 }
+        let k2 = Array::zeros(f.model_size());
+        let t = Zero::zero();
+        Self {
+            f,
+            dt,
+            t,
+            x,
+            k1,
+            k2,
+        }
     }
     fn core(&self) -> &Self::Core {
         &self.f
@@ -94,7 +137,7 @@ impl<F: Explicit> Scheme for Heun<F> {
     }
 }
 
-impl<F: Explicit> ModelSpec for Heun<F> {
+impl<F: ExplicitT> ModelSpec for Heun<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
     fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
@@ -102,7 +145,15 @@ impl<F: Explicit> ModelSpec for Heun<F> {
     }
 }
 
-impl<F: Explicit> TimeEvolution for Heun<F> {
+impl<F: ExplicitT> TimeEvolution for Heun<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
     fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
     where
         S: DataMut<Elem = Self::Scalar>,
@@ -111,33 +162,45 @@ impl<F: Explicit> TimeEvolution for Heun<F> {
         let dt_2 = self.dt * F::Scalar::real(0.5);
         // calc
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
-        let k1 = self.f.rhs(x);
+        let k1 = self.f.rhs_t(self.t, x);
         self.k1.zip_mut_with(k1, |buf, k1| *buf = *k1);
         Zip::from(&mut *k1).and(&self.x).apply(|k1, &x_| {
             *k1 = k1.mul_real(dt) + x_;
         });
-        let k2 = self.f.rhs(k1);
+        let k2 = self.f.rhs_t(self.t + dt, k1);
+        self.k2.zip_mut_with(k2, |buf, k2| *buf = *k2);
         Zip::from(&mut *k2)
             .and(&self.x)
             .and(&self.k1)
             .apply(|k2, &x_, &k1_| {
                 *k2 = x_ + (k1_ + *k2).mul_real(dt_2);
             });
+        self.t += dt;
         k2
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct RK4<F: Explicit> {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct RK4<F: ExplicitT> {
     f: F,
     dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
     x: Array<F::Scalar, F::Dim>,
     k1: Array<F::Scalar, F::Dim>,
     k2: Array<F::Scalar, F::Dim>,
     k3: Array<F::Scalar, F::Dim>,
+    k4: Array<F::Scalar, F::Dim>,
 }
 
-impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for RK4<F> {
+impl<A: Scalar, F: ExplicitT<Scalar = A>> TimeStep for RK4<F> {
     type Time = A::Real;
 
     fn get_dt(&self) -> Self::Time {
@@ -149,20 +212,24 @@ impl<A: Scalar, F: Explicit<Scalar = A>> TimeStep for RK4<F> {
     }
 }
 
-impl<F: Explicit> Scheme for RK4<F> {
+impl<F: ExplicitT> Scheme for RK4<F> {
     type Core = F;
     fn new(f: F, dt: Self::Time) -> Self {
         let x = Array::zeros(f.model_size());
         let k1 = Array::zeros(f.model_size());
         let k2 = Array::zeros(f.model_size());
         let k3 = Array::zeros(f.model_size());
+        let k4 = Array::zeros(f.model_size());
+        let t = Zero::zero();
         Self {
             f,
             dt,
+            t,
             x,
             k1,
             k2,
             k3,
+            k4,
         }
     }
     fn core(&self) -> &Self::Core {
@@ -173,7 +240,7 @@ impl<F: Explicit> Scheme for RK4<F> {
     }
 }
 
-impl<F: Explicit> ModelSpec for RK4<F> {
+impl<F: ExplicitT> ModelSpec for RK4<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
     fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
@@ -181,7 +248,15 @@ impl<F: Explicit> ModelSpec for RK4<F> {
     }
 }
 
-impl<F: Explicit> TimeEvolution for RK4<F> {
+impl<F: ExplicitT> TimeEvolution for RK4<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
     fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
     where
         S: DataMut<Elem = Self::Scalar>,
@@ -192,24 +267,25 @@ impl<F: Explicit> TimeEvolution for RK4<F> {
         let dt_6 = self.dt / F::Scalar::real(6.0);
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
         // k1
-        let k1 = self.f.rhs(x);
+        let k1 = self.f.rhs_t(self.t, x);
         self.k1.zip_mut_with(k1, |buf, k1| *buf = *k1);
         Zip::from(&mut *k1).and(&self.x).apply(|k1, &x| {
             *k1 = k1.mul_real(dt_2) + x;
         });
         // k2
-        let k2 = self.f.rhs(k1);
+        let k2 = self.f.rhs_t(self.t + dt_2, k1);
         self.k2.zip_mut_with(k2, |buf, k| *buf = *k);
         Zip::from(&mut *k2).and(&self.x).apply(|k2, &x| {
             *k2 = x + k2.mul_real(dt_2);
         });
         // k3
-        let k3 = self.f.rhs(k2);
+        let k3 = self.f.rhs_t(self.t + dt_2, k2);
         self.k3.zip_mut_with(k3, |buf, k| *buf = *k);
         Zip::from(&mut *k3).and(&self.x).apply(|k3, &x| {
             *k3 = x + k3.mul_real(dt);
         });
-        let k4 = self.f.rhs(k3);
+        let k4 = self.f.rhs_t(self.t + dt, k3);
+        self.k4.zip_mut_with(k4, |buf, k| *buf = *k);
         Zip::from(&mut *k4)
             .and(&self.x)
             .and(&self.k1)
@@ -218,6 +294,77 @@ impl<F: Explicit> TimeEvolution for RK4<F> {
             .apply(|k4, &x, &k1, &k2, &k3| {
                 *k4 = x + (k1 + (k2 + k3).mul_real(two) + *k4).mul_real(dt_6);
             });
+        self.t += dt;
         k4
     }
 }
+
+impl<F: ExplicitT> DenseOutput for Euler<F> {
+    /// linear interpolation
+    fn interpolate<S>(&mut self, t: Self::Time, x: &mut ArrayBase<S, Self::Dim>)
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let h = t - (self.t - self.dt);
+        Zip::from(x)
+            .and(&self.x)
+            .and(&self.k1)
+            .apply(|x, &x0, &k1| *x = x0 + k1.mul_real(h));
+    }
+}
+
+impl<F: ExplicitT> DenseOutput for Heun<F> {
+    /// continuous extension of the second order
+    fn interpolate<S>(&mut self, t: Self::Time, x: &mut ArrayBase<S, Self::Dim>)
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let dt = self.dt;
+        let s = (t - (self.t - dt)) / dt;
+        let b2 = s * s * F::Scalar::real(0.5);
+        let b1 = (s - b2) * dt;
+        let b2 = b2 * dt;
+        Zip::from(x)
+            .and(&self.x)
+            .and(&self.k1)
+            .and(&self.k2)
+            .apply(|x, &x0, &k1, &k2| *x = x0 + k1.mul_real(b1) + k2.mul_real(b2));
+    }
+}
+
+impl<F: ExplicitT> DenseOutput for RK4<F> {
+    /// continuous extension of the third order (Hairer, Norsett and Wanner)
+    fn interpolate<S>(&mut self, t: Self::Time, x: &mut ArrayBase<S, Self::Dim>)
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let dt = self.dt;
+        let s = (t - (self.t - dt)) / dt;
+        let (s2, s3) = (s * s, s * s * s);
+        let c = |v: f64| F::Scalar::real(v);
+        let b1 = (s - c(1.5) * s2 + c(2.0 / 3.0) * s3) * dt;
+        let b23 = (s2 - c(2.0 / 3.0) * s3) * dt;
+        let b4 = (c(-0.5) * s2 + c(2.0 / 3.0) * s3) * dt;
+        Zip::from(x)
+            .and(&self.x)
+            .and(&self.k1)
+            .and(&self.k2)
+            .and(&self.k3)
+            .and(&self.k4)
+            .apply(|x, &x0, &k1, &k2, &k3, &k4| {
+                *x = x0 + k1.mul_real(b1) + (k2 + k3).mul_real(b23) + k4.mul_real(b4);
+            });
+    }
+}
+
+impl<F: ExplicitJacobian<Dim = Ix1>> TangentScheme for Euler<F> {
+    type Tangent = Euler<Variational<F>>;
+}
+
+impl<F: ExplicitJacobian<Dim = Ix1>> TangentScheme for Heun<F> {
+    type Tangent = Heun<Variational<F>>;
+}
+
+impl<F: ExplicitJacobian<Dim = Ix1>> TangentScheme for RK4<F> {
+    type Tangent = RK4<Variational<F>>;
+}
@@ -0,0 +1,239 @@
+//! Fixed points (equilibria) of ODEs and their linear stability
+//!
+//! Equilibria `f(x) = 0` of `ExplicitT` models are located by the Newton iteration.
+//! The Jacobian matrix is given by [JacobianMatrix] as in the implicit schemes,
+//! i.e. by finite differences with `NumericalJacobian` (default),
+//! or by the tangent linear model with `AnalyticJacobian`.
+//! Non-autonomous models are evaluated at `t = 0`.
+//!
+//! [JacobianMatrix]: ../implicit/trait.JacobianMatrix.html
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::fixed_point::*;
+//! use eom::implicit::AnalyticJacobian;
+//! use ndarray::*;
+//! let f = ode::Lorenz63::new(10.0, 20.0, 8.0 / 3.0);
+//! let mut solver = Solver::<_, AnalyticJacobian>::new(f);
+//! let fp = solver.solve(arr1(&[7.0, 7.0, 18.0])).unwrap();
+//! println!("{} is {:?}", fp.x, fp.stability);
+//! ```
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::{Float, One, Zero};
+use rand::Rng;
+
+use crate::error::Result;
+use crate::implicit::{JacobianMatrix, NewtonError, NumericalJacobian};
+use crate::traits::*;
+
+/// Classification of a fixed point by the eigenvalues of the Jacobian matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stability {
+    /// all eigenvalues are real and negative
+    StableNode,
+    /// all eigenvalues have negative real parts, and some of them are complex
+    StableFocus,
+    /// all eigenvalues are real and positive
+    UnstableNode,
+    /// all eigenvalues have positive real parts, and some of them are complex
+    UnstableFocus,
+    /// eigenvalues are real, and some of them are positive and others negative
+    Saddle,
+    /// saddle with complex eigenvalues
+    SaddleFocus,
+    /// some eigenvalues have zero real parts, e.g. centers
+    NonHyperbolic,
+}
+
+impl Stability {
+    /// Classify the eigenvalues. Real or imaginary parts smaller than `tol` are regarded as zero.
+    pub fn classify<C: Scalar>(eigenvalues: &Array1<C>, tol: C::Real) -> Self {
+        let zero = C::Real::zero();
+        if eigenvalues.iter().any(|l| Float::abs(l.re()) <= tol) {
+            return Stability::NonHyperbolic;
+        }
+        let complex = eigenvalues.iter().any(|l| Float::abs(l.im()) > tol);
+        let stable = eigenvalues.iter().all(|l| l.re() < zero);
+        let unstable = eigenvalues.iter().all(|l| l.re() > zero);
+        match (stable, unstable, complex) {
+            (true, _, false) => Stability::StableNode,
+            (true, _, true) => Stability::StableFocus,
+            (_, true, false) => Stability::UnstableNode,
+            (_, true, true) => Stability::UnstableFocus,
+            (false, false, false) => Stability::Saddle,
+            (false, false, true) => Stability::SaddleFocus,
+        }
+    }
+
+    pub fn is_stable(self) -> bool {
+        matches!(self, Stability::StableNode | Stability::StableFocus)
+    }
+}
+
+/// Fixed point with the eigenvalues of the Jacobian matrix there
+#[derive(Debug, Clone)]
+pub struct FixedPoint<A: Scalar> {
+    pub x: Array1<A>,
+    pub eigenvalues: Array1<A::Complex>,
+    pub stability: Stability,
+    /// number of Newton iterations
+    pub iterations: usize,
+}
+
+impl<A: Scalar> FixedPoint<A> {
+    /// Number of eigenvalues with positive real parts
+    pub fn unstable_dim(&self) -> usize {
+        self.eigenvalues
+            .iter()
+            .filter(|l| l.re() > A::Real::zero())
+            .count()
+    }
+}
+
+/// Newton solver of `f(x) = 0` for the model `F`
+#[derive(Debug, Clone)]
+pub struct Solver<F: ExplicitT<Dim = Ix1>, J: JacobianMatrix<F> = NumericalJacobian> {
+    f: F,
+    jacobian: J,
+    tol: <F::Scalar as Scalar>::Real,
+    max_iter: usize,
+}
+
+impl<A, F, J> Solver<F, J>
+where
+    A: Scalar + Lapack,
+    F: ExplicitT<Scalar = A, Dim = Ix1>,
+    J: JacobianMatrix<F>,
+{
+    pub fn new(f: F) -> Self {
+        Solver {
+            f,
+            jacobian: J::default(),
+            tol: A::real(1e-10),
+            max_iter: 50,
+        }
+    }
+
+    /// The iteration converges when `|dx| <= tol (1 + |x|)` for the Newton update `dx`.
+    /// The default is `(1e-10, 50)`.
+    pub fn set_newton(&mut self, tol: A::Real, max_iter: usize) {
+        self.tol = tol;
+        self.max_iter = max_iter;
+    }
+
+    /// Tolerance and maximum number of iterations
+    pub fn get_newton(&self) -> (A::Real, usize) {
+        (self.tol, self.max_iter)
+    }
+
+    fn residual(&mut self, x: &Array1<A>) -> Array1<A> {
+        let mut fx = x.clone();
+        self.f.rhs_t(A::Real::zero(), &mut fx);
+        fx
+    }
+
+    /// Jacobian matrix of the model at `x`
+    pub fn jacobian(&mut self, x: &Array1<A>) -> Array2<A> {
+        self.jacobian.jacobian(&mut self.f, A::Real::zero(), x)
+    }
+
+    /// Find a fixed point by the Newton iteration starting from `x0`
+    ///
+    /// The update is halved while it increases the residual, which enlarges the basin of convergence.
+    /// Divergence of the iteration, e.g. from a non-finite `x0`, is reported as `Error::NonFinite`.
+    pub fn solve(&mut self, x0: Array1<A>) -> Result<FixedPoint<A>> {
+        let mut x = x0;
+        let mut fx = self.residual(&x);
+        for iter in 1..=self.max_iter {
+            let jac = self.jacobian(&x);
+            let dx = jac.solve_into(fx.mapv(|a| -a))?;
+            check_finite(&dx, A::Real::zero())?;
+            let nrm = fx.norm_l2();
+            let mut step = A::Real::one();
+            let mut x_new = &x + &dx;
+            let mut fx_new = self.residual(&x_new);
+            for _ in 0..10 {
+                if fx_new.norm_l2() <= nrm {
+                    break;
+                }
+                step /= A::real(2.0);
+                x_new = &x + &dx.mapv(|a| a.mul_real(step));
+                fx_new = self.residual(&x_new);
+            }
+            let converged = dx.norm_l2() * step <= self.tol * (A::Real::one() + x.norm_l2());
+            x = x_new;
+            fx = fx_new;
+            if converged {
+                return self.classify(x, iter);
+            }
+        }
+        Err(NewtonError::NotConverged {
+            iterations: self.max_iter,
+        }
+        .into())
+    }
+
+    fn classify(&mut self, x: Array1<A>, iterations: usize) -> Result<FixedPoint<A>> {
+        let jac = self.jacobian(&x);
+        let eigenvalues = jac.eigvals()?;
+        let scale = eigenvalues
+            .iter()
+            .fold(A::Real::one(), |m, l| m.max(l.abs()));
+        let tol = Float::sqrt(A::Real::epsilon()) * scale;
+        let stability = Stability::classify(&eigenvalues, tol);
+        Ok(FixedPoint {
+            x,
+            eigenvalues,
+            stability,
+            iterations,
+        })
+    }
+
+    /// Find distinct fixed points starting from each of `guesses`
+    ///
+    /// Starts which do not converge are skipped.
+    /// Fixed points closer than `sqrt(tol) (1 + |x|)` are regarded as the same.
+    pub fn multi_start<I>(&mut self, guesses: I) -> Vec<FixedPoint<A>>
+    where
+        I: IntoIterator<Item = Array1<A>>,
+    {
+        let mut found: Vec<FixedPoint<A>> = Vec::new();
+        let dist = Float::sqrt(self.tol);
+        for x0 in guesses {
+            if let Ok(fp) = self.solve(x0) {
+                let x_nrm = fp.x.norm_l2();
+                let known = found
+                    .iter()
+                    .any(|p| (&p.x - &fp.x).norm_l2() <= dist * (A::Real::one() + x_nrm));
+                if !known {
+                    found.push(fp);
+                }
+            }
+        }
+        found
+    }
+}
+
+impl<A, F, J> Solver<F, J>
+where
+    A: Scalar<Real = A> + Lapack,
+    F: ExplicitT<Scalar = A, Dim = Ix1>,
+    J: JacobianMatrix<F>,
+{
+    /// Find distinct fixed points starting from `count` random points
+    /// uniformly distributed in the box `center +/- radius`
+    pub fn random_starts<R: Rng>(
+        &mut self,
+        count: usize,
+        center: &Array1<A>,
+        radius: A,
+        rng: &mut R,
+    ) -> Vec<FixedPoint<A>> {
+        let guesses: Vec<_> = (0..count)
+            .map(|_| center.mapv(|c| c + (A::rand(rng) * A::real(2.0) - A::one()) * radius))
+            .collect();
+        self.multi_start(guesses)
+    }
+}
@@ -0,0 +1,733 @@
+//! implicit schemes for stiff equations
+//!
+//! The stage equations are solved by the simplified Newton iteration,
+//! where the Jacobian matrix of the rhs is evaluated once per step.
+//! When it fails, the step is retried by the full Newton iteration.
+//! `TimeEvolution::try_iterate` reports the failure of the Newton iteration as `Error::Newton`,
+//! while `TimeEvolution::iterate` fills the state by NaN and keeps the error in `last_error`.
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::{Float, One, Zero};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use super::traits::*;
+
+/// Error of the Newton iteration
+#[derive(Debug)]
+pub enum NewtonError {
+    /// the iteration does not converge within the maximum number of iterations
+    NotConverged { iterations: usize },
+    /// the Newton matrix cannot be factorized or solved
+    Linalg(error::LinalgError),
+}
+
+impl fmt::Display for NewtonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NewtonError::NotConverged { iterations } => write!(
+                f,
+                "Newton iteration does not converge in {} iterations",
+                iterations
+            ),
+            NewtonError::Linalg(e) => write!(f, "Newton matrix cannot be solved: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NewtonError {}
+
+impl From<error::LinalgError> for NewtonError {
+    fn from(e: error::LinalgError) -> Self {
+        NewtonError::Linalg(e)
+    }
+}
+
+/// Jacobian matrix of the rhs used in the Newton iteration
+pub trait JacobianMatrix<F: ExplicitT<Dim = Ix1>>: Clone + Default {
+    fn jacobian(
+        &mut self,
+        f: &mut F,
+        t: <F::Scalar as Scalar>::Real,
+        x: &Array1<F::Scalar>,
+    ) -> Array2<F::Scalar>;
+}
+
+/// Jacobian matrix by the forward difference
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NumericalJacobian;
+
+impl<F: ExplicitT<Dim = Ix1>> JacobianMatrix<F> for NumericalJacobian {
+    fn jacobian(
+        &mut self,
+        f: &mut F,
+        t: <F::Scalar as Scalar>::Real,
+        x: &Array1<F::Scalar>,
+    ) -> Array2<F::Scalar> {
+        let n = x.len();
+        let eps = Float::sqrt(<F::Scalar as Scalar>::Real::epsilon());
+        let mut fx = x.clone();
+        f.rhs_t(t, &mut fx);
+        let mut jac = Array::zeros((n, n));
+        for j in 0..n {
+            let h = eps * x[j].abs().max(One::one());
+            let mut xh = x.clone();
+            xh[j] += F::Scalar::from_real(h);
+            f.rhs_t(t, &mut xh);
+            Zip::from(jac.column_mut(j))
+                .and(&xh)
+                .and(&fx)
+                .apply(|j, &xh, &fx| *j = (xh - fx).div_real(h));
+        }
+        jac
+    }
+}
+
+/// Jacobian matrix by the tangent linear model `ExplicitJacobian`
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalyticJacobian;
+
+impl<F: ExplicitJacobian<Dim = Ix1>> JacobianMatrix<F> for AnalyticJacobian {
+    fn jacobian(
+        &mut self,
+        f: &mut F,
+        t: <F::Scalar as Scalar>::Real,
+        x: &Array1<F::Scalar>,
+    ) -> Array2<F::Scalar> {
+        let mut jac = Array::eye(x.len());
+        for mut col in jac.axis_iter_mut(Axis(1)) {
+            f.rhs_tangent(t, x, &mut col);
+        }
+        jac
+    }
+}
+
+/// Settings of the Newton iteration
+pub trait NewtonIteration: TimeStep {
+    /// tolerance and maximum number of iterations
+    fn get_newton(&self) -> (Self::Time, usize);
+    /// The iteration converges when `|dx| <= tol (1 + |x|)` for the Newton update `dx`.
+    /// `max_iter` limits the simplified and the full Newton iteration separately.
+    /// The default is `(1e-10, 20)`.
+    fn set_newton(&mut self, tol: Self::Time, max_iter: usize);
+    /// error in the last call of `TimeEvolution::iterate`
+    fn last_error(&self) -> Option<&NewtonError>;
+}
+
+/// Parameters of the Newton iteration shared by schemes
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Newton<R> {
+    tol: R,
+    max_iter: usize,
+}
+
+impl<R: Float> Newton<R> {
+    fn new() -> Self {
+        Newton {
+            tol: R::from(1e-10).unwrap(),
+            max_iter: 20,
+        }
+    }
+
+    fn converged<A: Scalar<Real = R> + Lapack>(&self, dx: &Array1<A>, x: &Array1<A>) -> bool {
+        dx.norm_l2() <= self.tol * (R::one() + x.norm_l2())
+    }
+
+    /// The update does not shrink from the `last` one
+    fn diverged<A: Scalar<Real = R> + Lapack>(&self, dx: &Array1<A>, last: &mut Option<R>) -> bool {
+        let nrm = dx.norm_l2();
+        let diverged = match *last {
+            Some(last) => nrm >= last || nrm.is_nan(),
+            None => !nrm.is_finite(),
+        };
+        *last = Some(nrm);
+        diverged
+    }
+}
+
+/// Radau IIA nodes
+fn radau_c() -> [f64; 3] {
+    let s6 = 6.0_f64.sqrt();
+    [(4.0 - s6) / 10.0, (4.0 + s6) / 10.0, 1.0]
+}
+
+/// Radau IIA Runge-Kutta matrix
+fn radau_a() -> [[f64; 3]; 3] {
+    let s6 = 6.0_f64.sqrt();
+    [
+        [
+            (88.0 - 7.0 * s6) / 360.0,
+            (296.0 - 169.0 * s6) / 1800.0,
+            (-2.0 + 3.0 * s6) / 225.0,
+        ],
+        [
+            (296.0 + 169.0 * s6) / 1800.0,
+            (88.0 + 7.0 * s6) / 360.0,
+            (-2.0 - 3.0 * s6) / 225.0,
+        ],
+        [(16.0 - s6) / 36.0, (16.0 + s6) / 36.0, 1.0 / 9.0],
+    ]
+}
+
+/// Newton matrix of the 3-stage Radau IIA scheme, whose `(i, j)` block is
+/// `delta_ij I - h a_ij J_j`
+fn radau_matrix<A: Scalar>(jm: &[Array2<A>; 3], h: A::Real) -> Array2<A> {
+    let n = jm[0].nrows();
+    let a = radau_a();
+    let mut m = Array::eye(3 * n);
+    for (i, ai) in a.iter().enumerate() {
+        for (j, (&aij, jm)) in ai.iter().zip(jm.iter()).enumerate() {
+            let ha = h * A::real(aij);
+            let mut block = m.slice_mut(s![i * n..(i + 1) * n, j * n..(j + 1) * n]);
+            Zip::from(&mut block)
+                .and(jm)
+                .apply(|m, &jm| *m -= jm.mul_real(ha));
+        }
+    }
+    m
+}
+
+/// One step of the 3-stage Radau IIA scheme from `(t, x)`
+///
+/// The simplified Newton iteration uses the Jacobian at `x` for all stages.
+/// If it fails, the step is restarted with the full Newton iteration,
+/// which re-evaluates the Jacobian at every stage in every iteration.
+fn radau_step<F, J>(
+    f: &mut F,
+    jac: &mut J,
+    newton: &Newton<<F::Scalar as Scalar>::Real>,
+    t: <F::Scalar as Scalar>::Real,
+    h: <F::Scalar as Scalar>::Real,
+    x: &Array1<F::Scalar>,
+) -> Result<Array1<F::Scalar>, NewtonError>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    J: JacobianMatrix<F>,
+{
+    let n = x.len();
+    let c = radau_c();
+    let a = radau_a();
+    let mut fz: Array1<F::Scalar> = Array::zeros(3 * n);
+    let mut iterations = 0;
+    for &full in &[false, true] {
+        let mut z: Array1<F::Scalar> = Array::zeros(3 * n);
+        let mut lu = None;
+        let mut last = None;
+        for _ in 0..newton.max_iter {
+            iterations += 1;
+            if full || lu.is_none() {
+                let jm = if full {
+                    let mut jm = |i: usize| {
+                        let y = x + &z.slice(s![i * n..(i + 1) * n]);
+                        jac.jacobian(f, t + h * F::Scalar::real(c[i]), &y)
+                    };
+                    [jm(0), jm(1), jm(2)]
+                } else {
+                    let jm = jac.jacobian(f, t, x);
+                    [jm.clone(), jm.clone(), jm]
+                };
+                lu = Some(radau_matrix(&jm, h).factorize_into()?);
+            }
+            for (i, &ci) in c.iter().enumerate() {
+                let mut fi = fz.slice_mut(s![i * n..(i + 1) * n]);
+                Zip::from(&mut fi)
+                    .and(x)
+                    .and(z.slice(s![i * n..(i + 1) * n]))
+                    .apply(|f, &x, &z| *f = x + z);
+                f.rhs_t(t + h * F::Scalar::real(ci), &mut fi);
+            }
+            let mut g = z.clone();
+            for (i, ai) in a.iter().enumerate() {
+                let mut gi = g.slice_mut(s![i * n..(i + 1) * n]);
+                for (j, &aij) in ai.iter().enumerate() {
+                    let ha = h * F::Scalar::real(aij);
+                    Zip::from(&mut gi)
+                        .and(fz.slice(s![j * n..(j + 1) * n]))
+                        .apply(|g, &f| *g -= f.mul_real(ha));
+                }
+            }
+            let dz = lu.as_ref().unwrap().solve_into(g)?;
+            z -= &dz;
+            if newton.converged(&dz, x) {
+                return Ok(x + &z.slice(s![2 * n..]));
+            }
+            if newton.diverged(&dz, &mut last) {
+                break;
+            }
+        }
+    }
+    Err(NewtonError::NotConverged { iterations })
+}
+
+/// One step of the BDF scheme `y = sum_j alpha_j hist_j + beta h f(t + h, y)`,
+/// where `hist[0]` is the state at `t` and the order is `hist.len()`
+///
+/// The fallback to the full Newton iteration is the same as `radau_step`.
+fn bdf_step<F, J>(
+    f: &mut F,
+    jac: &mut J,
+    newton: &Newton<<F::Scalar as Scalar>::Real>,
+    t: <F::Scalar as Scalar>::Real,
+    h: <F::Scalar as Scalar>::Real,
+    hist: &[Array1<F::Scalar>],
+) -> Result<Array1<F::Scalar>, NewtonError>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    J: JacobianMatrix<F>,
+{
+    let n = hist[0].len();
+    let (alpha, beta) = BDF[hist.len() - 1];
+    let hb = h * F::Scalar::real(beta);
+    let mut psi = Array::zeros(n);
+    for (y, &a) in hist.iter().zip(alpha.iter()) {
+        let a = F::Scalar::real(a);
+        Zip::from(&mut psi)
+            .and(y)
+            .apply(|psi, &y| *psi += y.mul_real(a));
+    }
+    let mut iterations = 0;
+    for &full in &[false, true] {
+        let mut y = hist[0].clone();
+        let mut lu = None;
+        let mut last = None;
+        for _ in 0..newton.max_iter {
+            iterations += 1;
+            if full || lu.is_none() {
+                let jm = if full {
+                    jac.jacobian(f, t + h, &y)
+                } else {
+                    jac.jacobian(f, t, &hist[0])
+                };
+                let m = Array::eye(n) - jm.mapv(|j| j.mul_real(hb));
+                lu = Some(m.factorize_into()?);
+            }
+            let mut g = y.clone();
+            f.rhs_t(t + h, &mut g);
+            Zip::from(&mut g)
+                .and(&y)
+                .and(&psi)
+                .apply(|g, &y, &psi| *g = y - psi - g.mul_real(hb));
+            let dy = lu.as_ref().unwrap().solve_into(g)?;
+            y -= &dy;
+            if newton.converged(&dy, &y) {
+                return Ok(y);
+            }
+            if newton.diverged(&dy, &mut last) {
+                break;
+            }
+        }
+    }
+    Err(NewtonError::NotConverged { iterations })
+}
+
+/// 3-stage Radau IIA scheme (order 5)
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, J: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, J: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct RadauIIA<F: ExplicitT<Dim = Ix1>, J: JacobianMatrix<F> = NumericalJacobian> {
+    f: F,
+    jac: J,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    newton: Newton<<F::Scalar as Scalar>::Real>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    error: Option<Arc<NewtonError>>,
+}
+
+impl<F, J> RadauIIA<F, J>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    J: JacobianMatrix<F>,
+{
+    /// Proceed one step, or return the error without changing the state
+    fn newton_step<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Ix1>,
+    ) -> Result<&'a mut ArrayBase<S, Ix1>, NewtonError>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        let y = radau_step(
+            &mut self.f,
+            &mut self.jac,
+            &self.newton,
+            self.t,
+            self.dt,
+            &x.to_owned(),
+        )?;
+        x.assign(&y);
+        self.t += self.dt;
+        Ok(x)
+    }
+}
+
+impl<F: ExplicitT<Dim = Ix1>, J: JacobianMatrix<F>> TimeStep for RadauIIA<F, J> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<F: ExplicitT<Dim = Ix1>, J: JacobianMatrix<F>> NewtonIteration for RadauIIA<F, J> {
+    fn get_newton(&self) -> (Self::Time, usize) {
+        (self.newton.tol, self.newton.max_iter)
+    }
+
+    fn set_newton(&mut self, tol: Self::Time, max_iter: usize) {
+        self.newton = Newton { tol, max_iter };
+    }
+
+    fn last_error(&self) -> Option<&NewtonError> {
+        self.error.as_deref()
+    }
+}
+
+impl<F, J> Scheme for RadauIIA<F, J>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    J: JacobianMatrix<F>,
+{
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        Self {
+            f,
+            jac: J::default(),
+            dt,
+            t: Zero::zero(),
+            newton: Newton::new(),
+            error: None,
+        }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: ExplicitT<Dim = Ix1>, J: JacobianMatrix<F>> ModelSpec for RadauIIA<F, J> {
+    type Scalar = F::Scalar;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.f.model_size()
+    }
+}
+
+impl<F, J> TimeEvolution for RadauIIA<F, J>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    J: JacobianMatrix<F>,
+{
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.error = self.newton_step(x).err().map(Arc::new);
+        if self.error.is_some() {
+            x.fill(F::Scalar::from_real(Float::nan()));
+            self.t += self.dt;
+        }
+        x
+    }
+
+    /// The state is not changed when the Newton iteration fails
+    fn try_iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Ix1>,
+    ) -> crate::error::Result<&'a mut ArrayBase<S, Ix1>>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.error = None;
+        self.newton_step(x)?;
+        check_finite(x, self.t)?;
+        Ok(x)
+    }
+}
+
+/// Order of the BDF scheme
+pub trait BdfOrder: Clone {
+    const ORDER: usize;
+}
+
+/// Coefficients `(alpha, beta)` of the BDF scheme of each order
+const BDF: [(&[f64], f64); 5] = [
+    (&[1.0], 1.0),
+    (&[4.0 / 3.0, -1.0 / 3.0], 2.0 / 3.0),
+    (&[18.0 / 11.0, -9.0 / 11.0, 2.0 / 11.0], 6.0 / 11.0),
+    (
+        &[48.0 / 25.0, -36.0 / 25.0, 16.0 / 25.0, -3.0 / 25.0],
+        12.0 / 25.0,
+    ),
+    (
+        &[
+            300.0 / 137.0,
+            -300.0 / 137.0,
+            200.0 / 137.0,
+            -75.0 / 137.0,
+            12.0 / 137.0,
+        ],
+        60.0 / 137.0,
+    ),
+];
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order1;
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order2;
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order3;
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order4;
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order5;
+
+impl BdfOrder for Order1 {
+    const ORDER: usize = 1;
+}
+impl BdfOrder for Order2 {
+    const ORDER: usize = 2;
+}
+impl BdfOrder for Order3 {
+    const ORDER: usize = 3;
+}
+impl BdfOrder for Order4 {
+    const ORDER: usize = 4;
+}
+impl BdfOrder for Order5 {
+    const ORDER: usize = 5;
+}
+
+/// Backward Euler scheme
+pub type BackwardEuler<F, J = NumericalJacobian> = BDF<F, Order1, J>;
+/// 2nd order BDF scheme
+pub type BDF2<F, J = NumericalJacobian> = BDF<F, Order2, J>;
+/// 3rd order BDF scheme
+pub type BDF3<F, J = NumericalJacobian> = BDF<F, Order3, J>;
+/// 4th order BDF scheme
+pub type BDF4<F, J = NumericalJacobian> = BDF<F, Order4, J>;
+/// 5th order BDF scheme
+pub type BDF5<F, J = NumericalJacobian> = BDF<F, Order5, J>;
+
+/// Backward differentiation formula with a fixed time-step
+///
+/// The scheme keeps the history of the states it returned. The history is discarded
+/// when `iterate` is called with another state, or when the time or time-step is changed,
+/// and the first steps after that are taken by `RadauIIA` to keep the order.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, J: serde::Serialize, F::Scalar: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, J: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct BDF<F: ExplicitT<Dim = Ix1>, K: BdfOrder, J: JacobianMatrix<F> = NumericalJacobian> {
+    f: F,
+    jac: J,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    newton: Newton<<F::Scalar as Scalar>::Real>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    error: Option<Arc<NewtonError>>,
+    /// states at `t, t - dt, ...` and the time and time-step when the history is taken
+    hist: Vec<Array1<F::Scalar>>,
+    hist_t: <F::Scalar as Scalar>::Real,
+    hist_dt: <F::Scalar as Scalar>::Real,
+    order: PhantomData<K>,
+}
+
+impl<F, K, J> BDF<F, K, J>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    K: BdfOrder,
+    J: JacobianMatrix<F>,
+{
+    /// Proceed one step, or return the error without changing the state
+    fn newton_step<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Ix1>,
+    ) -> Result<&'a mut ArrayBase<S, Ix1>, NewtonError>
+    where
+        S: DataMut<Elem = F::Scalar>,
+    {
+        let valid = !self.hist.is_empty()
+            && self.hist_t == self.t
+            && self.hist_dt == self.dt
+            && self.hist[0] == *x;
+        if !valid {
+            self.hist.clear();
+            self.hist.push(x.to_owned());
+        }
+        let y = if self.hist.len() < K::ORDER && K::ORDER > 1 {
+            radau_step(
+                &mut self.f,
+                &mut self.jac,
+                &self.newton,
+                self.t,
+                self.dt,
+                &self.hist[0],
+            )?
+        } else {
+            bdf_step(
+                &mut self.f,
+                &mut self.jac,
+                &self.newton,
+                self.t,
+                self.dt,
+                &self.hist,
+            )?
+        };
+        x.assign(&y);
+        self.hist.insert(0, y);
+        self.hist.truncate(K::ORDER);
+        self.t += self.dt;
+        self.hist_t = self.t;
+        self.hist_dt = self.dt;
+        Ok(x)
+    }
+}
+
+impl<F: ExplicitT<Dim = Ix1>, K: BdfOrder, J: JacobianMatrix<F>> TimeStep for BDF<F, K, J> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<F: ExplicitT<Dim = Ix1>, K: BdfOrder, J: JacobianMatrix<F>> NewtonIteration for BDF<F, K, J> {
+    fn get_newton(&self) -> (Self::Time, usize) {
+        (self.newton.tol, self.newton.max_iter)
+    }
+
+    fn set_newton(&mut self, tol: Self::Time, max_iter: usize) {
+        self.newton = Newton { tol, max_iter };
+    }
+
+    fn last_error(&self) -> Option<&NewtonError> {
+        self.error.as_deref()
+    }
+}
+
+impl<F, K, J> Scheme for BDF<F, K, J>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    K: BdfOrder,
+    J: JacobianMatrix<F>,
+{
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        Self {
+            f,
+            jac: J::default(),
+            dt,
+            t: Zero::zero(),
+            newton: Newton::new(),
+            error: None,
+            hist: Vec::with_capacity(K::ORDER),
+            hist_t: Zero::zero(),
+            hist_dt: Zero::zero(),
+            order: PhantomData,
+        }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: ExplicitT<Dim = Ix1>, K: BdfOrder, J: JacobianMatrix<F>> ModelSpec for BDF<F, K, J> {
+    type Scalar = F::Scalar;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.f.model_size()
+    }
+}
+
+impl<F, K, J> TimeEvolution for BDF<F, K, J>
+where
+    F: ExplicitT<Dim = Ix1>,
+    F::Scalar: Lapack,
+    K: BdfOrder,
+    J: JacobianMatrix<F>,
+{
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.error = self.newton_step(x).err().map(Arc::new);
+        if self.error.is_some() {
+            x.fill(F::Scalar::from_real(Float::nan()));
+            self.hist.clear();
+            self.t += self.dt;
+        }
+        x
+    }
+
+    /// The state is not changed when the Newton iteration fails
+    fn try_iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Ix1>,
+    ) -> crate::error::Result<&'a mut ArrayBase<S, Ix1>>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.error = None;
+        self.newton_step(x)?;
+        check_finite(x, self.t)?;
+        Ok(x)
+    }
+}
@@ -0,0 +1,818 @@
+//! Writers of trajectories into CSV, NumPy `.npy`/`.npz` and a chunked binary format
+//!
+//! The writers take the states one by one, and do not keep the trajectory in memory.
+//! The shape of the states is fixed by the first state.
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::io::*;
+//! use eom::traits::*;
+//! use ndarray::arr1;
+//! let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+//! let ts = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo);
+//! let mut csv = Csv::new(Vec::new()).header("time", &["x", "y", "z"]);
+//! csv.write_all(ts.timed().take(100)).unwrap();
+//! let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+//! assert!(text.starts_with("time,x,y,z\n"));
+//! ```
+
+use ndarray::*;
+use ndarray_linalg::*;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Scalar types which can be written into files
+pub trait Element: Scalar {
+    /// type descriptor of NumPy, e.g. `<f8`
+    const DESCR: &'static str;
+    /// Write in little endian, the real part first for complex
+    fn write_le<W: Write>(self, w: &mut W) -> io::Result<()>;
+    fn read_le<R: Read>(r: &mut R) -> io::Result<Self>;
+    /// Write as CSV field(s), the real and imaginary parts are separated for complex
+    fn write_csv<W: Write>(self, w: &mut W) -> io::Result<()>;
+    /// Column names for a component named `name`, `name.re,name.im` for complex
+    fn csv_header(name: &str) -> Vec<String>;
+}
+
+macro_rules! impl_element_real {
+    ($real:ty, $descr:expr) => {
+        impl Element for $real {
+            const DESCR: &'static str = $descr;
+            fn write_le<W: Write>(self, w: &mut W) -> io::Result<()> {
+                w.write_all(&self.to_le_bytes())
+            }
+            fn read_le<R: Read>(r: &mut R) -> io::Result<Self> {
+                let mut buf = [0; std::mem::size_of::<$real>()];
+                r.read_exact(&mut buf)?;
+                Ok(<$real>::from_le_bytes(buf))
+            }
+            fn write_csv<W: Write>(self, w: &mut W) -> io::Result<()> {
+                write!(w, "{}", self)
+            }
+            fn csv_header(name: &str) -> Vec<String> {
+                vec![name.to_string()]
+            }
+        }
+    };
+}
+
+impl_element_real!(f32, "<f4");
+impl_element_real!(f64, "<f8");
+
+macro_rules! impl_element_complex {
+    ($complex:ty, $real:ty, $descr:expr) => {
+        impl Element for $complex {
+            const DESCR: &'static str = $descr;
+            fn write_le<W: Write>(self, w: &mut W) -> io::Result<()> {
+                self.re.write_le(w)?;
+                self.im.write_le(w)
+            }
+            fn read_le<R: Read>(r: &mut R) -> io::Result<Self> {
+                let re = <$real>::read_le(r)?;
+                let im = <$real>::read_le(r)?;
+                Ok(<$complex>::new(re, im))
+            }
+            fn write_csv<W: Write>(self, w: &mut W) -> io::Result<()> {
+                write!(w, "{},{}", self.re, self.im)
+            }
+            fn csv_header(name: &str) -> Vec<String> {
+                vec![format!("{}.re", name), format!("{}.im", name)]
+            }
+        }
+    };
+}
+
+impl_element_complex!(c32, f32, "<c8");
+impl_element_complex!(c64, f64, "<c16");
+
+/// Common interface of the writers
+pub trait TrajectoryWriter<A: Element> {
+    /// Append the state `x` at the time `t`
+    fn write_state<T, S, D>(&mut self, t: T, x: &ArrayBase<S, D>) -> io::Result<()>
+    where
+        T: Into<f64>,
+        S: Data<Elem = A>,
+        D: Dimension;
+
+    /// Append all `(t, x)` pairs, e.g. of `TimeSeries::timed`, and return the number of them
+    ///
+    /// Since the time series is infinite, limit it by `take` or `take_while`.
+    fn write_all<I, T, S, D>(&mut self, series: I) -> io::Result<usize>
+    where
+        I: IntoIterator<Item = (T, ArrayBase<S, D>)>,
+        T: Into<f64>,
+        S: Data<Elem = A>,
+        D: Dimension,
+    {
+        let mut n = 0;
+        for (t, x) in series {
+            self.write_state(t, &x)?;
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+fn invalid_input(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Fix the shape by the first state, and check the others.
+/// Returns `true` for the first state.
+fn check_shape(shape: &mut Option<Vec<usize>>, x: &[usize]) -> io::Result<bool> {
+    match shape {
+        Some(s) if s.as_slice() == x => Ok(false),
+        Some(s) => Err(invalid_input(format!(
+            "Shape of state {:?} differs from the first state {:?}",
+            x, s
+        ))),
+        None => {
+            *shape = Some(x.to_vec());
+            Ok(true)
+        }
+    }
+}
+
+/// Encode `x` in the standard order into `buf`
+fn encode<A, S, D>(x: &ArrayBase<S, D>, buf: &mut Vec<u8>) -> io::Result<()>
+where
+    A: Element,
+    S: Data<Elem = A>,
+    D: Dimension,
+{
+    buf.clear();
+    for &a in x.iter() {
+        a.write_le(buf)?;
+    }
+    Ok(())
+}
+
+/// Writer of CSV with a header line
+///
+/// Each line consists of the time and the components of the state in the standard order.
+#[derive(Debug)]
+pub struct Csv<W: Write> {
+    inner: W,
+    time: String,
+    names: Option<Vec<String>>,
+    columns: Option<Vec<String>>,
+    shape: Option<Vec<usize>>,
+}
+
+impl<W: Write> Csv<W> {
+    /// Writer with the default header `t,x0,x1,...`
+    pub fn new(inner: W) -> Self {
+        Csv {
+            inner,
+            time: "t".to_string(),
+            names: None,
+            columns: None,
+            shape: None,
+        }
+    }
+
+    /// Set the names of the time and the components of the state
+    pub fn header<N: ToString>(mut self, time: &str, names: &[N]) -> Self {
+        self.time = time.to_string();
+        self.names = Some(names.iter().map(|n| n.to_string()).collect());
+        self
+    }
+
+    /// Set the names of the time and all columns as they are
+    ///
+    /// Complex components take two columns, e.g. `x0.re,x0.im,x1.re,x1.im,...`.
+    pub fn columns<N: ToString>(mut self, time: &str, columns: &[N]) -> Self {
+        self.time = time.to_string();
+        self.columns = Some(columns.iter().map(|n| n.to_string()).collect());
+        self
+    }
+
+    /// Flush and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_header<A: Element>(&mut self, size: usize) -> io::Result<()> {
+        let columns = match (&self.columns, &self.names) {
+            (Some(columns), _) if columns.len() == size * A::csv_header("").len() => {
+                columns.clone()
+            }
+            (Some(columns), _) => {
+                return Err(invalid_input(format!(
+                    "{} columns are given for the state of size {}",
+                    columns.len(),
+                    size
+                )))
+            }
+            (None, Some(names)) if names.len() == size => {
+                names.iter().flat_map(|n| A::csv_header(n)).collect()
+            }
+            (None, Some(names)) => {
+                return Err(invalid_input(format!(
+                    "{} names are given for the state of size {}",
+                    names.len(),
+                    size
+                )))
+            }
+            (None, None) => (0..size)
+                .flat_map(|i| A::csv_header(&format!("x{}", i)))
+                .collect(),
+        };
+        write!(self.inner, "{}", self.time)?;
+        for column in columns {
+            write!(self.inner, ",{}", column)?;
+        }
+        writeln!(self.inner)
+    }
+}
+
+impl<A: Element, W: Write> TrajectoryWriter<A> for Csv<W> {
+    fn write_state<T, S, D>(&mut self, t: T, x: &ArrayBase<S, D>) -> io::Result<()>
+    where
+        T: Into<f64>,
+        S: Data<Elem = A>,
+        D: Dimension,
+    {
+        if check_shape(&mut self.shape, x.shape())? {
+            self.write_header::<A>(x.len())?;
+        }
+        write!(self.inner, "{}", t.into())?;
+        for &a in x.iter() {
+            write!(self.inner, ",")?;
+            a.write_csv(&mut self.inner)?;
+        }
+        writeln!(self.inner)
+    }
+}
+
+/// Format the shape as a Python tuple
+fn py_tuple<T: ToString>(shape: &[T]) -> String {
+    let s: Vec<_> = shape.iter().map(|n| n.to_string()).collect();
+    if s.len() == 1 {
+        format!("({},)", s[0])
+    } else {
+        format!("({})", s.join(", "))
+    }
+}
+
+/// Header of NPY format version 1.0 for the dictionary `dict`
+///
+/// The header is padded to `len` bytes if given, to be rewritten in place later.
+fn npy_header(dict: &str, len: Option<usize>) -> Vec<u8> {
+    let mut header = b"\x93NUMPY\x01\x00\x00\x00".to_vec();
+    header.extend_from_slice(dict.as_bytes());
+    // the header ends with a newline, and the data is aligned to 64 bytes
+    let total = match len {
+        Some(len) => len,
+        None => (header.len() + 64) / 64 * 64,
+    };
+    assert!(header.len() < total, "NPY header overflow");
+    header.resize(total - 1, b' ');
+    header.push(b'\n');
+    let dict_len = (total - 10) as u16;
+    header[8..10].copy_from_slice(&dict_len.to_le_bytes());
+    header
+}
+
+/// Length of a header whose shape may grow up to `u64::MAX` records
+fn npy_header_len(dict: impl Fn(u64) -> String) -> usize {
+    npy_header(&dict(u64::MAX), None).len()
+}
+
+/// Writer of NPY file of a structured array with the fields `t` and `x`
+///
+/// The array has the shape `(N,)` for `N` states, and the dtype
+/// `[('t', '<f8'), ('x', DESCR, shape)]` where `shape` is the shape of a state.
+/// `numpy.load(path)` gives the times by `['t']` and the states by `['x']`.
+///
+/// The header is rewritten with the number of states by `finish` or drop.
+#[derive(Debug)]
+pub struct Npy<A: Element, W: Write + Seek> {
+    inner: Option<W>,
+    start: u64,
+    header_len: usize,
+    count: u64,
+    shape: Option<Vec<usize>>,
+    buf: Vec<u8>,
+    phantom: std::marker::PhantomData<A>,
+}
+
+impl<A: Element, W: Write + Seek> Npy<A, W> {
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        let start = inner.stream_position()?;
+        Ok(Npy {
+            inner: Some(inner),
+            start,
+            header_len: 0,
+            count: 0,
+            shape: None,
+            buf: Vec::new(),
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    fn dict(&self, count: u64) -> String {
+        let shape = self.shape.as_deref().unwrap_or(&[]);
+        let x = if shape.is_empty() {
+            format!("('x', '{}')", A::DESCR)
+        } else {
+            format!("('x', '{}', {})", A::DESCR, py_tuple(shape))
+        };
+        format!(
+            "{{'descr': [('t', '<f8'), {}], 'fortran_order': False, 'shape': ({},), }}",
+            x, count
+        )
+    }
+
+    fn begin(&mut self) -> io::Result<()> {
+        self.header_len = npy_header_len(|n| self.dict(n));
+        let header = npy_header(&self.dict(0), Some(self.header_len));
+        self.inner.as_mut().unwrap().write_all(&header)
+    }
+
+    fn complete(&mut self) -> io::Result<()> {
+        if self.shape.is_none() {
+            self.shape = Some(Vec::new());
+            self.begin()?;
+        }
+        let header = npy_header(&self.dict(self.count), Some(self.header_len));
+        let inner = self.inner.as_mut().unwrap();
+        inner.seek(SeekFrom::Start(self.start))?;
+        inner.write_all(&header)?;
+        inner.seek(SeekFrom::End(0))?;
+        inner.flush()
+    }
+
+    /// Complete the header and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.complete()?;
+        Ok(self.inner.take().unwrap())
+    }
+}
+
+impl<A: Element, W: Write + Seek> Drop for Npy<A, W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.complete();
+        }
+    }
+}
+
+impl<A: Element, W: Write + Seek> TrajectoryWriter<A> for Npy<A, W> {
+    fn write_state<T, S, D>(&mut self, t: T, x: &ArrayBase<S, D>) -> io::Result<()>
+    where
+        T: Into<f64>,
+        S: Data<Elem = A>,
+        D: Dimension,
+    {
+        if check_shape(&mut self.shape, x.shape())? {
+            self.begin()?;
+        }
+        encode(x, &mut self.buf)?;
+        let inner = self.inner.as_mut().unwrap();
+        inner.write_all(&t.into().to_le_bytes())?;
+        inner.write_all(&self.buf)?;
+        self.count += 1;
+        Ok(())
+    }
+}
+
+/// Date 1980-01-01 in the MS-DOS format used in ZIP
+const ZIP_DATE: u16 = (1 << 5) | 1;
+
+/// Sizes and offsets of ZIP archives without the ZIP64 extension are 32-bit
+fn zip_u32(n: u64) -> io::Result<u32> {
+    if n > u64::from(u32::MAX) {
+        Err(invalid_input(
+            "NPZ larger than 4 GiB is not supported".to_string(),
+        ))
+    } else {
+        Ok(n as u32)
+    }
+}
+
+/// Entry of a stored (uncompressed) ZIP archive
+struct ZipEntry {
+    name: &'static str,
+    offset: u64,
+    crc: u32,
+    size: u64,
+}
+
+impl ZipEntry {
+    /// Local file header, or central directory header if `central`
+    fn header(&self, central: bool) -> io::Result<Vec<u8>> {
+        let mut h = Vec::new();
+        if central {
+            h.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+            h.extend_from_slice(&20_u16.to_le_bytes()); // version made by
+        } else {
+            h.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+        }
+        h.extend_from_slice(&20_u16.to_le_bytes()); // version needed to extract
+        h.extend_from_slice(&0_u16.to_le_bytes()); // flags
+        h.extend_from_slice(&0_u16.to_le_bytes()); // stored
+        h.extend_from_slice(&0_u16.to_le_bytes()); // time
+        h.extend_from_slice(&ZIP_DATE.to_le_bytes());
+        h.extend_from_slice(&self.crc.to_le_bytes());
+        h.extend_from_slice(&zip_u32(self.size)?.to_le_bytes()); // compressed
+        h.extend_from_slice(&zip_u32(self.size)?.to_le_bytes()); // uncompressed
+        h.extend_from_slice(&(self.name.len() as u16).to_le_bytes());
+        h.extend_from_slice(&0_u16.to_le_bytes()); // extra field
+        if central {
+            h.extend_from_slice(&0_u16.to_le_bytes()); // comment
+            h.extend_from_slice(&0_u16.to_le_bytes()); // disk
+            h.extend_from_slice(&0_u16.to_le_bytes()); // internal attributes
+            h.extend_from_slice(&0_u32.to_le_bytes()); // external attributes
+            h.extend_from_slice(&zip_u32(self.offset)?.to_le_bytes());
+        }
+        h.extend_from_slice(self.name.as_bytes());
+        Ok(h)
+    }
+}
+
+/// Writer of NPZ archive with the arrays `t` and `x`
+///
+/// `t` is the times of the shape `(N,)`, and `x` is the states of the shape `(N, ...)`
+/// for `N` states. The states are streamed into the archive,
+/// but the times are kept in memory until `finish`.
+/// The archive is not compressed, and must be smaller than 4 GiB.
+#[derive(Debug)]
+pub struct Npz<A: Element, W: Write + Seek> {
+    inner: Option<W>,
+    start: u64,
+    header_len: usize,
+    times: Vec<f64>,
+    shape: Option<Vec<usize>>,
+    crc: crc32fast::Hasher,
+    size: u64,
+    buf: Vec<u8>,
+    phantom: std::marker::PhantomData<A>,
+}
+
+impl<A: Element, W: Write + Seek> Npz<A, W> {
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        let start = inner.stream_position()?;
+        Ok(Npz {
+            inner: Some(inner),
+            start,
+            header_len: 0,
+            times: Vec::new(),
+            shape: None,
+            crc: crc32fast::Hasher::new(),
+            size: 0,
+            buf: Vec::new(),
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    fn dict(&self, count: u64) -> String {
+        let mut shape = vec![count.to_string()];
+        shape.extend(self.shape.iter().flatten().map(|n| n.to_string()));
+        format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+            A::DESCR,
+            py_tuple(&shape)
+        )
+    }
+
+    /// Write the headers of `x.npy` with placeholders
+    fn begin(&mut self) -> io::Result<()> {
+        self.header_len = npy_header_len(|n| self.dict(n));
+        let entry = ZipEntry {
+            name: "x.npy",
+            offset: 0,
+            crc: 0,
+            size: 0,
+        };
+        let header = npy_header(&self.dict(0), Some(self.header_len));
+        let inner = self.inner.as_mut().unwrap();
+        inner.write_all(&entry.header(false)?)?;
+        inner.write_all(&header)
+    }
+
+    fn complete(&mut self) -> io::Result<()> {
+        if self.shape.is_none() {
+            self.shape = Some(Vec::new());
+            self.begin()?;
+        }
+        let count = self.times.len() as u64;
+        let header = npy_header(&self.dict(count), Some(self.header_len));
+        let mut crc = crc32fast::Hasher::new();
+        crc.update(&header);
+        crc.combine(&self.crc);
+        let x = ZipEntry {
+            name: "x.npy",
+            offset: 0,
+            crc: crc.finalize(),
+            size: header.len() as u64 + self.size,
+        };
+        let local = x.header(false)?;
+
+        let inner = self.inner.as_mut().unwrap();
+        inner.seek(SeekFrom::Start(self.start))?;
+        inner.write_all(&local)?;
+        inner.write_all(&header)?;
+        let t_offset = inner.seek(SeekFrom::End(0))? - self.start;
+
+        let mut t_data = npy_header(
+            &format!(
+                "{{'descr': '<f8', 'fortran_order': False, 'shape': {}, }}",
+                py_tuple(&[count])
+            ),
+            None,
+        );
+        for t in &self.times {
+            t_data.extend_from_slice(&t.to_le_bytes());
+        }
+        let t = ZipEntry {
+            name: "t.npy",
+            offset: t_offset,
+            crc: crc32fast::hash(&t_data),
+            size: t_data.len() as u64,
+        };
+        inner.write_all(&t.header(false)?)?;
+        inner.write_all(&t_data)?;
+
+        let cd_offset = zip_u32(inner.stream_position()? - self.start)?;
+        let mut cd = x.header(true)?;
+        cd.extend(t.header(true)?);
+        let cd_size = zip_u32(cd.len() as u64)?;
+        inner.write_all(&cd)?;
+        let mut end = Vec::new();
+        end.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+        end.extend_from_slice(&0_u16.to_le_bytes()); // disk
+        end.extend_from_slice(&0_u16.to_le_bytes()); // disk of central directory
+        end.extend_from_slice(&2_u16.to_le_bytes()); // entries in this disk
+        end.extend_from_slice(&2_u16.to_le_bytes()); // entries
+        end.extend_from_slice(&cd_size.to_le_bytes());
+        end.extend_from_slice(&cd_offset.to_le_bytes());
+        end.extend_from_slice(&0_u16.to_le_bytes()); // comment
+        inner.write_all(&end)?;
+        inner.flush()
+    }
+
+    /// Write the times and the index of the archive, and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.complete()?;
+        Ok(self.inner.take().unwrap())
+    }
+}
+
+impl<A: Element, W: Write + Seek> Drop for Npz<A, W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.complete();
+        }
+    }
+}
+
+impl<A: Element, W: Write + Seek> TrajectoryWriter<A> for Npz<A, W> {
+    fn write_state<T, S, D>(&mut self, t: T, x: &ArrayBase<S, D>) -> io::Result<()>
+    where
+        T: Into<f64>,
+        S: Data<Elem = A>,
+        D: Dimension,
+    {
+        if check_shape(&mut self.shape, x.shape())? {
+            self.begin()?;
+        }
+        encode(x, &mut self.buf)?;
+        self.inner.as_mut().unwrap().write_all(&self.buf)?;
+        self.crc.update(&self.buf);
+        self.size += self.buf.len() as u64;
+        self.times.push(t.into());
+        Ok(())
+    }
+}
+
+/// Magic bytes of the chunked format
+const CHUNKED_MAGIC: &[u8; 8] = b"EOMCHUNK";
+const CHUNKED_VERSION: u32 = 1;
+
+/// Writer of a compact binary format read by [ChunkedReader]
+///
+/// The file starts with the header
+///
+/// - magic bytes `EOMCHUNK` and the version `1` as `u32`
+/// - length of the NumPy type descriptor as `u8` and the descriptor, e.g. `<f8`
+/// - number of axes of a state as `u32`, and the lengths of axes as `u64`
+///
+/// followed by chunks consisting of the number of states `n` as `u64`,
+/// `n` times as `f64`, and `n` states in the standard order.
+/// All numbers are little endian. Since a chunk is written at once,
+/// the chunks before a crash are readable.
+///
+/// [ChunkedReader]: struct.ChunkedReader.html
+#[derive(Debug)]
+pub struct Chunked<A: Element, W: Write> {
+    inner: Option<W>,
+    chunk: usize,
+    shape: Option<Vec<usize>>,
+    times: Vec<f64>,
+    data: Vec<u8>,
+    phantom: std::marker::PhantomData<A>,
+}
+
+impl<A: Element, W: Write> Chunked<A, W> {
+    /// Writer with chunks of 1024 states
+    pub fn new(inner: W) -> Self {
+        Chunked {
+            inner: Some(inner),
+            chunk: 1024,
+            shape: None,
+            times: Vec::new(),
+            data: Vec::new(),
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Set the number of states in a chunk
+    pub fn chunk_size(mut self, chunk: usize) -> Self {
+        assert!(chunk > 0, "Chunk must contain at least one state");
+        self.chunk = chunk;
+        self
+    }
+
+    fn write_header(&mut self, shape: &[usize]) -> io::Result<()> {
+        let mut h = CHUNKED_MAGIC.to_vec();
+        h.extend_from_slice(&CHUNKED_VERSION.to_le_bytes());
+        h.push(A::DESCR.len() as u8);
+        h.extend_from_slice(A::DESCR.as_bytes());
+        h.extend_from_slice(&(shape.len() as u32).to_le_bytes());
+        for &n in shape {
+            h.extend_from_slice(&(n as u64).to_le_bytes());
+        }
+        self.inner.as_mut().unwrap().write_all(&h)
+    }
+
+    /// Write the buffered states as a chunk
+    pub fn flush_chunk(&mut self) -> io::Result<()> {
+        if self.times.is_empty() {
+            return Ok(());
+        }
+        let mut chunk = Vec::with_capacity(8 * (self.times.len() + 1) + self.data.len());
+        chunk.extend_from_slice(&(self.times.len() as u64).to_le_bytes());
+        for t in &self.times {
+            chunk.extend_from_slice(&t.to_le_bytes());
+        }
+        chunk.extend_from_slice(&self.data);
+        let inner = self.inner.as_mut().unwrap();
+        inner.write_all(&chunk)?;
+        inner.flush()?;
+        self.times.clear();
+        self.data.clear();
+        Ok(())
+    }
+
+    /// Write the last chunk and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush_chunk()?;
+        Ok(self.inner.take().unwrap())
+    }
+}
+
+impl<A: Element, W: Write> Drop for Chunked<A, W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.flush_chunk();
+        }
+    }
+}
+
+impl<A: Element, W: Write> TrajectoryWriter<A> for Chunked<A, W> {
+    fn write_state<T, S, D>(&mut self, t: T, x: &ArrayBase<S, D>) -> io::Result<()>
+    where
+        T: Into<f64>,
+        S: Data<Elem = A>,
+        D: Dimension,
+    {
+        if check_shape(&mut self.shape, x.shape())? {
+            self.write_header(x.shape())?;
+        }
+        self.times.push(t.into());
+        for &a in x.iter() {
+            a.write_le(&mut self.data)?;
+        }
+        if self.times.len() >= self.chunk {
+            self.flush_chunk()?;
+        }
+        Ok(())
+    }
+}
+
+/// Reader of the format written by [Chunked]
+///
+/// This is an iterator of `(t, x)`, and reads a chunk of times at once
+/// and the states one by one.
+///
+/// [Chunked]: struct.Chunked.html
+#[derive(Debug)]
+pub struct ChunkedReader<A: Element, R: Read> {
+    inner: R,
+    shape: Vec<usize>,
+    times: std::vec::IntoIter<f64>,
+    phantom: std::marker::PhantomData<A>,
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+impl<A: Element, R: Read> ChunkedReader<A, R> {
+    /// Read the header, and check the type of the elements
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        inner.read_exact(&mut magic)?;
+        if &magic != CHUNKED_MAGIC {
+            return Err(invalid_data("Not a chunked trajectory".to_string()));
+        }
+        let version = read_u32(&mut inner)?;
+        if version != CHUNKED_VERSION {
+            return Err(invalid_data(format!("Unknown version {}", version)));
+        }
+        let mut len = [0; 1];
+        inner.read_exact(&mut len)?;
+        let mut descr = vec![0; len[0] as usize];
+        inner.read_exact(&mut descr)?;
+        if descr != A::DESCR.as_bytes() {
+            return Err(invalid_data(format!(
+                "Elements are {}, not {}",
+                String::from_utf8_lossy(&descr),
+                A::DESCR
+            )));
+        }
+        let ndim = read_u32(&mut inner)?;
+        let shape = (0..ndim)
+            .map(|_| read_u64(&mut inner).map(|n| n as usize))
+            .collect::<io::Result<_>>()?;
+        Ok(ChunkedReader {
+            inner,
+            shape,
+            times: Vec::new().into_iter(),
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Shape of a state
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Read the times of the next chunk, or `None` at the end of file
+    fn next_chunk(&mut self) -> io::Result<Option<()>> {
+        let mut buf = [0; 8];
+        let mut read = 0;
+        while read < buf.len() {
+            match self.inner.read(&mut buf[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let n = u64::from_le_bytes(buf);
+        let times = (0..n)
+            .map(|_| read_u64(&mut self.inner).map(f64::from_bits))
+            .collect::<io::Result<Vec<_>>>()?;
+        self.times = times.into_iter();
+        Ok(Some(()))
+    }
+
+    fn read_state(&mut self) -> io::Result<ArrayD<A>> {
+        let size = self.shape.iter().product();
+        let data = (0..size)
+            .map(|_| A::read_le(&mut self.inner))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Array::from_shape_vec(self.shape.clone(), data).unwrap())
+    }
+}
+
+impl<A: Element, R: Read> Iterator for ChunkedReader<A, R> {
+    type Item = io::Result<(f64, ArrayD<A>)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(t) = self.times.next() {
+                return Some(self.read_state().map(|x| (t, x)));
+            }
+            match self.next_chunk() {
+                Ok(Some(())) => continue,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
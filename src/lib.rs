@@ -1,8 +1,25 @@
 //! Configurable ODE/PDE solver
 
+pub mod adaptive;
 pub mod adaptor;
+#[cfg(feature = "serde")]
+pub mod checkpoint;
+pub mod dense;
+pub mod dynamic;
+pub mod ensemble;
+pub mod error;
+pub mod event;
 pub mod explicit;
+pub mod fixed_point;
+pub mod implicit;
+pub mod io;
 pub mod lyapunov;
 pub mod ode;
+pub mod pde;
+pub mod periodic_orbit;
+pub mod poincare;
 pub mod semi_implicit;
+pub mod stochastic;
+pub mod symplectic;
+pub mod tangent;
 pub mod traits;
@@ -2,11 +2,18 @@
 
 use ndarray::*;
 use ndarray_linalg::*;
-use num_traits::{Float, FromPrimitive, One};
+use num_traits::{Float, FromPrimitive, One, ToPrimitive};
 
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::error::Result;
+use crate::io::Element;
 use crate::traits::*;
 
 /// Jacobian operator using numerical-differentiation
+///
+/// The time of `TimeEvolution` is restored after each evaluation,
+/// i.e. the Jacobian is always taken at the time when it is created.
 pub struct Jacobian<'jac, A, D, TEO>
 where
     A: Scalar + Lapack,
@@ -14,6 +21,7 @@ where
     TEO: 'jac + TimeEvolution<Scalar = A, Dim = D>,
 {
     f: &'jac mut TEO,
+    t: TEO::Time,
     x: Array<A, D>,
     fx: Array<A, D>,
     alpha: A::Real,
@@ -58,9 +66,11 @@ where
     where
         TEO: 'jac,
     {
+        let t = f.time();
         let mut fx = x.clone();
         f.iterate(&mut fx);
-        Jacobian { f, x, fx, alpha }
+        f.set_time(t);
+        Jacobian { f, t, x, fx, alpha }
     }
 
     pub fn apply(&mut self, mut dx: Array<A, D>) -> Array<A, D> {
@@ -78,6 +88,7 @@ where
             *dx = x + dx.mul_real(n);
         });
         let x_dx = self.f.iterate(dx);
+        self.f.set_time(self.t);
         Zip::from(&mut *x_dx).and(&self.fx).apply(|x_dx, &fx| {
             *x_dx = (*x_dx - fx).div_real(n);
         });
@@ -116,9 +127,56 @@ where
     A: Scalar + Lapack,
     TEO: TimeEvolution<Scalar = A, Dim = Ix1> + TimeStep<Time = A::Real>,
 {
-    let n = teo.model_size();
-    let dur = teo.get_dt() * TEO::Time::from_usize(duration).unwrap();
-    Series::new(teo, x, alpha)
+    let dt = teo.get_dt();
+    exponents_of(Series::new(teo, x, alpha), dt, duration)
+}
+
+/// Calculate all Lyapunov exponents using the exact tangent propagation
+pub fn exponents_exact<A, TEO>(teo: TEO, x: Array1<A>, duration: usize) -> Array1<A::Real>
+where
+    A: Scalar + Lapack,
+    TEO: TangentScheme<Scalar = A, Time = A::Real>,
+    TEO::Core: Clone,
+{
+    let dt = teo.get_dt();
+    exponents_of(Series::exact(teo, x), dt, duration)
+}
+
+/// Calculate all Lyapunov exponents, or returns the error when the state or tangent vectors
+/// become NaN or infinity, or the QR decomposition fails
+pub fn try_exponents<A, TEO>(
+    teo: TEO,
+    x: Array1<A>,
+    alpha: A::Real,
+    duration: usize,
+) -> Result<Array1<A::Real>>
+where
+    A: Scalar + Lapack,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1> + TimeStep<Time = A::Real>,
+{
+    let dt = teo.get_dt();
+    let mut series = Series::new(teo, x, alpha);
+    let n = series.prop.model_size();
+    let dur = dt * A::Real::from_usize(duration).unwrap();
+    for _ in 0..duration / 10 {
+        series.try_next()?;
+    }
+    let mut l = Array::zeros(n);
+    for _ in 0..duration {
+        let (_x, _q, r) = series.try_next()?;
+        azip!((l in &mut l, &r in &r.diag()) *l += Float::ln(r.abs()) / dur);
+    }
+    Ok(l)
+}
+
+fn exponents_of<A, P>(series: Series<A, P>, dt: A::Real, duration: usize) -> Array1<A::Real>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    let n = series.prop.model_size();
+    let dur = dt * A::Real::from_usize(duration).unwrap();
+    series
         .map(|(_x, _q, r)| r.diag().map(|x| num_traits::Float::ln(x.abs())))
         .skip(duration / 10)
         .take(duration)
@@ -128,76 +186,675 @@ where
         })
 }
 
+/// Propagation of the state and tangent vectors used in `Series`
+pub trait Propagator {
+    type Scalar: Scalar + Lapack;
+
+    fn model_size(&self) -> usize;
+
+    /// Current simulation time, used in error reports
+    fn time(&self) -> f64;
+
+    /// Advance the state `x` and the tangent vectors (columns of `q`) by one step
+    fn propagate(&mut self, x: &mut Array1<Self::Scalar>, q: &mut Array2<Self::Scalar>);
+}
+
+/// Tangent propagation by the numerical differentiation of `TimeEvolution`
+#[derive(Debug, Clone)]
+pub struct FiniteDifference<TEO: TimeEvolution> {
+    teo: TEO,
+    alpha: <TEO::Scalar as Scalar>::Real,
+}
+
+impl<TEO: TimeEvolution> FiniteDifference<TEO> {
+    pub fn new(teo: TEO, alpha: <TEO::Scalar as Scalar>::Real) -> Self {
+        FiniteDifference { teo, alpha }
+    }
+}
+
+impl<A, TEO> Propagator for FiniteDifference<TEO>
+where
+    A: Scalar + Lapack,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+{
+    type Scalar = A;
+
+    fn model_size(&self) -> usize {
+        self.teo.model_size()
+    }
+
+    fn time(&self) -> f64 {
+        self.teo.time().to_f64().unwrap_or(f64::NAN)
+    }
+
+    fn propagate(&mut self, x: &mut Array1<A>, q: &mut Array2<A>) {
+        self.teo
+            .lin_approx(x.to_owned(), self.alpha)
+            .apply_multi_inplace(q);
+        self.teo.iterate(x);
+    }
+}
+
+/// Exact tangent propagation by integrating the variational equation along with the state
+///
+/// The tangent vectors are propagated by the Jacobian of the discrete time-evolution
+/// of the scheme, which is free from the truncation error of `FiniteDifference`.
+#[derive(Debug, Clone)]
+pub struct Exact<TEO: TangentScheme> {
+    teo: TEO::Tangent,
+    xq: Array2<TEO::Scalar>,
+}
+
+impl<TEO: TangentScheme> Exact<TEO> {
+    pub fn new(teo: TEO) -> Self
+    where
+        TEO::Core: Clone,
+    {
+        let n = teo.model_size();
+        Exact {
+            teo: teo.tangent(n),
+            xq: Array::zeros((n, n + 1)),
+        }
+    }
+}
+
+impl<A, TEO> Propagator for Exact<TEO>
+where
+    A: Scalar + Lapack,
+    TEO: TangentScheme<Scalar = A>,
+{
+    type Scalar = A;
+
+    fn model_size(&self) -> usize {
+        self.xq.nrows()
+    }
+
+    fn time(&self) -> f64 {
+        self.teo.time().to_f64().unwrap_or(f64::NAN)
+    }
+
+    fn propagate(&mut self, x: &mut Array1<A>, q: &mut Array2<A>) {
+        self.xq.column_mut(0).assign(x);
+        self.xq.slice_mut(s![.., 1..]).assign(q);
+        self.teo.iterate(&mut self.xq);
+        x.assign(&self.xq.column(0));
+        q.assign(&self.xq.slice(s![.., 1..]));
+    }
+}
+
 /// An iterator for successive QR-decomposition in Lyapunov analysis
 ///
 /// This is used both to calculate the Lyapunov exponents and covariant Lyapunov vector (CLV).
 /// The `Item` of the iterator is `(x, Q, R)` where `x` is the state vector.
 /// Be sure that each column of `Q` belongs to the tangent space at `x`,
 /// and `R` is a map from the previous tangent space (i.e. at `F^{-1}(x)`) to the space spand by `Q`.
-pub struct Series<A, TEO>
+#[derive(Clone)]
+pub struct Series<A, P>
 where
     A: Scalar + Lapack,
-    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+    P: Propagator<Scalar = A>,
 {
-    teo: TEO,
+    prop: P,
     x: Array1<A>,
     q: Array2<A>,
-    alpha: A::Real,
 }
 
-impl<A, TEO> Series<A, TEO>
+impl<A, TEO> Series<A, FiniteDifference<TEO>>
 where
     A: Scalar + Lapack,
     TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
 {
+    /// Tangent vectors are propagated by numerical differentiation with the step `alpha`
     pub fn new(teo: TEO, x: Array1<A>, alpha: A::Real) -> Self {
-        let q = Array::eye(teo.model_size());
-        Series { teo, x, q, alpha }
+        Self::with_propagator(FiniteDifference::new(teo, alpha), x)
     }
 }
 
-impl<A, TEO> Iterator for Series<A, TEO>
+impl<A, TEO> Series<A, Exact<TEO>>
 where
     A: Scalar + Lapack,
-    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+    TEO: TangentScheme<Scalar = A>,
+    TEO::Core: Clone,
+{
+    /// Tangent vectors are propagated exactly by the variational equation
+    pub fn exact(teo: TEO, x: Array1<A>) -> Self {
+        Self::with_propagator(Exact::new(teo), x)
+    }
+}
+
+impl<A, P> Series<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    pub fn with_propagator(prop: P, x: Array1<A>) -> Self {
+        let q = Array::eye(prop.model_size());
+        Series { prop, x, q }
+    }
+
+    /// Fallible version of `next`, which checks that the state and tangent vectors are finite
+    pub fn try_next(&mut self) -> Result<(Array1<A>, Array2<A>, Array2<A>)> {
+        self.prop.propagate(&mut self.x, &mut self.q);
+        check_finite(&self.x, self.prop.time())?;
+        check_finite(&self.q, self.prop.time())?;
+        let (q, r) = self.q.qr_square_inplace()?;
+        Ok((self.x.to_owned(), q.to_owned(), r))
+    }
+}
+
+impl<A, P> Iterator for Series<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
 {
     type Item = (Array1<A>, Array2<A>, Array2<A>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let q = self
-            .teo
-            .lin_approx(self.x.to_owned(), self.alpha)
-            .apply_multi_inplace(&mut self.q);
-        let (q, r) = q.qr_square_inplace().unwrap();
-        self.teo.iterate(&mut self.x);
+        self.prop.propagate(&mut self.x, &mut self.q);
+        let (q, r) = self.q.qr_square_inplace().unwrap();
         Some((self.x.to_owned(), q.to_owned(), r))
     }
 }
 
+fn growth<A: Scalar + Lapack>(r: &Array2<A>) -> Array1<A::Real> {
+    r.diag().map(|x| Float::ln(x.abs()))
+}
+
+impl<A, P> Series<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    /// Local growth rates `ln|R_ii| / dt` at each step
+    pub fn local_rates(self) -> LocalRates<A, P> {
+        LocalRates { series: self }
+    }
+
+    /// Running estimate of the Lyapunov exponents, i.e. the average since the beginning
+    pub fn running(self) -> Running<A, P> {
+        let start = self.prop.time();
+        let sum = Array::zeros(self.prop.model_size());
+        Running {
+            series: self,
+            start,
+            sum,
+        }
+    }
+
+    /// Finite-time Lyapunov exponents over successive windows of `window` steps
+    pub fn finite_time(self, window: usize) -> FiniteTime<A, P> {
+        assert!(window > 0, "Window must have one or more steps");
+        FiniteTime {
+            series: self,
+            window,
+        }
+    }
+}
+
+/// An iterator of the state and the local growth rates along the trajectory
+///
+/// The rates are divided by the elapsed simulation time of each step,
+/// and thus also valid for adaptive schemes.
+pub struct LocalRates<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    series: Series<A, P>,
+}
+
+impl<A, P> LocalRates<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    /// Fallible version of `next`
+    pub fn try_next(&mut self) -> Result<(Array1<A>, Array1<A::Real>)> {
+        let t = self.series.prop.time();
+        let (x, _q, r) = self.series.try_next()?;
+        let dt = A::Real::from_f64(self.series.prop.time() - t).unwrap();
+        Ok((x, growth(&r).mapv_into(|l| l / dt)))
+    }
+}
+
+impl<A, P> Iterator for LocalRates<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    type Item = (Array1<A>, Array1<A::Real>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.series.prop.time();
+        let (x, _q, r) = self.series.next()?;
+        let dt = A::Real::from_f64(self.series.prop.time() - t).unwrap();
+        Some((x, growth(&r).mapv_into(|l| l / dt)))
+    }
+}
+
+/// An iterator of finite-time Lyapunov exponents (FTLE) over successive windows
+///
+/// Each item is the growth of the tangent vectors within a window
+/// divided by the simulation time of the window.
+pub struct FiniteTime<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    series: Series<A, P>,
+    window: usize,
+}
+
+impl<A, P> FiniteTime<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    /// Fallible version of `next`
+    pub fn try_next(&mut self) -> Result<Array1<A::Real>> {
+        let t = self.series.prop.time();
+        let mut l = Array::zeros(self.series.prop.model_size());
+        for _ in 0..self.window {
+            let (_x, _q, r) = self.series.try_next()?;
+            l += &growth(&r);
+        }
+        let dur = A::Real::from_f64(self.series.prop.time() - t).unwrap();
+        Ok(l.mapv_into(|l| l / dur))
+    }
+}
+
+impl<A, P> Iterator for FiniteTime<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    type Item = Array1<A::Real>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.series.prop.time();
+        let mut l = Array::zeros(self.series.prop.model_size());
+        for _ in 0..self.window {
+            let (_x, _q, r) = self.series.next()?;
+            l += &growth(&r);
+        }
+        let dur = A::Real::from_f64(self.series.prop.time() - t).unwrap();
+        Some(l.mapv_into(|l| l / dur))
+    }
+}
+
+/// An iterator of the running estimate of the Lyapunov exponents
+///
+/// The item is the elapsed simulation time and the exponents averaged over it,
+/// which shows the convergence of the estimate.
+pub struct Running<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    series: Series<A, P>,
+    start: f64,
+    sum: Array1<A::Real>,
+}
+
+impl<A, P> Running<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    /// Fallible version of `next`
+    pub fn try_next(&mut self) -> Result<(A::Real, Array1<A::Real>)> {
+        let (_x, _q, r) = self.series.try_next()?;
+        Ok(self.accumulate(&r))
+    }
+
+    fn accumulate(&mut self, r: &Array2<A>) -> (A::Real, Array1<A::Real>) {
+        self.sum += &growth(r);
+        let t = A::Real::from_f64(self.series.prop.time() - self.start).unwrap();
+        (t, self.sum.mapv(|l| l / t))
+    }
+}
+
+impl<A, P> Iterator for Running<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    type Item = (A::Real, Array1<A::Real>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_x, _q, r) = self.series.next()?;
+        Some(self.accumulate(&r))
+    }
+}
+
+/// Calculate finite-time Lyapunov exponents of `count` successive windows of `window` steps
+///
+/// Each row of the result is the exponents of a window.
+/// The first `window * count / 10` steps are discarded as the transient as in `exponents`.
+pub fn finite_time_exponents<A, TEO>(
+    teo: TEO,
+    x: Array1<A>,
+    alpha: A::Real,
+    window: usize,
+    count: usize,
+) -> Array2<A::Real>
+where
+    A: Scalar + Lapack,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+{
+    let mut series = Series::new(teo, x, alpha);
+    let n = series.prop.model_size();
+    for _ in 0..window * count / 10 {
+        series.next();
+    }
+    let mut l = Array::zeros((count, n));
+    for (mut row, ftle) in l.outer_iter_mut().zip(series.finite_time(window)) {
+        row.assign(&ftle);
+    }
+    l
+}
+
+/// Summary statistics of samples of exponents, e.g. FTLEs of many windows
+///
+/// Each row of the samples is a sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExponentStatistics<R> {
+    /// Number of samples
+    pub count: usize,
+    pub mean: Array1<R>,
+    /// Unbiased variance
+    pub variance: Array1<R>,
+    pub min: Array1<R>,
+    pub max: Array1<R>,
+}
+
+impl<R: Float + FromPrimitive> ExponentStatistics<R> {
+    pub fn new<S: Data<Elem = R>>(samples: &ArrayBase<S, Ix2>) -> Self {
+        assert!(samples.nrows() > 1, "Statistics needs two or more samples");
+        let fold = |init: R, f: fn(R, R) -> R| samples.fold_axis(Axis(0), init, |&a, &b| f(a, b));
+        ExponentStatistics {
+            count: samples.nrows(),
+            mean: samples.mean_axis(Axis(0)).unwrap(),
+            variance: samples.var_axis(Axis(0), R::one()),
+            min: fold(R::infinity(), R::min),
+            max: fold(R::neg_infinity(), R::max),
+        }
+    }
+
+    /// Standard deviation
+    pub fn std(&self) -> Array1<R> {
+        self.variance.mapv(R::sqrt)
+    }
+}
+
+/// Histogram with bins of equal width
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram<R> {
+    /// Edges of the bins, one more than the number of bins
+    pub edges: Array1<R>,
+    pub counts: Array1<usize>,
+}
+
+impl<R: Float + FromPrimitive> Histogram<R> {
+    /// Histogram of `data` in `range` divided into `bins` bins
+    ///
+    /// The upper end of the range belongs to the last bin. Values out of the range are ignored.
+    pub fn new<S: Data<Elem = R>>(data: &ArrayBase<S, Ix1>, bins: usize, range: (R, R)) -> Self {
+        let (lo, hi) = range;
+        assert!(bins > 0, "Histogram needs one or more bins");
+        assert!(lo < hi, "Range of the histogram is empty");
+        let nb = R::from_usize(bins).unwrap();
+        let edges = Array::linspace(lo, hi, bins + 1);
+        let mut counts = Array::zeros(bins);
+        for &a in data {
+            if !(a >= lo && a <= hi) {
+                continue;
+            }
+            let i = ((a - lo) / (hi - lo) * nb).to_usize().unwrap_or(0);
+            counts[i.min(bins - 1)] += 1;
+        }
+        Histogram { edges, counts }
+    }
+
+    /// Histogram over the range of the finite values of `data`
+    pub fn auto<S: Data<Elem = R>>(data: &ArrayBase<S, Ix1>, bins: usize) -> Self {
+        let finite = data.iter().cloned().filter(|a| a.is_finite());
+        let lo = finite.clone().fold(R::infinity(), R::min);
+        let hi = finite.fold(R::neg_infinity(), R::max);
+        let half = R::from_f64(0.5).unwrap();
+        let range = if lo < hi {
+            (lo, hi)
+        } else if lo == hi {
+            (lo - half, hi + half)
+        } else {
+            (-half, half)
+        };
+        Self::new(data, bins, range)
+    }
+
+    /// Centers of the bins
+    pub fn centers(&self) -> Array1<R> {
+        let n = self.counts.len();
+        let half = R::from_f64(0.5).unwrap();
+        Zip::from(&self.edges.slice(s![..n]))
+            .and(&self.edges.slice(s![1..]))
+            .apply_collect(|&a, &b| (a + b) * half)
+    }
+
+    /// Probability density normalized to unity over the range
+    pub fn density(&self) -> Array1<R> {
+        let total = R::from_usize(self.counts.sum()).unwrap();
+        let width = self.edges[1] - self.edges[0];
+        self.counts
+            .mapv(|c| R::from_usize(c).unwrap() / (total * width))
+    }
+}
+
+/// Lyapunov spectrum with the error estimated by block averaging
+///
+/// The exponents are the mean of the FTLEs of blocks, i.e. windows of the same length.
+/// The standard error is estimated from the variance among the blocks,
+/// which is valid when the blocks are longer than the correlation time of the FTLEs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum<R> {
+    pub exponents: Array1<R>,
+    /// Standard error of each exponent
+    pub std_error: Array1<R>,
+    /// Number of blocks
+    pub blocks: usize,
+}
+
+impl<R: Float + FromPrimitive> Spectrum<R> {
+    /// Spectrum from the FTLEs of blocks as rows, e.g. the result of `finite_time_exponents`
+    pub fn from_blocks<S: Data<Elem = R>>(blocks: &ArrayBase<S, Ix2>) -> Self {
+        let stat = ExponentStatistics::new(blocks);
+        let n = R::from_usize(stat.count).unwrap();
+        Spectrum {
+            std_error: stat.variance.mapv(|v| (v / n).sqrt()),
+            exponents: stat.mean,
+            blocks: stat.count,
+        }
+    }
+
+    /// Confidence interval `exponents -/+ z * std_error`, e.g. `z = 1.96` for 95%
+    pub fn confidence_interval(&self, z: R) -> (Array1<R>, Array1<R>) {
+        let lower = Zip::from(&self.exponents)
+            .and(&self.std_error)
+            .apply_collect(|&l, &e| l - z * e);
+        let upper = Zip::from(&self.exponents)
+            .and(&self.std_error)
+            .apply_collect(|&l, &e| l + z * e);
+        (lower, upper)
+    }
+
+    /// Kaplan-Yorke dimension of the exponents
+    pub fn kaplan_yorke_dimension(&self) -> R {
+        kaplan_yorke_dimension(&self.exponents)
+    }
+
+    /// Upper bound of the Kolmogorov-Sinai entropy
+    ///
+    /// The neutral exponent detected by `neutral(z)` is excluded even if its estimate is positive.
+    pub fn ks_entropy(&self, z: R) -> R {
+        ks_entropy(&self.exponents, self.neutral(z))
+    }
+
+    /// Index of the neutral exponent of a continuous-time flow
+    ///
+    /// The exponent closest to zero is regarded as neutral
+    /// if it is within `z` times its standard error from zero.
+    /// This is `None` if some exponents are NaN, e.g. for a diverged run.
+    pub fn neutral(&self, z: R) -> Option<usize> {
+        if self.exponents.iter().any(|l| l.is_nan()) {
+            return None;
+        }
+        let (i, _) = self
+            .exponents
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.abs().partial_cmp(&b.abs()).unwrap())?;
+        if self.exponents[i].abs() <= z * self.std_error[i] {
+            Some(i)
+        } else {
+            None
+        }
+    }
+}
+
+/// Estimate the Lyapunov spectrum with its error from `blocks` blocks of `block` steps
+///
+/// The transient is discarded as in `finite_time_exponents`.
+pub fn spectrum<A, TEO>(
+    teo: TEO,
+    x: Array1<A>,
+    alpha: A::Real,
+    block: usize,
+    blocks: usize,
+) -> Spectrum<A::Real>
+where
+    A: Scalar + Lapack,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+{
+    Spectrum::from_blocks(&finite_time_exponents(teo, x, alpha, block, blocks))
+}
+
+/// Kaplan-Yorke (Lyapunov) dimension
+///
+/// `D = k + (l_1 + ... + l_k) / |l_{k+1}|` where `k` is the largest number
+/// such that the sum of the `k` largest exponents is non-negative.
+/// This is NaN if some exponents are NaN.
+pub fn kaplan_yorke_dimension<R, S>(exponents: &ArrayBase<S, Ix1>) -> R
+where
+    R: Float + FromPrimitive,
+    S: Data<Elem = R>,
+{
+    if exponents.iter().any(|l| l.is_nan()) {
+        return R::nan();
+    }
+    let mut l: Vec<R> = exponents.to_vec();
+    l.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    let mut sum = R::zero();
+    for (k, &lk) in l.iter().enumerate() {
+        if sum + lk < R::zero() {
+            return R::from_usize(k).unwrap() + sum / lk.abs();
+        }
+        sum = sum + lk;
+    }
+    R::from_usize(l.len()).unwrap()
+}
+
+/// Sum of the positive exponents, an upper bound of the Kolmogorov-Sinai entropy by Pesin's formula
+///
+/// The exponent of the index `neutral`, e.g. the direction of a flow, is excluded from the sum,
+/// since its estimate may be slightly positive.
+pub fn ks_entropy<R, S>(exponents: &ArrayBase<S, Ix1>, neutral: Option<usize>) -> R
+where
+    R: Float,
+    S: Data<Elem = R>,
+{
+    exponents
+        .iter()
+        .enumerate()
+        .filter(|&(i, l)| *l > R::zero() && Some(i) != neutral)
+        .fold(R::zero(), |acc, (_, &l)| acc + l)
+}
+
 fn clv_backward<A: Scalar + Lapack>(c: &Array2<A>, r: &Array2<A>) -> (Array2<A>, Array1<A::Real>) {
-    let cd = r
-        .solve_triangular(UPLO::Upper, ::ndarray_linalg::Diag::NonUnit, c)
-        .expect("Failed to solve R");
+    try_clv_backward(c, r).expect("Failed to solve R")
+}
+
+fn try_clv_backward<A: Scalar + Lapack>(
+    c: &Array2<A>,
+    r: &Array2<A>,
+) -> Result<(Array2<A>, Array1<A::Real>)> {
+    let cd = r.solve_triangular(UPLO::Upper, ::ndarray_linalg::Diag::NonUnit, c)?;
     let (c, d) = normalize(cd, NormalizeAxis::Column);
     let f = Array::from(d).mapv_into(|x| A::Real::one() / x);
-    (c, f)
+    Ok((c, f))
 }
 
+/// State, CLVs as columns, and the local expansion rates of each CLV
+pub type CLV<A> = (Array1<A>, Array2<A>, Array1<<A as Scalar>::Real>);
+
 /// Calculate the Covariant Lyapunov Vectors at once
 ///
 /// This function saves the time series of QR-decomposition, and consumes many memories.
-pub fn vectors<A, TEO>(
+/// Use `vectors_checkpointed` for long time series.
+pub fn vectors<A, TEO>(teo: TEO, x: Array1<A>, alpha: A::Real, duration: usize) -> Vec<CLV<A>>
+where
+    A: Scalar + Lapack,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1> + Clone,
+{
+    vectors_of(Series::new(teo, x, alpha), duration)
+}
+
+/// Calculate the Covariant Lyapunov Vectors at once, or returns the error
+/// in the forward or backward iteration
+pub fn try_vectors<A, TEO>(
     teo: TEO,
     x: Array1<A>,
     alpha: A::Real,
     duration: usize,
-) -> Vec<(Array1<A>, Array2<A>, Array1<A::Real>)>
+) -> Result<Vec<CLV<A>>>
 where
     A: Scalar + Lapack,
     TEO: TimeEvolution<Scalar = A, Dim = Ix1> + Clone,
 {
-    let n = teo.model_size();
-    let qr_series = Series::new(teo, x, alpha)
+    let mut series = Series::new(teo, x, alpha);
+    let n = series.prop.model_size();
+    for _ in 0..duration / 10 {
+        series.try_next()?;
+    }
+    let qr_series = (0..duration + duration / 10)
+        .map(|_| series.try_next())
+        .collect::<Result<Vec<_>>>()?;
+    let mut c = Array::eye(n);
+    let mut clv_rev = Vec::with_capacity(qr_series.len());
+    for (x, q, r) in qr_series.into_iter().rev() {
+        let (c_now, f) = try_clv_backward(&c, &r)?;
+        clv_rev.push((x, q.dot(&c_now), f));
+        c = c_now;
+    }
+    Ok(clv_rev.into_iter().skip(duration / 10).rev().collect())
+}
+
+/// Calculate the Covariant Lyapunov Vectors at once using the exact tangent propagation
+pub fn vectors_exact<A, TEO>(teo: TEO, x: Array1<A>, duration: usize) -> Vec<CLV<A>>
+where
+    A: Scalar + Lapack,
+    TEO: TangentScheme<Scalar = A>,
+    TEO::Core: Clone,
+{
+    vectors_of(Series::exact(teo, x), duration)
+}
+
+fn vectors_of<A, P>(series: Series<A, P>, duration: usize) -> Vec<CLV<A>>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    let n = series.prop.model_size();
+    let qr_series = series
         .skip(duration / 10)
         .take(duration + duration / 10)
         .collect::<Vec<_>>();
@@ -213,3 +870,370 @@ where
         .collect::<Vec<_>>();
     clv_rev.into_iter().skip(duration / 10).rev().collect()
 }
+
+/// Covariant Lyapunov Vectors streamed in forward-time order with bounded memory
+///
+/// The forward pass writes the upper triangles of `R` into a buffer, e.g. a file,
+/// and keeps the `Series` at the beginning of every `segment` steps as checkpoints.
+/// The backward pass over the buffer keeps the coefficients of CLVs only at the ends of segments.
+/// Each segment is recomputed from its checkpoint when the iterator reaches it,
+/// thus `O(segment + duration / segment)` matrices are held in memory instead of `O(duration)`.
+/// The result is identical to `vectors` since the same operations are repeated.
+pub struct CheckpointedVectors<A, P>
+where
+    A: Scalar + Lapack,
+    P: Propagator<Scalar = A>,
+{
+    /// checkpoint at the beginning and the coefficients at the end of each segment
+    segments: std::vec::IntoIter<(Series<A, P>, Array2<A>)>,
+    current: std::vec::IntoIter<CLV<A>>,
+    segment: usize,
+    start: usize,
+    total: usize,
+    duration: usize,
+}
+
+impl<A, P> CheckpointedVectors<A, P>
+where
+    A: Scalar + Lapack + Element,
+    P: Propagator<Scalar = A> + Clone,
+{
+    /// Run the forward and backward passes over `duration` steps after the transient
+    ///
+    /// The transient steps are the same as `vectors`.
+    /// `buffer` needs `duration * 1.1 * n(n+1)/2` elements for the system of size `n`,
+    /// and is written from its current position.
+    pub fn new<B>(
+        mut series: Series<A, P>,
+        duration: usize,
+        segment: usize,
+        mut buffer: B,
+    ) -> Result<Self>
+    where
+        B: Read + Write + Seek,
+    {
+        assert!(segment > 0, "Segment must have one or more steps");
+        for _ in 0..duration / 10 {
+            series.try_next()?;
+        }
+        let total = duration + duration / 10;
+        let n = series.x.len();
+        let size = n * (n + 1) / 2 * std::mem::size_of::<A>();
+        let base = buffer.stream_position()?;
+
+        let mut checkpoints = Vec::new();
+        let mut bytes = Vec::with_capacity(size);
+        for k in 0..total {
+            if k % segment == 0 {
+                checkpoints.push(series.clone());
+            }
+            let (_x, _q, r) = series.try_next()?;
+            bytes.clear();
+            for i in 0..n {
+                for &a in r.slice(s![i, i..]) {
+                    a.write_le(&mut bytes)?;
+                }
+            }
+            buffer.write_all(&bytes)?;
+        }
+
+        let mut ends = Vec::with_capacity(checkpoints.len());
+        let mut c = Array::eye(n);
+        let mut r = Array::zeros((n, n));
+        for k in (0..total).rev() {
+            if k + 1 == total || (k + 1) % segment == 0 {
+                ends.push(c.clone());
+            }
+            buffer.seek(SeekFrom::Start(base + (k * size) as u64))?;
+            bytes.resize(size, 0);
+            buffer.read_exact(&mut bytes)?;
+            let mut cur = bytes.as_slice();
+            for i in 0..n {
+                for a in r.slice_mut(s![i, i..]) {
+                    *a = A::read_le(&mut cur)?;
+                }
+            }
+            c = try_clv_backward(&c, &r)?.0;
+        }
+        ends.reverse();
+
+        // segments after the duration are only needed for the backward pass
+        let needed = (duration + segment - 1) / segment;
+        let segments: Vec<_> = checkpoints.into_iter().zip(ends).take(needed).collect();
+        Ok(CheckpointedVectors {
+            segments: segments.into_iter(),
+            current: Vec::new().into_iter(),
+            segment,
+            start: 0,
+            total,
+            duration,
+        })
+    }
+
+    /// Recompute the next segment from its checkpoint
+    fn load_segment(&mut self) -> Result<()> {
+        let (mut series, mut c) = self.segments.next().unwrap();
+        let len = self.segment.min(self.total - self.start);
+        let qr_series = (0..len)
+            .map(|_| series.try_next())
+            .collect::<Result<Vec<_>>>()?;
+        let mut clv = Vec::with_capacity(len);
+        for (x, q, r) in qr_series.into_iter().rev() {
+            let (c_now, f) = try_clv_backward(&c, &r)?;
+            clv.push((x, q.dot(&c_now), f));
+            c = c_now;
+        }
+        clv.reverse();
+        clv.truncate(self.duration - self.start);
+        self.start += len;
+        self.current = clv.into_iter();
+        Ok(())
+    }
+}
+
+impl<A, P> Iterator for CheckpointedVectors<A, P>
+where
+    A: Scalar + Lapack + Element,
+    P: Propagator<Scalar = A> + Clone,
+{
+    type Item = Result<CLV<A>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(clv) = self.current.next() {
+                return Some(Ok(clv));
+            }
+            if self.start >= self.duration {
+                return None;
+            }
+            if let Err(e) = self.load_segment() {
+                // stop after reporting the error
+                self.start = self.duration;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Calculate the Covariant Lyapunov Vectors with bounded memory, see `CheckpointedVectors`
+///
+/// ```rust
+/// use eom::*;
+/// use eom::traits::*;
+/// use eom::lyapunov::*;
+/// use ndarray::*;
+/// use std::io::Cursor;
+/// let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+/// // use a file instead of `Cursor` for long runs
+/// let buffer = Cursor::new(Vec::new());
+/// let clv = vectors_checkpointed(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7, 1000, 100, buffer).unwrap();
+/// for v in clv {
+///     let (_x, v, _f) = v.unwrap();
+///     println!("{}", v.column(0).dot(&v.column(1)));
+/// }
+/// ```
+pub fn vectors_checkpointed<A, TEO, B>(
+    teo: TEO,
+    x: Array1<A>,
+    alpha: A::Real,
+    duration: usize,
+    segment: usize,
+    buffer: B,
+) -> Result<CheckpointedVectors<A, FiniteDifference<TEO>>>
+where
+    A: Scalar + Lapack + Element,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1>,
+    B: Read + Write + Seek,
+{
+    CheckpointedVectors::new(Series::new(teo, x, alpha), duration, segment, buffer)
+}
+
+/// Principal angles between the subspaces spanned by the columns of `a` and `b`, in ascending order
+///
+/// The angles are in `[0, pi/2]`, and the number of the angles is the smaller dimension.
+pub fn principal_angles<A, Sa, Sb>(
+    a: &ArrayBase<Sa, Ix2>,
+    b: &ArrayBase<Sb, Ix2>,
+) -> Array1<A::Real>
+where
+    A: Scalar + Lapack,
+    Sa: Data<Elem = A>,
+    Sb: Data<Elem = A>,
+{
+    if a.ncols() == 0 || b.ncols() == 0 {
+        return Array::zeros(0);
+    }
+    let (qa, _) = a.qr().expect("Failed to orthonormalize");
+    let (qb, _) = b.qr().expect("Failed to orthonormalize");
+    let m = qa.t().mapv(|x| x.conj()).dot(&qb);
+    let (_, s, _) = m.svd(false, false).expect("Failed to compute SVD");
+    // singular values are in descending order
+    s.mapv(|c| Float::acos(c.min(A::Real::one())))
+}
+
+/// Angles between each pair of vectors, i.e. the columns of `v`, in `[0, pi/2]`
+pub fn pairwise_angles<A, S>(v: &ArrayBase<S, Ix2>) -> Array2<A::Real>
+where
+    A: Scalar + Lapack,
+    S: Data<Elem = A>,
+{
+    let n = v.ncols();
+    let norms: Vec<_> = v.axis_iter(Axis(1)).map(|c| c.norm_l2()).collect();
+    Array::from_shape_fn((n, n), |(i, j)| {
+        let c = v.column(i).mapv(|x| x.conj()).dot(&v.column(j)).abs() / (norms[i] * norms[j]);
+        Float::acos(c.min(A::Real::one()))
+    })
+}
+
+/// Numbers of the unstable, neutral and stable CLVs, which are in the order of the exponents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Splitting {
+    pub unstable: usize,
+    pub neutral: usize,
+    pub stable: usize,
+}
+
+impl Splitting {
+    /// Classify the exponents by the tolerance `tol` of the neutral ones
+    pub fn from_exponents<R, S>(exponents: &ArrayBase<S, Ix1>, tol: R) -> Self
+    where
+        R: Float,
+        S: Data<Elem = R>,
+    {
+        let unstable = exponents.iter().filter(|&&l| l > tol).count();
+        let stable = exponents.iter().filter(|&&l| l < -tol).count();
+        Splitting {
+            unstable,
+            neutral: exponents.len() - unstable - stable,
+            stable,
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.unstable + self.neutral + self.stable
+    }
+}
+
+/// Minimal angles between the unstable, neutral and stable subspaces spanned by CLVs
+///
+/// The angle is `None` if either of the subspaces is empty.
+/// Small angles between the center-unstable (unstable and neutral) and stable subspaces
+/// indicate tangencies, i.e. the lack of hyperbolicity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubspaceAngles<R> {
+    pub unstable_neutral: Option<R>,
+    pub unstable_stable: Option<R>,
+    pub neutral_stable: Option<R>,
+    pub center_unstable_stable: Option<R>,
+}
+
+impl<R: Float> SubspaceAngles<R> {
+    /// Angles between the subspaces of CLVs `v` (as columns) split by `splitting`
+    pub fn new<A, S>(v: &ArrayBase<S, Ix2>, splitting: Splitting) -> Self
+    where
+        A: Scalar<Real = R> + Lapack,
+        S: Data<Elem = A>,
+    {
+        assert_eq!(v.ncols(), splitting.dim(), "Splitting does not match CLVs");
+        let nu = splitting.unstable;
+        let nc = nu + splitting.neutral;
+        let unstable = v.slice(s![.., ..nu]);
+        let neutral = v.slice(s![.., nu..nc]);
+        let stable = v.slice(s![.., nc..]);
+        let center_unstable = v.slice(s![.., ..nc]);
+        let min = |a, b| principal_angles(&a, &b).first().cloned();
+        SubspaceAngles {
+            unstable_neutral: min(unstable, neutral),
+            unstable_stable: min(unstable, stable),
+            neutral_stable: min(neutral, stable),
+            center_unstable_stable: min(center_unstable, stable),
+        }
+    }
+}
+
+/// Angles between the subspaces at each step of the output of `vectors`
+pub fn subspace_angles<A: Scalar + Lapack>(
+    clv: &[CLV<A>],
+    splitting: Splitting,
+) -> Vec<SubspaceAngles<A::Real>> {
+    clv.iter()
+        .map(|(_x, v, _f)| SubspaceAngles::new(v, splitting))
+        .collect()
+}
+
+/// Histogram of angles in `[0, pi/2]`
+///
+/// ```rust
+/// # use eom::*;
+/// # use eom::traits::*;
+/// # use eom::lyapunov::*;
+/// # use ndarray::*;
+/// let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+/// let clv = vectors(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7, 1000);
+/// let splitting = Splitting { unstable: 1, neutral: 1, stable: 1 };
+/// let angles = subspace_angles(&clv, splitting);
+/// let h = angle_histogram(angles.iter().filter_map(|a| a.center_unstable_stable), 50);
+/// ```
+pub fn angle_histogram<R, I>(angles: I, bins: usize) -> Histogram<R>
+where
+    R: Float + FromPrimitive,
+    I: IntoIterator<Item = R>,
+{
+    let angles: Array1<R> = angles.into_iter().collect();
+    let half_pi = R::from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+    Histogram::new(&angles, bins, (R::zero(), half_pi))
+}
+
+/// Check of the domination of Oseledets splitting (DOS)
+///
+/// The splitting between the `i`-th and `(i+1)`-th CLVs is dominated when
+/// the finite-time growth of the `i`-th CLV exceeds that of the `(i+1)`-th in every window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Domination<R> {
+    /// Number of steps in a window
+    pub window: usize,
+    /// Number of windows
+    pub windows: usize,
+    /// Fraction of the windows violating the order of the `i`-th and `(i+1)`-th CLVs
+    pub violation: Array1<R>,
+}
+
+impl<R: Float + FromPrimitive> Domination<R> {
+    /// Check the order of the local expansion rates of the output of `vectors`
+    /// in successive windows of `window` steps
+    pub fn new<A>(clv: &[CLV<A>], window: usize) -> Self
+    where
+        A: Scalar<Real = R> + Lapack,
+    {
+        assert!(window > 0, "Window must have one or more steps");
+        let n = clv.first().map(|(_x, _v, f)| f.len()).unwrap_or(0);
+        let mut violation = Array1::<R>::zeros(n.saturating_sub(1));
+        let chunks = clv.chunks_exact(window);
+        let windows = chunks.len();
+        for chunk in chunks {
+            let mut growth = Array1::<R>::zeros(n);
+            for (_x, _v, f) in chunk {
+                azip!((g in &mut growth, &f in f) *g = *g + f.ln());
+            }
+            for i in 0..n.saturating_sub(1) {
+                if growth[i] <= growth[i + 1] {
+                    violation[i] = violation[i] + R::one();
+                }
+            }
+        }
+        if windows > 0 {
+            let w = R::from_usize(windows).unwrap();
+            violation.mapv_inplace(|v| v / w);
+        }
+        Domination {
+            window,
+            windows,
+            violation,
+        }
+    }
+
+    /// Whether the splitting between the `i`-th and `(i+1)`-th CLVs is dominated
+    pub fn is_dominated(&self, i: usize) -> bool {
+        self.windows > 0 && self.violation[i] == R::zero()
+    }
+}
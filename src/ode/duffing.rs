@@ -0,0 +1,83 @@
+//! Periodically forced Duffing oscillator
+//! https://en.wikipedia.org/wiki/Duffing_equation
+
+use ndarray::*;
+
+use crate::traits::*;
+
+/// `x'' + delta x' + alpha x + beta x^3 = gamma cos(omega t)` as a first order system of `(x, x')`
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Duffing {
+    pub alpha: f64,
+    pub beta: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    pub omega: f64,
+}
+
+impl Default for Duffing {
+    fn default() -> Self {
+        Duffing {
+            alpha: -1.0,
+            beta: 1.0,
+            delta: 0.3,
+            gamma: 0.5,
+            omega: 1.2,
+        }
+    }
+}
+
+impl Duffing {
+    pub fn new(alpha: f64, beta: f64, delta: f64, gamma: f64, omega: f64) -> Self {
+        Duffing {
+            alpha,
+            beta,
+            delta,
+            gamma,
+            omega,
+        }
+    }
+}
+
+impl ModelSpec for Duffing {
+    type Scalar = f64;
+    type Dim = Ix1;
+
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl ExplicitT for Duffing {
+    fn rhs_t<'a, S>(&mut self, t: f64, v: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let x = v[0];
+        let y = v[1];
+        v[0] = y;
+        v[1] = -self.delta * y - self.alpha * x - self.beta * x * x * x
+            + self.gamma * (self.omega * t).cos();
+        v
+    }
+}
+
+impl ExplicitJacobian for Duffing {
+    fn rhs_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = f64>,
+        St: DataMut<Elem = f64>,
+    {
+        let x = v[0];
+        let (dx, dy) = (dv[0], dv[1]);
+        dv[0] = dy;
+        dv[1] = -self.delta * dy - (self.alpha + 3.0 * self.beta * x * x) * dx;
+        dv
+    }
+}
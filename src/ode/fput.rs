@@ -0,0 +1,109 @@
+//! Fermi-Pasta-Ulam-Tsingou chain
+//! https://en.wikipedia.org/wiki/Fermi%E2%80%93Pasta%E2%80%93Ulam%E2%80%93Tsingou_problem
+
+use ndarray::*;
+
+use crate::traits::*;
+
+/// Chain of `n` particles with fixed ends, whose state is `(q_1, .., q_n, p_1, .., p_n)`
+///
+/// `H = sum_i p_i^2/2 + sum_{i=0}^{n} V(q_{i+1} - q_i)` with `q_0 = q_{n+1} = 0`
+/// and `V(r) = r^2/2 + alpha r^3/3 + beta r^4/4`.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FPUT {
+    pub n: usize,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for FPUT {
+    fn default() -> Self {
+        FPUT {
+            n: 32,
+            alpha: 0.25,
+            beta: 0.0,
+        }
+    }
+}
+
+impl FPUT {
+    pub fn new(n: usize, alpha: f64, beta: f64) -> Self {
+        FPUT { n, alpha, beta }
+    }
+
+    fn potential(&self, r: f64) -> f64 {
+        r * r * (0.5 + r * (self.alpha / 3.0 + r * self.beta / 4.0))
+    }
+
+    fn spring(&self, r: f64) -> f64 {
+        r * (1.0 + r * (self.alpha + r * self.beta))
+    }
+
+    /// displacement at the site `i` in `0..=n+1` including the fixed ends
+    fn site<S: Data<Elem = f64>>(&self, q: &ArrayBase<S, Ix1>, i: usize) -> f64 {
+        if i == 0 || i > self.n {
+            0.0
+        } else {
+            q[i - 1]
+        }
+    }
+}
+
+impl ModelSpec for FPUT {
+    type Scalar = f64;
+    type Dim = Ix1;
+
+    fn model_size(&self) -> usize {
+        2 * self.n
+    }
+}
+
+impl Hamiltonian for FPUT {
+    fn velocity<'a, S>(&mut self, p: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        p
+    }
+
+    fn force<'a, S>(&mut self, q: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let mut left = self.spring(q[0]);
+        for i in 0..self.n {
+            let right = self.spring(self.site(q, i + 2) - q[i]);
+            q[i] = right - left;
+            left = right;
+        }
+        q
+    }
+
+    fn energy<S>(&self, x: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = f64>,
+    {
+        let q = x.slice(s![..self.n]);
+        let p = x.slice(s![self.n..]);
+        let kinetic = 0.5 * p.dot(&p);
+        let potential: f64 = (0..=self.n)
+            .map(|i| self.potential(self.site(&q, i + 1) - self.site(&q, i)))
+            .sum();
+        kinetic + potential
+    }
+}
+
+impl Explicit for FPUT {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let (mut q, mut p) = x.view_mut().split_at(Axis(0), self.n);
+        self.force(&mut q);
+        for (q, p) in q.iter_mut().zip(p.iter_mut()) {
+            std::mem::swap(q, p);
+        }
+        x
+    }
+}
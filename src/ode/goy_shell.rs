@@ -7,13 +7,14 @@ use num_traits::{PrimInt, Zero};
 use crate::traits::*;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GoyShell {
-    size: usize,
-    nu: f64,
-    e: f64,
-    k0: f64,
-    f: f64,
-    f_idx: usize,
+    pub size: usize,
+    pub nu: f64,
+    pub e: f64,
+    pub k0: f64,
+    pub f: f64,
+    pub f_idx: usize,
 }
 
 impl GoyShell {
@@ -82,3 +83,52 @@ impl SemiImplicit for GoyShell {
             .collect()
     }
 }
+
+impl SemiImplicitJacobian for GoyShell {
+    fn nlin_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = c64>,
+        St: DataMut<Elem = c64>,
+    {
+        let n = self.size as isize;
+        let conj = |a: ArrayView1<c64>, i: isize| -> c64 {
+            if i < 0 || i >= n {
+                c64::zero()
+            } else {
+                a[i as usize].conj()
+            }
+        };
+        let v = v.view();
+        let dv0 = dv.to_owned();
+        let dv0 = dv0.view();
+
+        let a = 1.0;
+        let b = -self.e;
+        let c = -(1.0 - self.e);
+
+        for i in 0..n {
+            let (vm2, vm1, vp1, vp2) = (
+                conj(v, i - 2),
+                conj(v, i - 1),
+                conj(v, i + 1),
+                conj(v, i + 2),
+            );
+            let (dm2, dm1, dp1, dp2) = (
+                conj(dv0, i - 2),
+                conj(dv0, i - 1),
+                conj(dv0, i + 1),
+                conj(dv0, i + 2),
+            );
+            dv[i as usize] = self.k(i as usize)
+                * (a * (dp1 * vp2 + vp1 * dp2)
+                    + 0.5 * b * (dp1 * vm1 + vp1 * dm1)
+                    + 0.25 * c * (dm1 * vm2 + vm1 * dm2));
+        }
+        dv
+    }
+}
@@ -0,0 +1,78 @@
+//! Henon-Heiles system
+//! https://en.wikipedia.org/wiki/H%C3%A9non%E2%80%93Heiles_system
+
+use ndarray::*;
+
+use crate::traits::*;
+
+/// `H = (px^2 + py^2)/2 + (x^2 + y^2)/2 + lambda (x^2 y - y^3/3)` with the state `(x, y, px, py)`
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HenonHeiles {
+    pub lambda: f64,
+}
+
+impl Default for HenonHeiles {
+    fn default() -> Self {
+        HenonHeiles { lambda: 1.0 }
+    }
+}
+
+impl HenonHeiles {
+    pub fn new(lambda: f64) -> Self {
+        HenonHeiles { lambda }
+    }
+}
+
+impl ModelSpec for HenonHeiles {
+    type Scalar = f64;
+    type Dim = Ix1;
+
+    fn model_size(&self) -> usize {
+        4
+    }
+}
+
+impl Hamiltonian for HenonHeiles {
+    fn velocity<'a, S>(&mut self, p: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        p
+    }
+
+    fn force<'a, S>(&mut self, q: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let x = q[0];
+        let y = q[1];
+        q[0] = -x - 2.0 * self.lambda * x * y;
+        q[1] = -y - self.lambda * (x * x - y * y);
+        q
+    }
+
+    fn energy<S>(&self, v: &ArrayBase<S, Ix1>) -> f64
+    where
+        S: Data<Elem = f64>,
+    {
+        let (x, y, px, py) = (v[0], v[1], v[2], v[3]);
+        0.5 * (px * px + py * py)
+            + 0.5 * (x * x + y * y)
+            + self.lambda * (x * x * y - y * y * y / 3.0)
+    }
+}
+
+impl Explicit for HenonHeiles {
+    fn rhs<'a, S>(&mut self, v: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let (x, y, px, py) = (v[0], v[1], v[2], v[3]);
+        v[0] = px;
+        v[1] = py;
+        v[2] = -x - 2.0 * self.lambda * x * y;
+        v[3] = -y - self.lambda * (x * x - y * y);
+        v
+    }
+}
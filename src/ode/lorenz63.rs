@@ -6,6 +6,7 @@ use ndarray::*;
 use crate::traits::*;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lorenz63 {
     pub p: f64,
     pub r: f64,
@@ -70,3 +71,43 @@ impl SemiImplicit for Lorenz63 {
         Array::from(vec![-self.p, -1.0, -self.b])
     }
 }
+
+impl ExplicitJacobian for Lorenz63 {
+    fn rhs_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = f64>,
+        St: DataMut<Elem = f64>,
+    {
+        let (x, y, z) = (v[0], v[1], v[2]);
+        let (dx, dy, dz) = (dv[0], dv[1], dv[2]);
+        dv[0] = self.p * (dy - dx);
+        dv[1] = (self.r - z) * dx - dy - x * dz;
+        dv[2] = y * dx + x * dy - self.b * dz;
+        dv
+    }
+}
+
+impl SemiImplicitJacobian for Lorenz63 {
+    fn nlin_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = f64>,
+        St: DataMut<Elem = f64>,
+    {
+        let (x, y, z) = (v[0], v[1], v[2]);
+        let (dx, dy, dz) = (dv[0], dv[1], dv[2]);
+        dv[0] = self.p * dy;
+        dv[1] = (self.r - z) * dx - x * dz;
+        dv[2] = y * dx + x * dy;
+        dv
+    }
+}
@@ -5,6 +5,7 @@ use crate::traits::*;
 use ndarray::*;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lorenz96 {
     pub f: f64,
     pub n: usize,
@@ -41,3 +42,26 @@ impl Explicit for Lorenz96 {
         v
     }
 }
+
+impl ExplicitJacobian for Lorenz96 {
+    fn rhs_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = f64>,
+        St: DataMut<Elem = f64>,
+    {
+        let n = v.len();
+        let dv0 = dv.to_owned();
+        for i in 0..n {
+            let p1 = (i + 1) % n;
+            let m1 = (i + n - 1) % n;
+            let m2 = (i + n - 2) % n;
+            dv[i] = (dv0[p1] - dv0[m2]) * v[m1] + (v[p1] - v[m2]) * dv0[m1] - dv0[i];
+        }
+        dv
+    }
+}
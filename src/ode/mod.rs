@@ -1,11 +1,21 @@
 //! Example nonlinear ODEs
 
+pub mod duffing;
+pub mod fput;
 pub mod goy_shell;
+pub mod henon_heiles;
 pub mod lorenz63;
 pub mod lorenz96;
+pub mod robertson;
 pub mod roessler;
+pub mod van_der_pol;
 
+pub use self::duffing::Duffing;
+pub use self::fput::FPUT;
 pub use self::goy_shell::GoyShell;
+pub use self::henon_heiles::HenonHeiles;
 pub use self::lorenz63::Lorenz63;
 pub use self::lorenz96::Lorenz96;
+pub use self::robertson::Robertson;
 pub use self::roessler::Roessler;
+pub use self::van_der_pol::VanDerPol;
@@ -0,0 +1,75 @@
+//! Robertson's stiff chemical kinetics, a standard test problem for stiff solvers
+
+use ndarray::*;
+
+use crate::traits::*;
+
+/// Reactions `A -> B` (rate `k1`), `2B -> B + C` (rate `k2`) and `B + C -> A + C` (rate `k3`)
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Robertson {
+    pub k1: f64,
+    pub k2: f64,
+    pub k3: f64,
+}
+
+impl Default for Robertson {
+    fn default() -> Self {
+        Robertson {
+            k1: 0.04,
+            k2: 3e7,
+            k3: 1e4,
+        }
+    }
+}
+
+impl Robertson {
+    pub fn new(k1: f64, k2: f64, k3: f64) -> Self {
+        Robertson { k1, k2, k3 }
+    }
+}
+
+impl ModelSpec for Robertson {
+    type Scalar = f64;
+    type Dim = Ix1;
+
+    fn model_size(&self) -> usize {
+        3
+    }
+}
+
+impl Explicit for Robertson {
+    fn rhs<'a, S>(&mut self, v: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let a = v[0];
+        let b = v[1];
+        let c = v[2];
+        v[0] = -self.k1 * a + self.k3 * b * c;
+        v[1] = self.k1 * a - self.k3 * b * c - self.k2 * b * b;
+        v[2] = self.k2 * b * b;
+        v
+    }
+}
+
+impl ExplicitJacobian for Robertson {
+    fn rhs_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = f64>,
+        St: DataMut<Elem = f64>,
+    {
+        let (b, c) = (v[1], v[2]);
+        let (da, db, dc) = (dv[0], dv[1], dv[2]);
+        let dbc = self.k3 * (db * c + b * dc);
+        dv[0] = -self.k1 * da + dbc;
+        dv[1] = self.k1 * da - dbc - 2.0 * self.k2 * b * db;
+        dv[2] = 2.0 * self.k2 * b * db;
+        dv
+    }
+}
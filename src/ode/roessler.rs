@@ -5,6 +5,7 @@ use crate::traits::*;
 use ndarray::*;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Roessler {
     pub a: f64,
     pub b: f64,
@@ -49,3 +50,23 @@ impl Explicit for Roessler {
         v
     }
 }
+
+impl ExplicitJacobian for Roessler {
+    fn rhs_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = f64>,
+        St: DataMut<Elem = f64>,
+    {
+        let (x, z) = (v[0], v[2]);
+        let (dx, dy, dz) = (dv[0], dv[1], dv[2]);
+        dv[0] = -dy - dz;
+        dv[1] = dx + self.a * dy;
+        dv[2] = z * dx + (x - self.c) * dz;
+        dv
+    }
+}
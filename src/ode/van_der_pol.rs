@@ -0,0 +1,72 @@
+//! Periodically forced Van der Pol oscillator
+//! https://en.wikipedia.org/wiki/Van_der_Pol_oscillator
+
+use ndarray::*;
+
+use crate::traits::*;
+
+/// `x'' - mu (1 - x^2) x' + x = a sin(omega t)` as a first order system of `(x, x')`
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VanDerPol {
+    pub mu: f64,
+    pub a: f64,
+    pub omega: f64,
+}
+
+impl Default for VanDerPol {
+    fn default() -> Self {
+        VanDerPol {
+            mu: 8.53,
+            a: 1.2,
+            omega: 2.0 * std::f64::consts::PI / 10.0,
+        }
+    }
+}
+
+impl VanDerPol {
+    pub fn new(mu: f64, a: f64, omega: f64) -> Self {
+        VanDerPol { mu, a, omega }
+    }
+}
+
+impl ModelSpec for VanDerPol {
+    type Scalar = f64;
+    type Dim = Ix1;
+
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl ExplicitT for VanDerPol {
+    fn rhs_t<'a, S>(&mut self, t: f64, v: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let x = v[0];
+        let y = v[1];
+        v[0] = y;
+        v[1] = self.mu * (1.0 - x * x) * y - x + self.a * (self.omega * t).sin();
+        v
+    }
+}
+
+impl ExplicitJacobian for VanDerPol {
+    fn rhs_tangent<'a, S, St>(
+        &mut self,
+        _t: f64,
+        v: &ArrayBase<S, Ix1>,
+        dv: &'a mut ArrayBase<St, Ix1>,
+    ) -> &'a mut ArrayBase<St, Ix1>
+    where
+        S: Data<Elem = f64>,
+        St: DataMut<Elem = f64>,
+    {
+        let (x, y) = (v[0], v[1]);
+        let (dx, dy) = (dv[0], dv[1]);
+        dv[0] = dy;
+        dv[1] = self.mu * (1.0 - x * x) * dy - (2.0 * self.mu * x * y + 1.0) * dx;
+        dv
+    }
+}
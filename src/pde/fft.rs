@@ -0,0 +1,120 @@
+//! Radix-2 FFT for pseudo-spectral methods
+
+use ndarray::*;
+use num_complex::Complex64 as c64;
+use num_traits::Zero;
+use std::f64::consts::PI;
+
+/// Complex FFT of power-of-two length by iterative Cooley-Tukey algorithm
+#[derive(Debug, Clone)]
+struct Radix2 {
+    n: usize,
+    twiddle: Vec<c64>,
+    rev: Vec<usize>,
+}
+
+impl Radix2 {
+    fn new(n: usize) -> Self {
+        assert!(n.is_power_of_two(), "FFT size must be a power of two");
+        let bits = n.trailing_zeros();
+        let rev = (0..n)
+            .map(|i| {
+                if bits == 0 {
+                    0
+                } else {
+                    i.reverse_bits() >> (usize::BITS - bits)
+                }
+            })
+            .collect();
+        let twiddle = (0..n / 2)
+            .map(|i| c64::from_polar(1.0, -2.0 * PI * i as f64 / n as f64))
+            .collect();
+        Radix2 { n, twiddle, rev }
+    }
+
+    /// In-place transform. `inverse` flips the sign of the exponent without normalization.
+    fn transform(&self, a: &mut [c64], inverse: bool) {
+        let n = self.n;
+        for i in 0..n {
+            let j = self.rev[i];
+            if i < j {
+                a.swap(i, j);
+            }
+        }
+        let mut len = 2;
+        while len <= n {
+            let step = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..len / 2 {
+                    let w = if inverse {
+                        self.twiddle[k * step].conj()
+                    } else {
+                        self.twiddle[k * step]
+                    };
+                    let u = a[start + k];
+                    let v = a[start + k + len / 2] * w;
+                    a[start + k] = u + v;
+                    a[start + k + len / 2] = u - v;
+                }
+            }
+            len <<= 1;
+        }
+    }
+}
+
+/// Pair of a real field and its Fourier coefficients
+///
+/// The real field `r` has `n` grid points, and `c` keeps `n/2 + 1` coefficients
+/// of non-negative wave numbers. The forward transform is normalized by `1/n`,
+/// i.e. `r[m] = sum_j c[j] exp(2 pi i j m / n)` with the Hermite symmetry.
+#[derive(Debug, Clone)]
+pub struct Pair {
+    pub r: Array1<f64>,
+    pub c: Array1<c64>,
+    fft: Radix2,
+    buf: Vec<c64>,
+}
+
+impl Pair {
+    /// `n` must be a power of two
+    pub fn new(n: usize) -> Self {
+        Pair {
+            r: Array::zeros(n),
+            c: Array::zeros(n / 2 + 1),
+            fft: Radix2::new(n),
+            buf: vec![c64::zero(); n],
+        }
+    }
+
+    /// Number of grid points in real space
+    pub fn size(&self) -> usize {
+        self.r.len()
+    }
+
+    /// Real field to Fourier coefficients
+    pub fn r2c(&mut self) {
+        let n = self.size();
+        for (b, r) in self.buf.iter_mut().zip(self.r.iter()) {
+            *b = c64::new(*r, 0.0);
+        }
+        self.fft.transform(&mut self.buf, false);
+        for (c, b) in self.c.iter_mut().zip(self.buf.iter()) {
+            *c = b / n as f64;
+        }
+    }
+
+    /// Fourier coefficients to real field
+    pub fn c2r(&mut self) {
+        let n = self.size();
+        for (j, c) in self.c.iter().enumerate() {
+            self.buf[j] = *c;
+            if j > 0 && j < n - j {
+                self.buf[n - j] = c.conj();
+            }
+        }
+        self.fft.transform(&mut self.buf, true);
+        for (r, b) in self.r.iter_mut().zip(self.buf.iter()) {
+            *r = b.re;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Kuramoto-Sivashinsky equation
+//! https://en.wikipedia.org/wiki/Kuramoto%E2%80%93Sivashinsky_equation
+
+use ndarray::*;
+use num_complex::Complex64 as c64;
+use std::f64::consts::PI;
+
+use super::Pair;
+use crate::traits::*;
+
+/// One-dimensional KSE `u_t + u u_x + u_xx + u_xxxx = 0` on a periodic domain
+///
+/// The state is the Fourier coefficients of `u` (see [Pair]),
+/// and the nonlinear term is evaluated pseudo-spectrally with the 2/3-rule dealiasing.
+///
+/// [Pair]: ../fft/struct.Pair.html
+#[derive(Clone, Debug)]
+pub struct KSE {
+    n: usize,
+    length: f64,
+    ik: Array1<c64>,
+    u: Pair,
+}
+
+impl KSE {
+    /// `n` grid points (a power of two) on the domain of size `length`
+    pub fn new(n: usize, length: f64) -> Self {
+        let ik = (0..n / 2 + 1)
+            .map(|j| c64::new(0.0, 2.0 * PI * j as f64 / length))
+            .collect();
+        KSE {
+            n,
+            length,
+            ik,
+            u: Pair::new(n),
+        }
+    }
+
+    /// Number of grid points in real space
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Size of the periodic domain
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+impl ModelSpec for KSE {
+    type Scalar = c64;
+    type Dim = Ix1;
+
+    fn model_size(&self) -> usize {
+        self.n / 2 + 1
+    }
+}
+
+impl SemiImplicit for KSE {
+    fn nlin<'a, S>(&mut self, uc: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = c64>,
+    {
+        self.u.c.assign(uc);
+        self.u.c2r();
+        self.u.r.mapv_inplace(|u| 0.5 * u * u);
+        self.u.r2c();
+        let cutoff = self.n / 3;
+        for (j, (uc, (&ik, &u2))) in uc
+            .iter_mut()
+            .zip(self.ik.iter().zip(self.u.c.iter()))
+            .enumerate()
+        {
+            *uc = if j <= cutoff {
+                -ik * u2
+            } else {
+                c64::new(0.0, 0.0)
+            };
+        }
+        uc
+    }
+
+    fn diag(&self) -> Array<c64, Ix1> {
+        self.ik.mapv(|ik| -ik * ik - ik * ik * ik * ik)
+    }
+}
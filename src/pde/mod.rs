@@ -0,0 +1,9 @@
+//! Example nonlinear PDEs
+
+pub mod fft;
+pub mod kse;
+pub mod she;
+
+pub use self::fft::Pair;
+pub use self::kse::KSE;
+pub use self::she::SHE;
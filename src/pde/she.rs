@@ -0,0 +1,83 @@
+//! Swift-Hohenberg equation
+//! https://en.wikipedia.org/wiki/Swift%E2%80%93Hohenberg_equation
+
+use ndarray::*;
+use num_complex::Complex64 as c64;
+use std::f64::consts::PI;
+
+use super::Pair;
+use crate::traits::*;
+
+/// One-dimensional SHE `u_t = r u - (1 + d_xx)^2 u - u^3` on a periodic domain
+///
+/// The state is the Fourier coefficients of `u` (see [Pair]),
+/// and the cubic term is evaluated pseudo-spectrally with the 1/2-rule dealiasing.
+///
+/// [Pair]: ../fft/struct.Pair.html
+#[derive(Clone, Debug)]
+pub struct SHE {
+    n: usize,
+    length: f64,
+    r: f64,
+    k: Array1<f64>,
+    u: Pair,
+}
+
+impl SHE {
+    /// `n` grid points (a power of two) on the domain of size `length` with the control parameter `r`
+    pub fn new(n: usize, length: f64, r: f64) -> Self {
+        let k = (0..n / 2 + 1)
+            .map(|j| 2.0 * PI * j as f64 / length)
+            .collect();
+        SHE {
+            n,
+            length,
+            r,
+            k,
+            u: Pair::new(n),
+        }
+    }
+
+    /// Number of grid points in real space
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Size of the periodic domain
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+impl ModelSpec for SHE {
+    type Scalar = c64;
+    type Dim = Ix1;
+
+    fn model_size(&self) -> usize {
+        self.n / 2 + 1
+    }
+}
+
+impl SemiImplicit for SHE {
+    fn nlin<'a, S>(&mut self, uc: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = c64>,
+    {
+        self.u.c.assign(uc);
+        self.u.c2r();
+        self.u.r.mapv_inplace(|u| -u * u * u);
+        self.u.r2c();
+        let cutoff = self.n / 4;
+        for (j, (uc, &u3)) in uc.iter_mut().zip(self.u.c.iter()).enumerate() {
+            *uc = if j <= cutoff { u3 } else { c64::new(0.0, 0.0) };
+        }
+        uc
+    }
+
+    fn diag(&self) -> Array<c64, Ix1> {
+        self.k.mapv(|k| {
+            let l = 1.0 - k * k;
+            c64::new(self.r - l * l, 0.0)
+        })
+    }
+}
@@ -0,0 +1,517 @@
+//! Unstable periodic orbits by the Newton shooting method
+//!
+//! Any `TimeEvolution` is used as the flow map `phi_T`,
+//! and the initial state `x` and the period `T` are solved from
+//!
+//! ```text
+//! phi_T(x) - x = 0
+//! <f(x), dx> = 0
+//! ```
+//!
+//! where the second equation is the phase condition fixing the position along the orbit
+//! for each Newton update `dx`, and the vector field `f` is approximated by a single step of the scheme.
+//! The Jacobian matrix of the flow map is applied by finite differences,
+//! and the Newton equations are solved directly or by GMRES (Newton-Krylov method).
+//! Each Newton update is halved while it increases the residual,
+//! and the iteration fails if ten halvings do not decrease it.
+//!
+//! The flow map is evaluated by a clone of the scheme starting from `t = 0`,
+//! and thus fixed-step schemes give the smoothest map.
+//! Complex models, e.g. the Fourier coefficients of `pde::KSE`,
+//! are regarded as real systems of twice the size.
+//!
+//! Initial guesses are taken from near-recurrences of a long time-series by [recurrences].
+//! Equilibria satisfy `phi_T(x) = x` for any `T`, and the iteration may converge to them from poor guesses.
+//! Periodic orbits of autonomous systems are distinguished by the Floquet multiplier 1 along the flow.
+//!
+//! [recurrences]: fn.recurrences.html
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::periodic_orbit::*;
+//! use eom::traits::*;
+//! use ndarray::*;
+//! let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+//! let guesses = recurrences(arr1(&[1.0, 0.0, 0.0]), &mut teo, 2000, (50, 200), 3);
+//! let solver = Solver::new(teo);
+//! for guess in guesses {
+//!     if let Ok(orbit) = solver.solve(guess.x, guess.period) {
+//!         println!("T = {}, multipliers = {}", orbit.period, orbit.multipliers);
+//!     }
+//! }
+//! ```
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::{Float, One, Zero};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::adaptor;
+use crate::error::Result;
+use crate::implicit::NewtonError;
+use crate::traits::*;
+
+/// Solver of the linear Newton equations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linear {
+    /// LU decomposition of the Jacobian matrix assembled column by column,
+    /// which costs an integration over the period for each dimension of the state
+    Direct,
+    /// GMRES restarted after `dim` Krylov vectors at most `restarts` times
+    ///
+    /// Floquet multipliers are the Ritz values of the Arnoldi iteration with `dim` vectors,
+    /// which approximate the multipliers of the largest modulus.
+    Gmres { dim: usize, restarts: usize },
+}
+
+/// Periodic orbit `phi_T(x) = x`
+#[derive(Debug, Clone)]
+pub struct PeriodicOrbit<A: Scalar> {
+    /// state on the orbit
+    pub x: Array1<A>,
+    pub period: A::Real,
+    /// eigenvalues of the monodromy matrix in the descending order of modulus
+    pub multipliers: Array1<<A::Real as Scalar>::Complex>,
+    /// `|phi_T(x) - x|`
+    pub residual: A::Real,
+    /// number of Newton iterations
+    pub iterations: usize,
+}
+
+impl<A: Scalar> PeriodicOrbit<A> {
+    /// Floquet exponents `ln|mu| / T` of the multipliers `mu`
+    pub fn exponents(&self) -> Array1<A::Real> {
+        let period = self.period;
+        self.multipliers.mapv(|mu| Float::ln(mu.abs()) / period)
+    }
+}
+
+/// A pair of close states in a time-series, which is an initial guess of a periodic orbit
+#[derive(Debug, Clone)]
+pub struct Recurrence<A: Scalar> {
+    pub x: Array1<A>,
+    /// time until the return
+    pub period: A::Real,
+    /// distance of the returned state from `x`
+    pub distance: A::Real,
+}
+
+/// Find near-recurrences in the time-series of `steps` steps from `x0`
+///
+/// For each state, the closest state between `gap.0` and `gap.1` steps later is searched.
+/// At most `count` recurrences are returned in the ascending order of the distance,
+/// and their starting states are separated by `gap.0` steps at least.
+/// `x0` should be already on the attractor.
+pub fn recurrences<A, TEO>(
+    x0: Array1<A>,
+    teo: &mut TEO,
+    steps: usize,
+    gap: (usize, usize),
+    count: usize,
+) -> Vec<Recurrence<A>>
+where
+    A: Scalar + Lapack,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1, Time = A::Real>,
+{
+    let (min, max) = gap;
+    assert!(min > 0 && min <= max, "Invalid gap of recurrences");
+    let series: Vec<_> = adaptor::time_series(x0, teo).timed().take(steps).collect();
+    let mut candidates = Vec::new();
+    for i in 0..series.len() {
+        let (_, ref xi) = series[i];
+        let closest = (i + min..(i + max + 1).min(series.len()))
+            .map(|j| (j, (&series[j].1 - xi).norm_l2()))
+            .fold(None, |best: Option<(usize, A::Real)>, (j, d)| match best {
+                Some((_, db)) if db <= d => best,
+                _ => Some((j, d)),
+            });
+        if let Some((j, d)) = closest {
+            candidates.push((d, i, j));
+        }
+    }
+    candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut found: Vec<(usize, Recurrence<A>)> = Vec::new();
+    for (distance, i, j) in candidates {
+        if found.len() >= count {
+            break;
+        }
+        if found
+            .iter()
+            .all(|(k, _)| (*k as isize - i as isize).abs() >= min as isize)
+        {
+            let rec = Recurrence {
+                x: series[i].1.clone(),
+                period: series[j].0 - series[i].0,
+                distance,
+            };
+            found.push((i, rec));
+        }
+    }
+    found.into_iter().map(|(_, rec)| rec).collect()
+}
+
+/// Newton solver of periodic orbits of the flow map given by `TEO`
+#[derive(Debug, Clone)]
+pub struct Solver<TEO: TimeEvolution> {
+    teo: TEO,
+    linear: Linear,
+    tol: TEO::Time,
+    max_iter: usize,
+}
+
+impl<A, R, TEO> Solver<TEO>
+where
+    A: Scalar<Real = R> + Lapack,
+    R: Scalar<Real = R> + Float + Lapack,
+    TEO: TimeEvolution<Scalar = A, Dim = Ix1, Time = R>,
+{
+    pub fn new(teo: TEO) -> Self {
+        Solver {
+            teo,
+            linear: Linear::Direct,
+            tol: R::real(1e-8),
+            max_iter: 50,
+        }
+    }
+
+    /// The default is `Linear::Direct`
+    pub fn set_linear(&mut self, linear: Linear) {
+        self.linear = linear;
+    }
+
+    pub fn get_linear(&self) -> Linear {
+        self.linear
+    }
+
+    /// The iteration converges when `|phi_T(x) - x| <= tol (1 + |x|)`.
+    /// The default is `(1e-8, 50)`.
+    pub fn set_newton(&mut self, tol: R, max_iter: usize) {
+        self.tol = tol;
+        self.max_iter = max_iter;
+    }
+
+    /// Tolerance and maximum number of iterations
+    pub fn get_newton(&self) -> (R, usize) {
+        (self.tol, self.max_iter)
+    }
+
+    /// Flow map `phi_t(x)`
+    pub fn flow(&self, x: &Array1<A>, t: R) -> Result<Array1<A>> {
+        let mut teo = self.teo.clone();
+        teo.set_time(R::zero());
+        let mut y = x.clone();
+        adaptor::integrate_to(&mut teo, &mut y, t);
+        check_finite(&y, t)?;
+        Ok(y)
+    }
+
+    /// States on the orbit at `points` equally spaced times in `[0, T)`
+    pub fn trajectory(&self, orbit: &PeriodicOrbit<A>, points: usize) -> Result<Array2<A>> {
+        let mut teo = self.teo.clone();
+        teo.set_time(R::zero());
+        let mut x = orbit.x.clone();
+        let mut traj = Array2::zeros((points, x.len()));
+        for (k, mut row) in traj.axis_iter_mut(Axis(0)).enumerate() {
+            let t = orbit.period * R::real(k) / R::real(points);
+            adaptor::integrate_to(&mut teo, &mut x, t);
+            check_finite(&x, t)?;
+            row.assign(&x);
+        }
+        Ok(traj)
+    }
+
+    /// Vector field approximated by a single step
+    fn velocity(&self, x: &Array1<A>) -> Result<Array1<A>> {
+        let h = self.teo.get_dt();
+        let y = self.flow(x, h)?;
+        Ok((y - x).mapv(|a| a.div_real(h)))
+    }
+
+    /// Solve the periodic orbit from the initial guess `(x0, period)`
+    pub fn solve(&self, x0: Array1<A>, period: R) -> Result<PeriodicOrbit<A>> {
+        let unit = imaginary_unit::<A>();
+        let mut x = x0;
+        let mut t = period;
+        let mut y = self.flow(&x, t)?;
+        let mut res = (&y - &x).norm_l2();
+        for iter in 0..=self.max_iter {
+            if res <= self.tol * (R::one() + x.norm_l2()) {
+                let multipliers = self.multipliers(&x, t, &y)?;
+                return Ok(PeriodicOrbit {
+                    x,
+                    period: t,
+                    multipliers,
+                    residual: res,
+                    iterations: iter,
+                });
+            }
+            if iter == self.max_iter {
+                break;
+            }
+            let d = self.update(&x, t, &y, res)?;
+            let dx = join(&d.slice(s![..-1]), unit);
+            let dt = d[d.len() - 1];
+            // damping by halving the update while the residual increases
+            let mut step = R::one();
+            let mut next = None;
+            for _ in 0..10 {
+                let t_new = t + dt * step;
+                if t_new > R::zero() {
+                    let x_new = &x + &dx.mapv(|a| a.mul_real(step));
+                    if let Ok(y_new) = self.flow(&x_new, t_new) {
+                        let res_new = (&y_new - &x_new).norm_l2();
+                        if res_new < res {
+                            next = Some((x_new, t_new, y_new, res_new));
+                            break;
+                        }
+                    }
+                }
+                step /= R::real(2.0);
+            }
+            // the update does not decrease the residual in any step size
+            let (x_new, t_new, y_new, res_new) = next.ok_or(NewtonError::NotConverged {
+                iterations: iter + 1,
+            })?;
+            x = x_new;
+            t = t_new;
+            y = y_new;
+            res = res_new;
+        }
+        Err(NewtonError::NotConverged {
+            iterations: self.max_iter,
+        }
+        .into())
+    }
+
+    /// Newton update `(dx, dT)` in the real representation
+    fn update(&self, x: &Array1<A>, t: R, y: &Array1<A>, res: R) -> Result<Array1<R>> {
+        let unit = imaginary_unit::<A>();
+        let f0 = split(&self.velocity(x)?);
+        let f1 = split(&self.velocity(y)?);
+        let yr = split(y);
+        let m = yr.len();
+        let mut rhs = Array1::zeros(m + 1);
+        rhs.slice_mut(s![..m]).assign(&(split(x) - &yr));
+        let alpha = Float::sqrt(R::epsilon()) * (R::one() + x.norm_l2());
+        let mut jacobian = |v: &Array1<R>| -> Result<Array1<R>> {
+            let vx = v.slice(s![..m]);
+            let vt = v[m];
+            let mut jv = Array1::zeros(m + 1);
+            let nrm = vx.norm_l2();
+            if nrm > R::zero() {
+                let eps = alpha / nrm;
+                let x_eps = x + &join(&vx, unit).mapv(|a| a.mul_real(eps));
+                let y_eps = split(&self.flow(&x_eps, t)?);
+                jv.slice_mut(s![..m])
+                    .assign(&((y_eps - &yr).mapv(|a| a / eps) - vx));
+            }
+            jv.slice_mut(s![..m]).scaled_add(vt, &f1);
+            jv[m] = f0.dot(&vx);
+            Ok(jv)
+        };
+        match self.linear {
+            Linear::Direct => {
+                let mut a = Array2::zeros((m + 1, m + 1));
+                for k in 0..=m {
+                    let mut e = Array1::zeros(m + 1);
+                    e[k] = R::one();
+                    a.column_mut(k).assign(&jacobian(&e)?);
+                }
+                Ok(a.solve_into(rhs)?)
+            }
+            Linear::Gmres { dim, restarts } => {
+                // inexact Newton: the linear residual is reduced as the Newton residual
+                let tol = rhs.norm_l2() * res.min(R::real(1e-2));
+                gmres(&mut jacobian, &rhs, dim, restarts, tol)
+            }
+        }
+    }
+
+    /// Floquet multipliers in the descending order of modulus
+    fn multipliers(
+        &self,
+        x: &Array1<A>,
+        t: R,
+        y: &Array1<A>,
+    ) -> Result<Array1<<R as Scalar>::Complex>> {
+        let unit = imaginary_unit::<A>();
+        let yr = split(y);
+        let m = yr.len();
+        let alpha = Float::sqrt(R::epsilon()) * (R::one() + x.norm_l2());
+        let mut monodromy = |v: &Array1<R>| -> Result<Array1<R>> {
+            let eps = alpha / v.norm_l2();
+            let x_eps = x + &join(&v.view(), unit).mapv(|a| a.mul_real(eps));
+            Ok((split(&self.flow(&x_eps, t)?) - &yr).mapv(|a| a / eps))
+        };
+        let h = match self.linear {
+            Linear::Direct => {
+                let mut a = Array2::zeros((m, m));
+                for k in 0..m {
+                    let mut e = Array1::zeros(m);
+                    e[k] = R::one();
+                    a.column_mut(k).assign(&monodromy(&e)?);
+                }
+                a
+            }
+            Linear::Gmres { dim, .. } => {
+                let mut rng = StdRng::seed_from_u64(0);
+                let v0 = Array1::from_shape_fn(m, |_| R::rand(&mut rng) - R::real(0.5));
+                let (_, h) = arnoldi(&mut monodromy, &v0, dim.min(m), R::zero())?;
+                let k = h.ncols();
+                h.slice(s![..k, ..]).to_owned()
+            }
+        };
+        let mut mu = h.eigvals()?.to_vec();
+        mu.sort_by(|a, b| {
+            b.abs()
+                .partial_cmp(&a.abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Ok(Array1::from(mu))
+    }
+}
+
+/// `sqrt(-1)` if `A` is complex
+fn imaginary_unit<A: Scalar>() -> Option<A> {
+    let i = A::from_real(-A::Real::one()).sqrt();
+    if i.im().is_zero() {
+        None
+    } else {
+        Some(i)
+    }
+}
+
+/// Real representation of the state: real parts followed by imaginary parts for complex states
+fn split<A: Scalar>(x: &Array1<A>) -> Array1<A::Real> {
+    match imaginary_unit::<A>() {
+        None => x.mapv(|a| a.re()),
+        Some(_) => {
+            let n = x.len();
+            Array1::from_shape_fn(2 * n, |k| if k < n { x[k].re() } else { x[k - n].im() })
+        }
+    }
+}
+
+/// Inverse of `split`
+fn join<A: Scalar, S: Data<Elem = A::Real>>(v: &ArrayBase<S, Ix1>, unit: Option<A>) -> Array1<A> {
+    match unit {
+        None => v.mapv(A::from_real),
+        Some(i) => {
+            let n = v.len() / 2;
+            Array1::from_shape_fn(n, |k| A::from_real(v[k]) + i.mul_real(v[k + n]))
+        }
+    }
+}
+
+/// Orthogonalize `w` against the orthonormal `basis` by the modified Gram-Schmidt method,
+/// and return the coefficients followed by the norm of the remainder
+fn orthogonalize<R: Scalar<Real = R> + Float + Lapack>(
+    basis: &[Array1<R>],
+    w: &mut Array1<R>,
+) -> Vec<R> {
+    let mut h: Vec<R> = basis
+        .iter()
+        .map(|v| {
+            let c = v.dot(w);
+            w.scaled_add(-c, v);
+            c
+        })
+        .collect();
+    h.push(w.norm_l2());
+    h
+}
+
+/// Arnoldi iteration of `k` steps at most, which stops when the remainder is smaller than `tol`
+///
+/// Returns the orthonormal basis and the `(j + 1) x j` Hessenberg matrix of `j <= k` steps.
+fn arnoldi<R, Op>(
+    op: &mut Op,
+    v0: &Array1<R>,
+    k: usize,
+    tol: R,
+) -> Result<(Vec<Array1<R>>, Array2<R>)>
+where
+    R: Scalar<Real = R> + Float + Lapack,
+    Op: FnMut(&Array1<R>) -> Result<Array1<R>>,
+{
+    let nrm = v0.norm_l2();
+    let mut basis = vec![v0.mapv(|a| a / nrm)];
+    let mut h = Array2::zeros((k + 1, k));
+    for j in 0..k {
+        let mut w = op(&basis[j])?;
+        let col = orthogonalize(&basis, &mut w);
+        for (i, c) in col.iter().enumerate() {
+            h[(i, j)] = *c;
+        }
+        if col[j + 1] <= tol {
+            return Ok((basis, h.slice(s![..j + 2, ..j + 1]).to_owned()));
+        }
+        let nrm = col[j + 1];
+        basis.push(w.mapv(|a| a / nrm));
+    }
+    Ok((basis, h))
+}
+
+/// Restarted GMRES solving `op(x) = b` until `|b - op(x)| <= tol`
+fn gmres<R, Op>(
+    op: &mut Op,
+    b: &Array1<R>,
+    dim: usize,
+    restarts: usize,
+    tol: R,
+) -> Result<Array1<R>>
+where
+    R: Scalar<Real = R> + Float + Lapack,
+    Op: FnMut(&Array1<R>) -> Result<Array1<R>>,
+{
+    let mut x = Array1::zeros(b.len());
+    for cycle in 0..=restarts {
+        let r = if cycle == 0 { b.clone() } else { b - &op(&x)? };
+        let beta = r.norm_l2();
+        if beta <= tol {
+            break;
+        }
+        let mut basis = vec![r.mapv(|a| a / beta)];
+        // Hessenberg columns reduced to the upper triangular by the Givens rotations
+        let mut cols: Vec<Vec<R>> = Vec::new();
+        let mut rotations: Vec<(R, R)> = Vec::new();
+        let mut g = vec![beta];
+        for j in 0..dim {
+            let mut w = op(&basis[j])?;
+            let mut col = orthogonalize(&basis, &mut w);
+            let nrm = col[j + 1];
+            for (i, &(c, s)) in rotations.iter().enumerate() {
+                let t = c * col[i] + s * col[i + 1];
+                col[i + 1] = c * col[i + 1] - s * col[i];
+                col[i] = t;
+            }
+            let d = Float::hypot(col[j], col[j + 1]);
+            if d <= R::zero() {
+                // breakdown: the Hessenberg matrix is singular, and the current solution is kept
+                break;
+            }
+            let (c, s) = (col[j] / d, col[j + 1] / d);
+            col[j] = d;
+            col[j + 1] = R::zero();
+            g.push(-s * g[j]);
+            g[j] = c * g[j];
+            rotations.push((c, s));
+            cols.push(col);
+            if Float::abs(g[j + 1]) <= tol || nrm <= R::zero() {
+                break;
+            }
+            basis.push(w.mapv(|a| a / nrm));
+        }
+        // back substitution of the triangular system
+        let k = cols.len();
+        let mut y = vec![R::zero(); k];
+        for i in (0..k).rev() {
+            let s = ((i + 1)..k).fold(g[i], |s, l| s - cols[l][i] * y[l]);
+            y[i] = s / cols[i][i];
+        }
+        for (yi, v) in y.iter().zip(basis.iter()) {
+            x.scaled_add(*yi, v);
+        }
+    }
+    Ok(x)
+}
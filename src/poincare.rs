@@ -0,0 +1,369 @@
+//! Poincaré sections and first-return maps
+//!
+//! These adaptors work with any `TimeEvolution`, since they use only the states after each step.
+//! Crossings and maxima are located on the cubic polynomial interpolating the last four states,
+//! which is accurate to the fourth order in the step size.
+//! For explicit schemes, [event::detect] locates crossings on the Hermite interpolant instead.
+//!
+//! [event::detect]: ../event/fn.detect.html
+//!
+//! ```rust
+//! use eom::*;
+//! use eom::traits::*;
+//! use eom::poincare::*;
+//! use ndarray::*;
+//! let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+//! // Lorenz map: successive maxima of z
+//! let z_max = maxima(arr1(&[1.0, 0.0, 0.0]), &mut teo, |x| x[2]).map(|(_t, z)| z);
+//! for (z0, z1) in return_map(z_max).take(100) {
+//!     println!("{} {}", z0, z1);
+//! }
+//! ```
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::Float;
+use std::collections::VecDeque;
+
+use crate::event::{illinois, Direction};
+use crate::traits::*;
+
+/// Number of states used in the interpolation
+pub(crate) const POINTS: usize = 4;
+
+/// Hyperplane `Re<n, x> = c` with the direction of crossings
+#[derive(Debug, Clone)]
+pub struct Hyperplane<A: Scalar> {
+    normal: Array1<A>,
+    offset: A::Real,
+    direction: Direction,
+}
+
+impl<A: Scalar> Hyperplane<A> {
+    /// Hyperplane of the normal vector `normal` and the offset `offset`, crossed in both directions
+    pub fn new(normal: Array1<A>, offset: A::Real) -> Self {
+        Hyperplane {
+            normal,
+            offset,
+            direction: Direction::Both,
+        }
+    }
+
+    /// Hyperplane `x[axis] = value` of states of size `size`
+    pub fn axis(size: usize, axis: usize, value: A::Real) -> Self {
+        let mut normal = Array::zeros(size);
+        normal[axis] = A::one();
+        Self::new(normal, value)
+    }
+
+    /// Detect crossings only in the given direction,
+    /// `Rising` is the direction of the normal vector
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// `Re<n, x> - c`, which is positive on the side the normal vector points to
+    pub fn value<S: Data<Elem = A>>(&self, x: &ArrayBase<S, Ix1>) -> A::Real {
+        let nx = Zip::from(&self.normal)
+            .and(x)
+            .fold(A::zero(), |acc, &n, &x| acc + n.conj() * x);
+        nx.re() - self.offset
+    }
+}
+
+/// Intersection of the trajectory and the hyperplane
+#[derive(Debug, Clone)]
+pub struct Crossing<A: Scalar> {
+    /// time of the crossing
+    pub t: A::Real,
+    /// interpolated state on the hyperplane
+    pub x: Array1<A>,
+}
+
+/// Lagrange basis polynomials of the nodes `ts` at `t`
+pub(crate) fn weights<T: Float>(ts: &[T], t: T) -> Vec<T> {
+    (0..ts.len())
+        .map(|j| {
+            ts.iter()
+                .enumerate()
+                .filter(|&(i, _)| i != j)
+                .fold(T::one(), |w, (_, &ti)| w * (t - ti) / (ts[j] - ti))
+        })
+        .collect()
+}
+
+/// Time derivatives of the Lagrange basis polynomials of the nodes `ts` at `t`
+fn derivative_weights<T: Float>(ts: &[T], t: T) -> Vec<T> {
+    (0..ts.len())
+        .map(|j| {
+            (0..ts.len())
+                .filter(|&m| m != j)
+                .map(|m| {
+                    ts.iter()
+                        .enumerate()
+                        .filter(|&(i, _)| i != j && i != m)
+                        .fold(T::one() / (ts[j] - ts[m]), |w, (_, &ti)| {
+                            w * (t - ti) / (ts[j] - ti)
+                        })
+                })
+                .fold(T::zero(), |acc, w| acc + w)
+        })
+        .collect()
+}
+
+/// Interpolate the states `(t_j, x_j)` at `t`
+fn interpolate<A: Scalar>(points: &VecDeque<(A::Real, Array1<A>)>, t: A::Real) -> Array1<A> {
+    let ts: Vec<_> = points.iter().map(|(t, _)| *t).collect();
+    let mut x = Array::zeros(points[0].1.len());
+    for (w, (_, xj)) in weights(&ts, t).into_iter().zip(points) {
+        Zip::from(&mut x)
+            .and(xj)
+            .apply(|x, &xj| *x += xj.mul_real(w));
+    }
+    x
+}
+
+/// Maximum of the polynomial interpolating `(t_j, h_j)` around the sampled maximum at `tm`
+fn refine_maximum<T: Float>(points: &VecDeque<(T, T)>, ta: T, tm: T, tb: T, tol: T) -> (T, T) {
+    let (ts, hs): (Vec<_>, Vec<_>) = points.iter().cloned().unzip();
+    let eval = |w: Vec<T>| {
+        w.into_iter()
+            .zip(&hs)
+            .fold(T::zero(), |acc, (w, &h)| acc + w * h)
+    };
+    let dh = |t| eval(derivative_weights(&ts, t));
+    let (da, db) = (dh(ta), dh(tb));
+    let t = if da > T::zero() && db < T::zero() {
+        illinois(dh, ta, da, tb, db, tol)
+    } else {
+        tm
+    };
+    (t, eval(weights(&ts, t)))
+}
+
+/// An iterator of the crossings of a hyperplane generated by [section]
+///
+/// [section]: fn.section.html
+pub struct Section<'a, TEO>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    teo: &'a mut TEO,
+    plane: Hyperplane<TEO::Scalar>,
+    x: Array1<TEO::Scalar>,
+    g: TEO::Time,
+    points: VecDeque<(TEO::Time, Array1<TEO::Scalar>)>,
+    tol: TEO::Time,
+    t_end: Option<TEO::Time>,
+}
+
+/// Integrate EoM from `x0` and yield the crossings of `plane`
+///
+/// The iterator does not end unless the time range is limited by [Section::until].
+///
+/// [Section::until]: struct.Section.html#method.until
+pub fn section<TEO>(
+    x0: Array1<TEO::Scalar>,
+    teo: &mut TEO,
+    plane: Hyperplane<TEO::Scalar>,
+) -> Section<'_, TEO>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    let g = plane.value(&x0);
+    let mut points = VecDeque::with_capacity(POINTS);
+    points.push_back((teo.time(), x0.clone()));
+    Section {
+        teo,
+        plane,
+        x: x0,
+        g,
+        points,
+        tol: TEO::Scalar::real(1e-12),
+        t_end: None,
+    }
+}
+
+impl<'a, TEO> Section<'a, TEO>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    /// Stop the integration when the time reaches `t_end`
+    pub fn until(mut self, t_end: TEO::Time) -> Self {
+        self.t_end = Some(t_end);
+        self
+    }
+
+    /// Absolute tolerance of the crossing time (default `1e-12`)
+    pub fn tolerance(mut self, tol: TEO::Time) -> Self {
+        self.tol = tol;
+        self
+    }
+}
+
+impl<'a, TEO> Iterator for Section<'a, TEO>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    type Item = Crossing<TEO::Scalar>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let t0 = self.teo.time();
+            if self.t_end.map_or(false, |t_end| t0 >= t_end) {
+                return None;
+            }
+            self.teo.iterate(&mut self.x);
+            let t1 = self.teo.time();
+            if self.points.len() == POINTS {
+                self.points.pop_front();
+            }
+            self.points.push_back((t1, self.x.clone()));
+            let g0 = self.g;
+            let g1 = self.plane.value(&self.x);
+            self.g = g1;
+            if !self.plane.direction.matches(g0, g1) {
+                continue;
+            }
+            let (plane, points) = (&self.plane, &self.points);
+            let t = illinois(
+                |t| plane.value(&interpolate(points, t)),
+                t0,
+                g0,
+                t1,
+                g1,
+                self.tol,
+            );
+            if self.t_end.map_or(false, |t_end| t > t_end) {
+                return None;
+            }
+            let x = interpolate(&self.points, t);
+            return Some(Crossing { t, x });
+        }
+    }
+}
+
+/// An iterator of the local maxima of an observable generated by [maxima]
+///
+/// [maxima]: fn.maxima.html
+pub struct Maxima<'a, TEO, H>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+{
+    teo: &'a mut TEO,
+    observable: H,
+    x: Array1<TEO::Scalar>,
+    points: VecDeque<(TEO::Time, TEO::Time)>,
+    tol: TEO::Time,
+    t_end: Option<TEO::Time>,
+}
+
+/// Integrate EoM from `x0` and yield the time and value of each local maximum of `observable`
+///
+/// Use the negated observable for minima.
+/// The iterator does not end unless the time range is limited by [Maxima::until].
+///
+/// [Maxima::until]: struct.Maxima.html#method.until
+pub fn maxima<TEO, H>(
+    x0: Array1<TEO::Scalar>,
+    teo: &mut TEO,
+    mut observable: H,
+) -> Maxima<'_, TEO, H>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+    H: FnMut(ArrayView1<TEO::Scalar>) -> TEO::Time,
+{
+    let mut points = VecDeque::with_capacity(POINTS);
+    points.push_back((teo.time(), observable(x0.view())));
+    Maxima {
+        teo,
+        observable,
+        x: x0,
+        points,
+        tol: TEO::Scalar::real(1e-12),
+        t_end: None,
+    }
+}
+
+impl<'a, TEO, H> Maxima<'a, TEO, H>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+    H: FnMut(ArrayView1<TEO::Scalar>) -> TEO::Time,
+{
+    /// Stop the integration when the time reaches `t_end`
+    pub fn until(mut self, t_end: TEO::Time) -> Self {
+        self.t_end = Some(t_end);
+        self
+    }
+
+    /// Absolute tolerance of the time of maxima (default `1e-12`)
+    pub fn tolerance(mut self, tol: TEO::Time) -> Self {
+        self.tol = tol;
+        self
+    }
+}
+
+impl<'a, TEO, H> Iterator for Maxima<'a, TEO, H>
+where
+    TEO: TimeEvolution<Dim = Ix1>,
+    TEO::Scalar: Scalar<Real = TEO::Time>,
+    H: FnMut(ArrayView1<TEO::Scalar>) -> TEO::Time,
+{
+    type Item = (TEO::Time, TEO::Time);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let t0 = self.teo.time();
+            if self.t_end.map_or(false, |t_end| t0 >= t_end) {
+                return None;
+            }
+            self.teo.iterate(&mut self.x);
+            if self.points.len() == POINTS {
+                self.points.pop_front();
+            }
+            let h = (self.observable)(self.x.view());
+            self.points.push_back((self.teo.time(), h));
+            let n = self.points.len();
+            if n < 3 {
+                continue;
+            }
+            let (ta, ha) = self.points[n - 3];
+            let (tm, hm) = self.points[n - 2];
+            let (tb, hb) = self.points[n - 1];
+            if !(hm > ha && hm >= hb) {
+                continue;
+            }
+            let (t, h) = refine_maximum(&self.points, ta, tm, tb, self.tol);
+            if self.t_end.map_or(false, |t_end| t > t_end) {
+                return None;
+            }
+            return Some((t, h));
+        }
+    }
+}
+
+/// Pairs of successive values `(s_n, s_{n+1})` of a sequence, i.e. the first-return map
+///
+/// e.g. the Lorenz map of the successive maxima of z,
+/// or a coordinate of successive crossings of a Poincaré section.
+pub fn return_map<T, I>(values: I) -> impl Iterator<Item = (T, T)>
+where
+    T: Clone,
+    I: IntoIterator<Item = T>,
+{
+    let mut values = values.into_iter();
+    let mut prev = values.next();
+    std::iter::from_fn(move || {
+        let next = values.next()?;
+        let p = prev.replace(next.clone())?;
+        Some((p, next))
+    })
+}
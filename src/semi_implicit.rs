@@ -2,24 +2,36 @@
 
 use ndarray::*;
 use ndarray_linalg::*;
+use num_traits::{One, Zero};
 
+use super::tangent::Variational;
 use super::traits::*;
 
 /// Linear ODE with diagonalized matrix (exactly solvable)
 #[derive(Debug, Clone)]
-pub struct Diagonal<F: SemiImplicit> {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Diagonal<F: SemiImplicitT> {
     exp_diag: Array<F::Scalar, F::Dim>,
     diag: Array<F::Scalar, F::Dim>,
     dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
 }
 
-impl<F: SemiImplicit> TimeStep for Diagonal<F> {
+impl<F: SemiImplicitT> TimeStep for Diagonal<F> {
     type Time = <F::Scalar as Scalar>::Real;
 
     fn get_dt(&self) -> Self::Time {
         self.dt
     }
     fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
         Zip::from(&mut self.exp_diag)
             .and(&self.diag)
             .apply(|a, &b| {
@@ -28,7 +40,7 @@ impl<F: SemiImplicit> TimeStep for Diagonal<F> {
     }
 }
 
-impl<F: SemiImplicit> ModelSpec for Diagonal<F> {
+impl<F: SemiImplicitT> ModelSpec for Diagonal<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
 
@@ -37,7 +49,15 @@ impl<F: SemiImplicit> ModelSpec for Diagonal<F> {
     }
 }
 
-impl<F: SemiImplicit> TimeEvolution for Diagonal<F> {
+impl<F: SemiImplicitT> TimeEvolution for Diagonal<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
     fn iterate<'a, S>(
         &mut self,
         x: &'a mut ArrayBase<S, Self::Dim>,
@@ -48,13 +68,14 @@ impl<F: SemiImplicit> TimeEvolution for Diagonal<F> {
         for (val, d) in x.iter_mut().zip(self.exp_diag.iter()) {
             *val = *val * *d;
         }
+        self.t += self.dt;
         x
     }
 }
 
-impl<F: SemiImplicit> Diagonal<F> {
+impl<F: SemiImplicitT> Diagonal<F> {
     fn new(f: F, dt: <Self as TimeStep>::Time) -> Self {
-        let diag = f.diag();
+        let diag = f.diag_t();
         let mut exp_diag = diag.to_owned();
         for v in exp_diag.iter_mut() {
             *v = v.mul_real(dt).exp();
@@ -63,15 +84,25 @@ impl<F: SemiImplicit> Diagonal<F> {
             exp_diag: exp_diag,
             diag: diag,
             dt: dt,
+            t: Zero::zero(),
         }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct DiagRK4<F: SemiImplicit> {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct DiagRK4<F: SemiImplicitT> {
     nlin: F,
     lin: Diagonal<F>,
     dt: <Diagonal<F> as TimeStep>::Time,
+    t: <Diagonal<F> as TimeStep>::Time,
     x: Array<F::Scalar, F::Dim>,
     lx: Array<F::Scalar, F::Dim>,
     k1: Array<F::Scalar, F::Dim>,
@@ -79,7 +110,7 @@ pub struct DiagRK4<F: SemiImplicit> {
     k3: Array<F::Scalar, F::Dim>,
 }
 
-impl<F: SemiImplicit> Scheme for DiagRK4<F> {
+impl<F: SemiImplicitT> Scheme for DiagRK4<F> {
     type Core = F;
     fn new(nlin: F, dt: Self::Time) -> Self {
         let lin = Diagonal::new(nlin.clone(), dt / F::Scalar::real(2.0));
@@ -92,6 +123,7 @@ impl<F: SemiImplicit> Scheme for DiagRK4<F> {
             nlin,
             lin,
             dt,
+            t: Zero::zero(),
             x,
             lx,
             k1,
@@ -107,7 +139,7 @@ impl<F: SemiImplicit> Scheme for DiagRK4<F> {
     }
 }
 
-impl<F: SemiImplicit> TimeStep for DiagRK4<F> {
+impl<F: SemiImplicitT> TimeStep for DiagRK4<F> {
     type Time = <Diagonal<F> as TimeStep>::Time;
 
     fn get_dt(&self) -> Self::Time {
@@ -115,11 +147,12 @@ impl<F: SemiImplicit> TimeStep for DiagRK4<F> {
     }
 
     fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
         self.lin.set_dt(dt / F::Scalar::real(2.0));
     }
 }
 
-impl<F: SemiImplicit> ModelSpec for DiagRK4<F> {
+impl<F: SemiImplicitT> ModelSpec for DiagRK4<F> {
     type Scalar = F::Scalar;
     type Dim = F::Dim;
 
@@ -128,7 +161,15 @@ impl<F: SemiImplicit> ModelSpec for DiagRK4<F> {
     }
 }
 
-impl<F: SemiImplicit> TimeEvolution for DiagRK4<F> {
+impl<F: SemiImplicitT> TimeEvolution for DiagRK4<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
     fn iterate<'a, S>(
         &mut self,
         x: &'a mut ArrayBase<S, Self::Dim>,
@@ -148,22 +189,23 @@ impl<F: SemiImplicit> TimeEvolution for DiagRK4<F> {
         self.x.zip_mut_with(x, |buf, x| *buf = *x);
         self.lx.zip_mut_with(x, |buf, lx| *buf = *lx);
         l.iterate(&mut self.lx);
-        let k1 = f.nlin(x);
+        let t = self.t;
+        let k1 = f.nlin_t(t, x);
         self.k1.zip_mut_with(k1, |buf, k1| *buf = *k1);
         Zip::from(&mut *k1).and(&self.x).apply(|k1, &x_| {
             *k1 = x_ + k1.mul_real(dt_2);
         });
-        let k2 = f.nlin(l.iterate(k1));
+        let k2 = f.nlin_t(t + dt_2, l.iterate(k1));
         self.k2.zip_mut_with(k2, |buf, k| *buf = *k);
         Zip::from(&mut *k2).and(&self.lx).apply(|k2, &lx| {
             *k2 = lx + k2.mul_real(dt_2);
         });
-        let k3 = f.nlin(k2);
+        let k3 = f.nlin_t(t + dt_2, k2);
         self.k3.zip_mut_with(k3, |buf, k| *buf = *k);
         Zip::from(&mut *k3).and(&self.lx).apply(|k3, &lx| {
             *k3 = lx + k3.mul_real(dt);
         });
-        let k4 = f.nlin(l.iterate(k3));
+        let k4 = f.nlin_t(t + dt, l.iterate(k3));
         Zip::from(&mut self.x)
             .and(&self.k1)
             .apply(|x_, k1_| *x_ = *x_ + k1_.mul_real(dt_6));
@@ -176,6 +218,518 @@ impl<F: SemiImplicit> TimeEvolution for DiagRK4<F> {
         Zip::from(&mut *k4).and(&self.x).apply(|k4, &x_| {
             *k4 = x_ + k4.mul_real(dt_6);
         });
+        self.t += dt;
         k4
     }
 }
+
+/// Mean of `f` over a circle of radius one around `z` for evaluating the phi-functions stably,
+/// see Kassam and Trefethen, SIAM J. Sci. Comput. 26 (2005) 1214.
+///
+/// The imaginary part of the result is dropped for real `z`,
+/// since the phi-functions are real on the real axis.
+fn contour_mean<A: Scalar>(z: A, f: impl Fn(A::Complex) -> A::Complex) -> A {
+    const M: usize = 32;
+    let zc = z.as_c();
+    let mut sum = A::Complex::zero();
+    for m in 0..M {
+        let theta = 2.0 * ::std::f64::consts::PI * (m as f64 + 0.5) / M as f64;
+        sum += f(zc + A::complex(theta.cos(), theta.sin()));
+    }
+    let mean = sum.div_real(A::real(M as f64));
+    let re = A::from_real(mean.re());
+    if z.im().is_zero() {
+        re
+    } else {
+        // `(z - Re z) / Im z` is the imaginary unit in `A`
+        re + (z - A::from_real(z.re())).mul_real(mean.im() / z.im())
+    }
+}
+
+/// `(exp(z) - 1) / z`
+fn phi1<A: Scalar>(z: A) -> A {
+    contour_mean(z, |z| (z.exp() - A::Complex::one()) / z)
+}
+
+/// `(exp(z) - 1 - z) / z^2`
+fn phi2<A: Scalar>(z: A) -> A {
+    contour_mean(z, |z| (z.exp() - A::Complex::one() - z) / (z * z))
+}
+
+/// Exponential time differencing scheme of first order (exponential Euler)
+///
+/// This evaluates the stiff linear part exactly, and the nonlinear part is assumed to be
+/// constant during a time-step.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct ETD1<F: SemiImplicitT> {
+    nlin: F,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    diag: Array<F::Scalar, F::Dim>,
+    e: Array<F::Scalar, F::Dim>,
+    f1: Array<F::Scalar, F::Dim>,
+    x: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: SemiImplicitT> ETD1<F> {
+    fn coefficients(&mut self) {
+        let dt = self.dt;
+        Zip::from(&mut self.e)
+            .and(&mut self.f1)
+            .and(&self.diag)
+            .apply(|e, f1, &d| {
+                let z = d.mul_real(dt);
+                *e = z.exp();
+                *f1 = phi1(z).mul_real(dt);
+            });
+    }
+}
+
+impl<F: SemiImplicitT> Scheme for ETD1<F> {
+    type Core = F;
+    fn new(nlin: F, dt: Self::Time) -> Self {
+        let diag = nlin.diag_t();
+        let e = Array::zeros(nlin.model_size());
+        let f1 = Array::zeros(nlin.model_size());
+        let x = Array::zeros(nlin.model_size());
+        let mut etd = ETD1 {
+            nlin,
+            dt,
+            t: Zero::zero(),
+            diag,
+            e,
+            f1,
+            x,
+        };
+        etd.coefficients();
+        etd
+    }
+    fn core(&self) -> &Self::Core {
+        &self.nlin
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.nlin
+    }
+}
+
+impl<F: SemiImplicitT> TimeStep for ETD1<F> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+        self.coefficients();
+    }
+}
+
+impl<F: SemiImplicitT> ModelSpec for ETD1<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.nlin.model_size()
+    }
+}
+
+impl<F: SemiImplicitT> TimeEvolution for ETD1<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.x.zip_mut_with(x, |buf, x| *buf = *x);
+        let nx = self.nlin.nlin_t(self.t, x);
+        Zip::from(&mut *nx)
+            .and(&self.x)
+            .and(&self.e)
+            .and(&self.f1)
+            .apply(|nx, &x, &e, &f1| {
+                *nx = e * x + f1 * *nx;
+            });
+        self.t += self.dt;
+        nx
+    }
+}
+
+/// Exponential time differencing scheme of second order (ETDRK2) by Cox and Matthews
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct ETDRK2<F: SemiImplicitT> {
+    nlin: F,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    diag: Array<F::Scalar, F::Dim>,
+    e: Array<F::Scalar, F::Dim>,
+    f1: Array<F::Scalar, F::Dim>,
+    f2: Array<F::Scalar, F::Dim>,
+    x: Array<F::Scalar, F::Dim>,
+    nx: Array<F::Scalar, F::Dim>,
+    a: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: SemiImplicitT> ETDRK2<F> {
+    fn coefficients(&mut self) {
+        let dt = self.dt;
+        Zip::from(&mut self.e)
+            .and(&mut self.f1)
+            .and(&mut self.f2)
+            .and(&self.diag)
+            .apply(|e, f1, f2, &d| {
+                let z = d.mul_real(dt);
+                *e = z.exp();
+                *f1 = phi1(z).mul_real(dt);
+                *f2 = phi2(z).mul_real(dt);
+            });
+    }
+}
+
+impl<F: SemiImplicitT> Scheme for ETDRK2<F> {
+    type Core = F;
+    fn new(nlin: F, dt: Self::Time) -> Self {
+        let diag = nlin.diag_t();
+        let e = Array::zeros(nlin.model_size());
+        let f1 = Array::zeros(nlin.model_size());
+        let f2 = Array::zeros(nlin.model_size());
+        let x = Array::zeros(nlin.model_size());
+        let nx = Array::zeros(nlin.model_size());
+        let a = Array::zeros(nlin.model_size());
+        let mut etd = ETDRK2 {
+            nlin,
+            dt,
+            t: Zero::zero(),
+            diag,
+            e,
+            f1,
+            f2,
+            x,
+            nx,
+            a,
+        };
+        etd.coefficients();
+        etd
+    }
+    fn core(&self) -> &Self::Core {
+        &self.nlin
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.nlin
+    }
+}
+
+impl<F: SemiImplicitT> TimeStep for ETDRK2<F> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+        self.coefficients();
+    }
+}
+
+impl<F: SemiImplicitT> ModelSpec for ETDRK2<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.nlin.model_size()
+    }
+}
+
+impl<F: SemiImplicitT> TimeEvolution for ETDRK2<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.x.zip_mut_with(x, |buf, x| *buf = *x);
+        let nx = self.nlin.nlin_t(self.t, x);
+        self.nx.zip_mut_with(nx, |buf, nx| *buf = *nx);
+        // a = exp(hL) x + h phi1(hL) N(x)
+        Zip::from(&mut self.a)
+            .and(&self.x)
+            .and(&self.nx)
+            .and(&self.e)
+            .and(&self.f1)
+            .apply(|a, &x, &nx, &e, &f1| {
+                *a = e * x + f1 * nx;
+            });
+        nx.zip_mut_with(&self.a, |buf, a| *buf = *a);
+        let na = self.nlin.nlin_t(self.t + self.dt, nx);
+        Zip::from(&mut *na)
+            .and(&self.a)
+            .and(&self.nx)
+            .and(&self.f2)
+            .apply(|na, &a, &nx, &f2| {
+                *na = a + f2 * (*na - nx);
+            });
+        self.t += self.dt;
+        na
+    }
+}
+
+/// Exponential time differencing scheme of fourth order (ETDRK4) by Cox and Matthews
+///
+/// The coefficients are evaluated by the contour integral proposed by Kassam and Trefethen.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct ETDRK4<F: SemiImplicitT> {
+    nlin: F,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    diag: Array<F::Scalar, F::Dim>,
+    e: Array<F::Scalar, F::Dim>,
+    e2: Array<F::Scalar, F::Dim>,
+    q: Array<F::Scalar, F::Dim>,
+    f1: Array<F::Scalar, F::Dim>,
+    f2: Array<F::Scalar, F::Dim>,
+    f3: Array<F::Scalar, F::Dim>,
+    x: Array<F::Scalar, F::Dim>,
+    nx: Array<F::Scalar, F::Dim>,
+    a: Array<F::Scalar, F::Dim>,
+    na: Array<F::Scalar, F::Dim>,
+    nb: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: SemiImplicitT> ETDRK4<F> {
+    fn coefficients(&mut self) {
+        let dt = self.dt;
+        Zip::from(&mut self.e)
+            .and(&mut self.e2)
+            .and(&mut self.q)
+            .and(&self.diag)
+            .apply(|e, e2, q, &d| {
+                let z = d.mul_real(dt);
+                *e = z.exp();
+                *e2 = z.mul_real(F::Scalar::real(0.5)).exp();
+                *q = phi1(z.mul_real(F::Scalar::real(0.5))).mul_real(dt * F::Scalar::real(0.5));
+            });
+        let one = <F::Scalar as Scalar>::Complex::one();
+        let c = |v: f64| one.mul_real(F::Scalar::real(v));
+        Zip::from(&mut self.f1)
+            .and(&mut self.f2)
+            .and(&mut self.f3)
+            .and(&self.diag)
+            .apply(|f1, f2, f3, &d| {
+                let z = d.mul_real(dt);
+                *f1 = contour_mean(z, |z| {
+                    (c(-4.0) - z + z.exp() * (c(4.0) - z.mul_real(F::Scalar::real(3.0)) + z * z))
+                        / (z * z * z)
+                })
+                .mul_real(dt);
+                *f2 = contour_mean(z, |z| (c(2.0) + z + z.exp() * (c(-2.0) + z)) / (z * z * z))
+                    .mul_real(dt);
+                *f3 = contour_mean(z, |z| {
+                    (c(-4.0) - z.mul_real(F::Scalar::real(3.0)) - z * z + z.exp() * (c(4.0) - z))
+                        / (z * z * z)
+                })
+                .mul_real(dt);
+            });
+    }
+}
+
+impl<F: SemiImplicitT> Scheme for ETDRK4<F> {
+    type Core = F;
+    fn new(nlin: F, dt: Self::Time) -> Self {
+        let diag = nlin.diag_t();
+        let e = Array::zeros(nlin.model_size());
+        let e2 = Array::zeros(nlin.model_size());
+        let q = Array::zeros(nlin.model_size());
+        let f1 = Array::zeros(nlin.model_size());
+        let f2 = Array::zeros(nlin.model_size());
+        let f3 = Array::zeros(nlin.model_size());
+        let x = Array::zeros(nlin.model_size());
+        let nx = Array::zeros(nlin.model_size());
+        let a = Array::zeros(nlin.model_size());
+        let na = Array::zeros(nlin.model_size());
+        let nb = Array::zeros(nlin.model_size());
+        let mut etd = ETDRK4 {
+            nlin,
+            dt,
+            t: Zero::zero(),
+            diag,
+            e,
+            e2,
+            q,
+            f1,
+            f2,
+            f3,
+            x,
+            nx,
+            a,
+            na,
+            nb,
+        };
+        etd.coefficients();
+        etd
+    }
+    fn core(&self) -> &Self::Core {
+        &self.nlin
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.nlin
+    }
+}
+
+impl<F: SemiImplicitT> TimeStep for ETDRK4<F> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+        self.coefficients();
+    }
+}
+
+impl<F: SemiImplicitT> ModelSpec for ETDRK4<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.nlin.model_size()
+    }
+}
+
+impl<F: SemiImplicitT> TimeEvolution for ETDRK4<F> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let two = F::Scalar::real(2.0);
+        let t = self.t;
+        let dt = self.dt;
+        let dt_2 = self.dt * F::Scalar::real(0.5);
+        let f = &mut self.nlin;
+        self.x.zip_mut_with(x, |buf, x| *buf = *x);
+        // N(x)
+        self.nx.zip_mut_with(x, |buf, x| *buf = *x);
+        f.nlin_t(t, &mut self.nx);
+        // a = exp(hL/2) x + Q N(x)
+        Zip::from(&mut self.a)
+            .and(&self.x)
+            .and(&self.nx)
+            .and(&self.e2)
+            .and(&self.q)
+            .apply(|a, &x, &nx, &e2, &q| {
+                *a = e2 * x + q * nx;
+            });
+        self.na.zip_mut_with(&self.a, |buf, a| *buf = *a);
+        f.nlin_t(t + dt_2, &mut self.na);
+        // b = exp(hL/2) x + Q N(a)
+        Zip::from(&mut self.nb)
+            .and(&self.x)
+            .and(&self.na)
+            .and(&self.e2)
+            .and(&self.q)
+            .apply(|b, &x, &na, &e2, &q| {
+                *b = e2 * x + q * na;
+            });
+        f.nlin_t(t + dt_2, &mut self.nb);
+        // c = exp(hL/2) a + Q (2N(b) - N(x))
+        Zip::from(&mut *x)
+            .and(&self.a)
+            .and(&self.nb)
+            .and(&self.nx)
+            .and(&self.e2)
+            .and(&self.q)
+            .apply(|c, &a, &nb, &nx, &e2, &q| {
+                *c = e2 * a + q * (nb.mul_real(two) - nx);
+            });
+        let nc = f.nlin_t(t + dt, x);
+        Zip::from(&mut *nc)
+            .and(&self.x)
+            .and(&self.e)
+            .and(&self.f3)
+            .apply(|nc, &x, &e, &f3| {
+                *nc = e * x + f3 * *nc;
+            });
+        Zip::from(&mut *nc)
+            .and(&self.nx)
+            .and(&self.na)
+            .and(&self.nb)
+            .and(&self.f1)
+            .and(&self.f2)
+            .apply(|nc, &nx, &na, &nb, &f1, &f2| {
+                *nc += f1 * nx + f2 * (na + nb).mul_real(two);
+            });
+        self.t += dt;
+        nc
+    }
+}
+
+impl<F: SemiImplicitJacobian<Dim = Ix1>> TangentScheme for DiagRK4<F> {
+    type Tangent = DiagRK4<Variational<F>>;
+}
+
+impl<F: SemiImplicitJacobian<Dim = Ix1>> TangentScheme for ETD1<F> {
+    type Tangent = ETD1<Variational<F>>;
+}
+
+impl<F: SemiImplicitJacobian<Dim = Ix1>> TangentScheme for ETDRK2<F> {
+    type Tangent = ETDRK2<Variational<F>>;
+}
+
+impl<F: SemiImplicitJacobian<Dim = Ix1>> TangentScheme for ETDRK4<F> {
+    type Tangent = ETDRK4<Variational<F>>;
+}
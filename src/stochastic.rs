@@ -0,0 +1,636 @@
+//! stochastic schemes for SDE `dx = f(t, x) dt + g(t, x) dW`
+//!
+//! Each scheme owns a random number generator which is seeded by zero in `Scheme::new`,
+//! so that runs are reproducible. Use `seed` or `with_rng` to draw another realization.
+//! The default generator [ChaChaRng] keeps its seed and position in the stream,
+//! and thus schemes are checkpointed with the noise to be drawn.
+//!
+//! [ChaChaRng]: struct.ChaChaRng.html
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::{Float, Zero};
+use rand::{Error, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use super::traits::*;
+
+/// ChaCha20 generator which remembers its seed
+///
+/// This draws the same stream as `rand::rngs::StdRng` for the same seed,
+/// and it is serialized as the seed and the position in the stream with the `serde` feature.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "ChaChaState", into = "ChaChaState")
+)]
+pub struct ChaChaRng {
+    seed: [u8; 32],
+    rng: ChaCha20Rng,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ChaChaState {
+    seed: [u8; 32],
+    word_pos: u128,
+}
+
+#[cfg(feature = "serde")]
+impl From<ChaChaState> for ChaChaRng {
+    fn from(state: ChaChaState) -> Self {
+        let mut rng = ChaChaRng::from_seed(state.seed);
+        rng.rng.set_word_pos(state.word_pos);
+        rng
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ChaChaRng> for ChaChaState {
+    fn from(rng: ChaChaRng) -> Self {
+        ChaChaState {
+            seed: rng.seed,
+            word_pos: rng.rng.get_word_pos(),
+        }
+    }
+}
+
+impl ChaChaRng {
+    /// Seed of the stream
+    pub fn get_seed(&self) -> [u8; 32] {
+        self.seed
+    }
+}
+
+impl SeedableRng for ChaChaRng {
+    type Seed = [u8; 32];
+    fn from_seed(seed: Self::Seed) -> Self {
+        ChaChaRng {
+            seed,
+            rng: ChaCha20Rng::from_seed(seed),
+        }
+    }
+}
+
+impl RngCore for ChaChaRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Fill `dw` by increments of independent Wiener processes over `dt`,
+/// using the Box-Muller transform
+fn wiener<A: Scalar, R: Rng>(rng: &mut R, dt: A::Real, dw: &mut Array1<A::Real>) {
+    let sd = Float::sqrt(Float::abs(dt));
+    let mut normal = None;
+    for w in dw.iter_mut() {
+        let z = match normal.take() {
+            Some(z) => z,
+            None => {
+                let u1: f64 = 1.0 - rng.gen::<f64>();
+                let u2: f64 = rng.gen();
+                let r = (-2.0 * u1.ln()).sqrt();
+                let theta = 2.0 * std::f64::consts::PI * u2;
+                normal = Some(r * theta.sin());
+                r * theta.cos()
+            }
+        };
+        *w = A::real(z) * sd;
+    }
+}
+
+/// Additive noise `g = sigma` on every element
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct AdditiveNoise<F: ExplicitT> {
+    pub f: F,
+    pub sigma: <F::Scalar as Scalar>::Real,
+}
+
+impl<F: ExplicitT> AdditiveNoise<F> {
+    pub fn new(f: F, sigma: <F::Scalar as Scalar>::Real) -> Self {
+        AdditiveNoise { f, sigma }
+    }
+}
+
+impl<F: ExplicitT> ModelSpec for AdditiveNoise<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: ExplicitT> ExplicitT for AdditiveNoise<F> {
+    fn rhs_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.f.rhs_t(t, x)
+    }
+}
+
+impl<F: ExplicitT> Diffusion for AdditiveNoise<F> {
+    const NOISE: Noise = Noise::Additive;
+
+    fn noise_size(&self) -> usize {
+        self.f.model_size().into_dimension().size()
+    }
+
+    fn diffusion<S, Sw, Sg>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        _x: &ArrayBase<S, Self::Dim>,
+        dw: &ArrayBase<Sw, Ix1>,
+        gdw: &mut ArrayBase<Sg, Self::Dim>,
+    ) where
+        S: Data<Elem = Self::Scalar>,
+        Sw: Data<Elem = <Self::Scalar as Scalar>::Real>,
+        Sg: DataMut<Elem = Self::Scalar>,
+    {
+        for (g, &w) in gdw.iter_mut().zip(dw.iter()) {
+            *g = F::Scalar::from_real(self.sigma * w);
+        }
+    }
+}
+
+/// Diagonal multiplicative noise `g_i = sigma x_i`
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct MultiplicativeNoise<F: ExplicitT> {
+    pub f: F,
+    pub sigma: <F::Scalar as Scalar>::Real,
+}
+
+impl<F: ExplicitT> MultiplicativeNoise<F> {
+    pub fn new(f: F, sigma: <F::Scalar as Scalar>::Real) -> Self {
+        MultiplicativeNoise { f, sigma }
+    }
+}
+
+impl<F: ExplicitT> ModelSpec for MultiplicativeNoise<F> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: ExplicitT> ExplicitT for MultiplicativeNoise<F> {
+    fn rhs_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.f.rhs_t(t, x)
+    }
+}
+
+impl<F: ExplicitT> Diffusion for MultiplicativeNoise<F> {
+    const NOISE: Noise = Noise::Diagonal;
+
+    fn noise_size(&self) -> usize {
+        self.f.model_size().into_dimension().size()
+    }
+
+    fn diffusion<S, Sw, Sg>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        x: &ArrayBase<S, Self::Dim>,
+        dw: &ArrayBase<Sw, Ix1>,
+        gdw: &mut ArrayBase<Sg, Self::Dim>,
+    ) where
+        S: Data<Elem = Self::Scalar>,
+        Sw: Data<Elem = <Self::Scalar as Scalar>::Real>,
+        Sg: DataMut<Elem = Self::Scalar>,
+    {
+        for ((g, &x), &w) in gdw.iter_mut().zip(x.iter()).zip(dw.iter()) {
+            *g = x.mul_real(self.sigma * w);
+        }
+    }
+
+    fn diffusion_gradient<'a, S>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let s2 = self.sigma * self.sigma;
+        x.map_inplace(|x| *x = x.mul_real(s2));
+        x
+    }
+}
+
+/// Euler-Maruyama scheme (strong order 1/2, or 1 for the additive noise)
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, R: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, R: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct EulerMaruyama<F: Diffusion, R: Rng + Clone = ChaChaRng> {
+    f: F,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    rng: R,
+    dw: Array1<<F::Scalar as Scalar>::Real>,
+    x: Array<F::Scalar, F::Dim>,
+    g: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: Diffusion, R: Rng + Clone> EulerMaruyama<F, R> {
+    pub fn with_rng(f: F, dt: <F::Scalar as Scalar>::Real, rng: R) -> Self {
+        let dw = Array::zeros(f.noise_size());
+        let x = Array::zeros(f.model_size());
+        let g = Array::zeros(f.model_size());
+        let t = Zero::zero();
+        Self {
+            f,
+            dt,
+            t,
+            rng,
+            dw,
+            x,
+            g,
+        }
+    }
+
+    /// Wiener increments used in the last iteration
+    pub fn last_increment(&self) -> &Array1<<F::Scalar as Scalar>::Real> {
+        &self.dw
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone + SeedableRng> EulerMaruyama<F, R> {
+    /// Reset the random number generator
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> TimeStep for EulerMaruyama<F, R> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone + SeedableRng> Scheme for EulerMaruyama<F, R> {
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        Self::with_rng(f, dt, R::seed_from_u64(0))
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> ModelSpec for EulerMaruyama<F, R> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> TimeEvolution for EulerMaruyama<F, R> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let dt = self.dt;
+        wiener::<F::Scalar, R>(&mut self.rng, dt, &mut self.dw);
+        self.x.zip_mut_with(x, |buf, x| *buf = *x);
+        self.f.diffusion(self.t, &self.x, &self.dw, &mut self.g);
+        let fx = self.f.rhs_t(self.t, x);
+        Zip::from(&mut *fx)
+            .and(&self.x)
+            .and(&self.g)
+            .apply(|fx, &x, &g| {
+                *fx = x + fx.mul_real(dt) + g;
+            });
+        self.t += dt;
+        fx
+    }
+}
+
+/// Milstein scheme (strong order 1) for the additive or diagonal noise
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, R: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, R: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Milstein<F: Diffusion, R: Rng + Clone = ChaChaRng> {
+    f: F,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    rng: R,
+    dw: Array1<<F::Scalar as Scalar>::Real>,
+    x: Array<F::Scalar, F::Dim>,
+    g: Array<F::Scalar, F::Dim>,
+    c: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: Diffusion, R: Rng + Clone> Milstein<F, R> {
+    /// Panics for the general noise, which requires the Levy area
+    pub fn with_rng(f: F, dt: <F::Scalar as Scalar>::Real, rng: R) -> Self {
+        assert!(
+            F::NOISE != Noise::General,
+            "Milstein scheme is only implemented for the additive or diagonal noise"
+        );
+        let dw = Array::zeros(f.noise_size());
+        let x = Array::zeros(f.model_size());
+        let g = Array::zeros(f.model_size());
+        let c = Array::zeros(f.model_size());
+        let t = Zero::zero();
+        Self {
+            f,
+            dt,
+            t,
+            rng,
+            dw,
+            x,
+            g,
+            c,
+        }
+    }
+
+    /// Wiener increments used in the last iteration
+    pub fn last_increment(&self) -> &Array1<<F::Scalar as Scalar>::Real> {
+        &self.dw
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone + SeedableRng> Milstein<F, R> {
+    /// Reset the random number generator
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> TimeStep for Milstein<F, R> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone + SeedableRng> Scheme for Milstein<F, R> {
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        Self::with_rng(f, dt, R::seed_from_u64(0))
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> ModelSpec for Milstein<F, R> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> TimeEvolution for Milstein<F, R> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let dt = self.dt;
+        let half = F::Scalar::real(0.5);
+        wiener::<F::Scalar, R>(&mut self.rng, dt, &mut self.dw);
+        self.x.zip_mut_with(x, |buf, x| *buf = *x);
+        self.c.zip_mut_with(x, |buf, x| *buf = *x);
+        self.f.diffusion(self.t, &self.x, &self.dw, &mut self.g);
+        self.f.diffusion_gradient(self.t, &mut self.c);
+        for (c, &w) in self.c.iter_mut().zip(self.dw.iter()) {
+            *c = c.mul_real(half * (w * w - dt));
+        }
+        let fx = self.f.rhs_t(self.t, x);
+        Zip::from(&mut *fx)
+            .and(&self.x)
+            .and(&self.g)
+            .and(&self.c)
+            .apply(|fx, &x, &g, &c| {
+                *fx = x + fx.mul_real(dt) + g + c;
+            });
+        self.t += dt;
+        fx
+    }
+}
+
+/// Stochastic Heun scheme
+///
+/// This is a predictor-corrector scheme which converges to the solution in the Stratonovich sense
+/// for the multiplicative noise. It agrees with the Ito solution for the additive noise.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, R: serde::Serialize, F::Scalar: serde::Serialize, F::Dim: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, R: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, F::Dim: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct StochasticHeun<F: Diffusion, R: Rng + Clone = ChaChaRng> {
+    f: F,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    rng: R,
+    dw: Array1<<F::Scalar as Scalar>::Real>,
+    x: Array<F::Scalar, F::Dim>,
+    k: Array<F::Scalar, F::Dim>,
+    g: Array<F::Scalar, F::Dim>,
+    y: Array<F::Scalar, F::Dim>,
+    gy: Array<F::Scalar, F::Dim>,
+}
+
+impl<F: Diffusion, R: Rng + Clone> StochasticHeun<F, R> {
+    pub fn with_rng(f: F, dt: <F::Scalar as Scalar>::Real, rng: R) -> Self {
+        let dw = Array::zeros(f.noise_size());
+        let x = Array::zeros(f.model_size());
+        let k = Array::zeros(f.model_size());
+        let g = Array::zeros(f.model_size());
+        let y = Array::zeros(f.model_size());
+        let gy = Array::zeros(f.model_size());
+        let t = Zero::zero();
+        Self {
+            f,
+            dt,
+            t,
+            rng,
+            dw,
+            x,
+            k,
+            g,
+            y,
+            gy,
+        }
+    }
+
+    /// Wiener increments used in the last iteration
+    pub fn last_increment(&self) -> &Array1<<F::Scalar as Scalar>::Real> {
+        &self.dw
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone + SeedableRng> StochasticHeun<F, R> {
+    /// Reset the random number generator
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> TimeStep for StochasticHeun<F, R> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone + SeedableRng> Scheme for StochasticHeun<F, R> {
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        Self::with_rng(f, dt, R::seed_from_u64(0))
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> ModelSpec for StochasticHeun<F, R> {
+    type Scalar = F::Scalar;
+    type Dim = F::Dim;
+    fn model_size(&self) -> <Self::Dim as Dimension>::Pattern {
+        self.f.model_size()
+    }
+}
+
+impl<F: Diffusion, R: Rng + Clone> TimeEvolution for StochasticHeun<F, R> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, F::Dim>) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let dt = self.dt;
+        let dt_2 = F::Scalar::real(0.5) * dt;
+        let half = F::Scalar::real(0.5);
+        wiener::<F::Scalar, R>(&mut self.rng, dt, &mut self.dw);
+        // predictor
+        self.x.zip_mut_with(x, |buf, x| *buf = *x);
+        self.k.zip_mut_with(x, |buf, x| *buf = *x);
+        self.f.diffusion(self.t, &self.x, &self.dw, &mut self.g);
+        self.f.rhs_t(self.t, &mut self.k);
+        Zip::from(&mut self.y)
+            .and(&self.x)
+            .and(&self.k)
+            .and(&self.g)
+            .apply(|y, &x, &k, &g| {
+                *y = x + k.mul_real(dt) + g;
+            });
+        // corrector
+        self.f
+            .diffusion(self.t + dt, &self.y, &self.dw, &mut self.gy);
+        x.zip_mut_with(&self.y, |x, y| *x = *y);
+        let fy = self.f.rhs_t(self.t + dt, x);
+        Zip::from(&mut *fy)
+            .and(&self.x)
+            .and(&self.k)
+            .and(&self.g)
+            .and(&self.gy)
+            .apply(|fy, &x, &k, &g, &gy| {
+                *fy = x + (k + *fy).mul_real(dt_2) + (g + gy).mul_real(half);
+            });
+        self.t += dt;
+        fy
+    }
+}
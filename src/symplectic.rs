@@ -0,0 +1,200 @@
+//! symplectic schemes for separable Hamiltonian systems
+
+use ndarray::*;
+use ndarray_linalg::*;
+use num_traits::Zero;
+use std::marker::PhantomData;
+
+use super::traits::*;
+
+/// Coefficients of a splitting method
+///
+/// One step consists of `A.len()` pairs of a drift `q += a_i dt dT/dp(p)`
+/// followed by a kick `p -= b_i dt dV/dq(q)`. Zero coefficients are skipped.
+pub trait Splitting: Clone {
+    /// order of accuracy
+    const ORDER: i32;
+    /// drift coefficients
+    const A: &'static [f64];
+    /// kick coefficients
+    const B: &'static [f64];
+}
+
+/// `1 / (2 - 2^{1/3})` for the triple jump
+const W1: f64 = 1.351_207_191_959_657_8;
+/// `-2^{1/3} / (2 - 2^{1/3})` for the triple jump
+const W0: f64 = -1.702_414_383_919_315_3;
+
+/// Stormer-Verlet (leapfrog) in the drift-kick-drift form
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Leapfrog2;
+
+impl Splitting for Leapfrog2 {
+    const ORDER: i32 = 2;
+    const A: &'static [f64] = &[0.5, 0.5];
+    const B: &'static [f64] = &[1.0, 0.0];
+}
+
+/// Yoshida's triple jump of the leapfrog in the kick-drift-kick form
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Yoshida4th;
+
+impl Splitting for Yoshida4th {
+    const ORDER: i32 = 4;
+    const A: &'static [f64] = &[0.0, W1, W0, W1];
+    const B: &'static [f64] = &[0.5 * W1, 0.5 * (W1 + W0), 0.5 * (W0 + W1), 0.5 * W1];
+}
+
+/// `w_1, w_2, w_3` of the solution A by Yoshida (1990)
+const Y6: [f64; 3] = [
+    -1.177_679_984_178_87,
+    0.235_573_213_359_357,
+    0.784_513_610_477_560,
+];
+/// `w_0 = 1 - 2 (w_1 + w_2 + w_3)`
+const Y6_0: f64 = 1.0 - 2.0 * (Y6[0] + Y6[1] + Y6[2]);
+
+/// Yoshida's 6th order composition of the leapfrog in the drift-kick-drift form
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Yoshida6th;
+
+impl Splitting for Yoshida6th {
+    const ORDER: i32 = 6;
+    const A: &'static [f64] = &[
+        0.5 * Y6[2],
+        0.5 * (Y6[2] + Y6[1]),
+        0.5 * (Y6[1] + Y6[0]),
+        0.5 * (Y6[0] + Y6_0),
+        0.5 * (Y6_0 + Y6[0]),
+        0.5 * (Y6[0] + Y6[1]),
+        0.5 * (Y6[1] + Y6[2]),
+        0.5 * Y6[2],
+    ];
+    const B: &'static [f64] = &[Y6[2], Y6[1], Y6[0], Y6_0, Y6[0], Y6[1], Y6[2], 0.0];
+}
+
+/// Forest-Ruth 4th order scheme
+///
+/// This is the triple jump of the leapfrog in the drift-kick-drift form,
+/// which uses three force evaluations per step.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForestRuth4;
+
+impl Splitting for ForestRuth4 {
+    const ORDER: i32 = 4;
+    const A: &'static [f64] = &[0.5 * W1, 0.5 * (1.0 - W1), 0.5 * (1.0 - W1), 0.5 * W1];
+    const B: &'static [f64] = &[W1, 1.0 - 2.0 * W1, W1, 0.0];
+}
+
+/// Stormer-Verlet scheme
+pub type StormerVerlet<F> = Symplectic<F, Leapfrog2>;
+/// Yoshida 4th order scheme
+pub type Yoshida4<F> = Symplectic<F, Yoshida4th>;
+/// Yoshida 6th order scheme
+pub type Yoshida6<F> = Symplectic<F, Yoshida6th>;
+/// Forest-Ruth scheme
+pub type ForestRuth<F> = Symplectic<F, ForestRuth4>;
+
+/// Splitting scheme for separable Hamiltonian systems
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize, <F::Scalar as Scalar>::Real: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>, <F::Scalar as Scalar>::Real: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Symplectic<F: Hamiltonian, T: Splitting> {
+    f: F,
+    dt: <F::Scalar as Scalar>::Real,
+    t: <F::Scalar as Scalar>::Real,
+    buf: Array1<F::Scalar>,
+    splitting: PhantomData<T>,
+}
+
+impl<F: Hamiltonian, T: Splitting> TimeStep for Symplectic<F, T> {
+    type Time = <F::Scalar as Scalar>::Real;
+
+    fn get_dt(&self) -> Self::Time {
+        self.dt
+    }
+
+    fn set_dt(&mut self, dt: Self::Time) {
+        self.dt = dt;
+    }
+}
+
+impl<F: Hamiltonian, T: Splitting> Scheme for Symplectic<F, T> {
+    type Core = F;
+    fn new(f: F, dt: Self::Time) -> Self {
+        let n = f.model_size();
+        assert_eq!(n % 2, 0, "State of Hamiltonian system must be (q, p)");
+        Self {
+            f,
+            dt,
+            t: Zero::zero(),
+            buf: Array::zeros(n / 2),
+            splitting: PhantomData,
+        }
+    }
+    fn core(&self) -> &Self::Core {
+        &self.f
+    }
+    fn core_mut(&mut self) -> &mut Self::Core {
+        &mut self.f
+    }
+}
+
+impl<F: Hamiltonian, T: Splitting> ModelSpec for Symplectic<F, T> {
+    type Scalar = F::Scalar;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        self.f.model_size()
+    }
+}
+
+impl<F: Hamiltonian, T: Splitting> TimeEvolution for Symplectic<F, T> {
+    fn time(&self) -> Self::Time {
+        self.t
+    }
+
+    fn set_time(&mut self, t: Self::Time) {
+        self.t = t;
+    }
+
+    fn iterate<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        let dt = self.dt;
+        let n = self.buf.len();
+        {
+            let (mut q, mut p) = x.view_mut().split_at(Axis(0), n);
+            for (&a, &b) in T::A.iter().zip(T::B.iter()) {
+                if a != 0.0 {
+                    self.buf.assign(&p);
+                    self.f.velocity(&mut self.buf);
+                    let c = dt * F::Scalar::real(a);
+                    Zip::from(&mut q)
+                        .and(&self.buf)
+                        .apply(|q, &v| *q += v.mul_real(c));
+                }
+                if b != 0.0 {
+                    self.buf.assign(&q);
+                    self.f.force(&mut self.buf);
+                    let c = dt * F::Scalar::real(b);
+                    Zip::from(&mut p)
+                        .and(&self.buf)
+                        .apply(|p, &f| *p += f.mul_real(c));
+                }
+            }
+        }
+        self.t += dt;
+        x
+    }
+}
@@ -0,0 +1,98 @@
+//! Tangent linear propagation by variational equations
+
+use ndarray::*;
+use ndarray_linalg::*;
+
+use crate::traits::*;
+
+/// Model extended by the variational equation
+///
+/// The state is a matrix `[x | V]` of shape `(n, 1 + m)`, where the first column is the state
+/// of the original model and the others are `m` tangent vectors which follow `dV/dt = J(t, x) V`.
+/// Integrating this system by a scheme gives the state and its tangent linear propagation at once.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "F: serde::Serialize, F::Scalar: serde::Serialize",
+        deserialize = "F: serde::Deserialize<'de>, F::Scalar: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Variational<F: ModelSpec<Dim = Ix1>> {
+    f: F,
+    m: usize,
+    x: Array1<F::Scalar>,
+}
+
+impl<F: ModelSpec<Dim = Ix1>> Variational<F> {
+    /// Extend the model `f` by `m` tangent vectors
+    pub fn new(f: F, m: usize) -> Self {
+        let x = Array::zeros(f.model_size());
+        Variational { f, m, x }
+    }
+
+    /// Original model
+    pub fn core(&self) -> &F {
+        &self.f
+    }
+
+    /// Number of tangent vectors
+    pub fn tangent_size(&self) -> usize {
+        self.m
+    }
+}
+
+impl<F: ModelSpec<Dim = Ix1>> ModelSpec for Variational<F> {
+    type Scalar = F::Scalar;
+    type Dim = Ix2;
+
+    fn model_size(&self) -> (usize, usize) {
+        (self.f.model_size(), 1 + self.m)
+    }
+}
+
+impl<F: ExplicitJacobian<Dim = Ix1>> ExplicitT for Variational<F> {
+    fn rhs_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Ix2>,
+    ) -> &'a mut ArrayBase<S, Ix2>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.x.assign(&x.column(0));
+        self.f.rhs_t(t, &mut x.column_mut(0));
+        for j in 1..=self.m {
+            self.f.rhs_tangent(t, &self.x, &mut x.column_mut(j));
+        }
+        x
+    }
+}
+
+impl<F: SemiImplicitJacobian<Dim = Ix1>> SemiImplicitT for Variational<F> {
+    fn nlin_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Ix2>,
+    ) -> &'a mut ArrayBase<S, Ix2>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.x.assign(&x.column(0));
+        self.f.nlin_t(t, &mut x.column_mut(0));
+        for j in 1..=self.m {
+            self.f.nlin_tangent(t, &self.x, &mut x.column_mut(j));
+        }
+        x
+    }
+
+    fn diag_t(&self) -> Array2<Self::Scalar> {
+        let d = self.f.diag_t();
+        let mut diag = Array::zeros(self.model_size());
+        for mut col in diag.axis_iter_mut(Axis(1)) {
+            col.assign(&d);
+        }
+        diag
+    }
+}
@@ -2,7 +2,10 @@
 
 use ndarray::*;
 use ndarray_linalg::*;
-use num_traits::Float;
+use num_traits::{Float, Zero};
+
+use crate::error::{Error, Result};
+use crate::tangent::Variational;
 
 /// Model specification
 pub trait ModelSpec: Clone {
@@ -16,6 +19,24 @@ pub trait TimeStep {
     type Time: Scalar + Float;
     fn get_dt(&self) -> Self::Time;
     fn set_dt(&mut self, dt: Self::Time);
+    /// time-step used in the last iteration
+    ///
+    /// This is identical to `get_dt` for fixed-step schemes,
+    /// and adaptive schemes override it with the step actually accepted.
+    fn last_dt(&self) -> Self::Time {
+        self.get_dt()
+    }
+}
+
+/// Interface for schemes which control their time-step by local error estimates
+pub trait AdaptiveTimeStep: TimeStep {
+    /// absolute and relative tolerances `(atol, rtol)`
+    fn get_tolerance(&self) -> (Self::Time, Self::Time);
+    fn set_tolerance(&mut self, atol: Self::Time, rtol: Self::Time);
+    /// number of accepted steps
+    fn accepted_steps(&self) -> usize;
+    /// number of rejected (and retried) steps
+    fn rejected_steps(&self) -> usize;
 }
 
 /// Core implementation for explicit schemes
@@ -39,8 +60,170 @@ pub trait SemiImplicit: ModelSpec {
     fn diag(&self) -> Array<Self::Scalar, Self::Dim>;
 }
 
+/// Core implementation for symplectic schemes
+///
+/// The model is a separable Hamiltonian system `H(q, p) = T(p) + V(q)`,
+/// and its state is the concatenation `x = (q, p)` of `model_size() / 2` coordinates and momenta.
+pub trait Hamiltonian: ModelSpec<Dim = Ix1> {
+    /// calculate `dT/dp` in-place, i.e. `dq/dt`
+    fn velocity<'a, S>(&mut self, p: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = Self::Scalar>;
+    /// calculate `-dV/dq` in-place, i.e. `dp/dt`
+    fn force<'a, S>(&mut self, q: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = Self::Scalar>;
+    /// value of the Hamiltonian at `x = (q, p)`
+    fn energy<S>(&self, x: &ArrayBase<S, Ix1>) -> Self::Scalar
+    where
+        S: Data<Elem = Self::Scalar>;
+}
+
+/// Core implementation for explicit schemes of non-autonomous systems
+///
+/// This is implemented for every autonomous `Explicit` model, which ignores the time.
+pub trait ExplicitT: ModelSpec {
+    /// calculate right hand side (rhs) of Explicit from current time and state
+    fn rhs_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>;
+}
+
+impl<F: Explicit> ExplicitT for F {
+    fn rhs_t<'a, S>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.rhs(x)
+    }
+}
+
+/// Core implementation for semi-implicit schemes of non-autonomous systems
+///
+/// This is implemented for every autonomous `SemiImplicit` model, which ignores the time.
+pub trait SemiImplicitT: ModelSpec {
+    /// non-linear part of stiff equation at time `t`
+    fn nlin_t<'a, S>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>;
+    /// diagonal elements of stiff linear part, which must not depend on time
+    fn diag_t(&self) -> Array<Self::Scalar, Self::Dim>;
+}
+
+impl<F: SemiImplicit> SemiImplicitT for F {
+    fn nlin_t<'a, S>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.nlin(x)
+    }
+
+    fn diag_t(&self) -> Array<Self::Scalar, Self::Dim> {
+        self.diag()
+    }
+}
+
+/// Tangent linear model of `ExplicitT`
+pub trait ExplicitJacobian: ExplicitT {
+    /// calculate `J dx` in-place, where `J` is the Jacobian matrix of rhs at `(t, x)`
+    fn rhs_tangent<'a, S, St>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &ArrayBase<S, Self::Dim>,
+        dx: &'a mut ArrayBase<St, Self::Dim>,
+    ) -> &'a mut ArrayBase<St, Self::Dim>
+    where
+        S: Data<Elem = Self::Scalar>,
+        St: DataMut<Elem = Self::Scalar>;
+}
+
+/// Tangent linear model of the non-linear part of `SemiImplicitT`
+pub trait SemiImplicitJacobian: SemiImplicitT {
+    /// calculate `J dx` in-place, where `J` is the Jacobian matrix of the non-linear part at `(t, x)`
+    fn nlin_tangent<'a, S, St>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &ArrayBase<S, Self::Dim>,
+        dx: &'a mut ArrayBase<St, Self::Dim>,
+    ) -> &'a mut ArrayBase<St, Self::Dim>
+    where
+        S: Data<Elem = Self::Scalar>,
+        St: DataMut<Elem = Self::Scalar>;
+}
+
+/// Structure of the diffusion coefficient `g(t, x)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Noise {
+    /// `g` depends only on time, and is diagonal
+    Additive,
+    /// `g` is diagonal, and `g_i` depends only on `x_i`
+    Diagonal,
+    /// `g` is a general `n x m` matrix
+    General,
+}
+
+/// Core implementation for stochastic schemes
+///
+/// The model represents the Ito SDE `dx = f(t, x) dt + g(t, x) dW`,
+/// where the drift `f` is given by `ExplicitT`.
+pub trait Diffusion: ExplicitT {
+    /// structure of the diffusion coefficient
+    const NOISE: Noise;
+    /// number of independent Wiener processes, i.e. the size of `dW`
+    fn noise_size(&self) -> usize;
+    /// calculate `g(t, x) dw` into `gdw`
+    ///
+    /// For the additive and diagonal noise, `dw[i]` drives the `i`-th element of `x` in logical order.
+    fn diffusion<S, Sw, Sg>(
+        &mut self,
+        t: <Self::Scalar as Scalar>::Real,
+        x: &ArrayBase<S, Self::Dim>,
+        dw: &ArrayBase<Sw, Ix1>,
+        gdw: &mut ArrayBase<Sg, Self::Dim>,
+    ) where
+        S: Data<Elem = Self::Scalar>,
+        Sw: Data<Elem = <Self::Scalar as Scalar>::Real>,
+        Sg: DataMut<Elem = Self::Scalar>;
+    /// calculate `g_i dg_i/dx_i` in-place for the diagonal noise, which is used in the Milstein scheme
+    ///
+    /// This vanishes for the additive noise.
+    fn diffusion_gradient<'a, S>(
+        &mut self,
+        _t: <Self::Scalar as Scalar>::Real,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> &'a mut ArrayBase<S, Self::Dim>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        x.fill(Self::Scalar::zero());
+        x
+    }
+}
+
 /// Time-evolution operator
+///
+/// The operator keeps the current simulation time, which is advanced by `iterate`.
 pub trait TimeEvolution: ModelSpec + TimeStep {
+    /// current simulation time
+    fn time(&self) -> Self::Time;
+    fn set_time(&mut self, t: Self::Time);
+
     /// calculate next step
     fn iterate<'a, S>(
         &mut self,
@@ -63,6 +246,62 @@ pub trait TimeEvolution: ModelSpec + TimeStep {
         }
         a
     }
+
+    /// calculate next step, and fail if the state becomes NaN or infinity
+    fn try_iterate<'a, S>(
+        &mut self,
+        x: &'a mut ArrayBase<S, Self::Dim>,
+    ) -> Result<&'a mut ArrayBase<S, Self::Dim>>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        self.iterate(x);
+        check_finite(x, self.time())?;
+        Ok(x)
+    }
+
+    /// calculate n-step, and stop at the first step failed
+    fn try_iterate_n<'a, S>(
+        &mut self,
+        a: &'a mut ArrayBase<S, Self::Dim>,
+        n: usize,
+    ) -> Result<&'a mut ArrayBase<S, Self::Dim>>
+    where
+        S: DataMut<Elem = Self::Scalar>,
+    {
+        for _ in 0..n {
+            self.try_iterate(a)?;
+        }
+        Ok(a)
+    }
+}
+
+/// Check that all elements of `x` are finite, or returns `Error::NonFinite` at the time `t`
+pub fn check_finite<A, S, D, T>(x: &ArrayBase<S, D>, t: T) -> Result<()>
+where
+    A: Scalar,
+    S: Data<Elem = A>,
+    D: Dimension,
+    T: Float,
+{
+    if x.iter().all(|x| x.abs().is_finite()) {
+        Ok(())
+    } else {
+        Err(Error::NonFinite {
+            time: t.to_f64().unwrap_or(f64::NAN),
+        })
+    }
+}
+
+/// Time-evolution operators which evaluate the solution within the last step
+///
+/// The last step covers `[time() - last_dt(), time()]`. The interpolation is valid
+/// until the next call of `iterate`, `set_time` or `set_dt`.
+pub trait DenseOutput: TimeEvolution {
+    /// Write the solution at `t` in the last step into `x`
+    fn interpolate<S>(&mut self, t: Self::Time, x: &mut ArrayBase<S, Self::Dim>)
+    where
+        S: DataMut<Elem = Self::Scalar>;
 }
 
 /// Time evolution schemes
@@ -75,3 +314,28 @@ pub trait Scheme: TimeEvolution {
     /// Get mutable core
     fn core_mut(&mut self) -> &mut Self::Core;
 }
+
+/// Schemes which integrate the variational equation along with the state
+///
+/// The tangent scheme is the same scheme applied to the [Variational] system,
+/// which gives the exact Jacobian of the discrete time-evolution.
+///
+/// [Variational]: ../tangent/struct.Variational.html
+pub trait TangentScheme: Scheme<Dim = Ix1> {
+    type Tangent: Scheme<
+        Core = Variational<Self::Core>,
+        Scalar = Self::Scalar,
+        Dim = Ix2,
+        Time = Self::Time,
+    >;
+
+    /// Create the tangent scheme propagating `m` tangent vectors at the same time-step and time
+    fn tangent(&self, m: usize) -> Self::Tangent
+    where
+        Self::Core: Clone,
+    {
+        let mut teo = Self::Tangent::new(Variational::new(self.core().clone(), m), self.get_dt());
+        teo.set_time(self.time());
+        teo
+    }
+}
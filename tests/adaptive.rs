@@ -0,0 +1,84 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::traits::*;
+use eom::*;
+
+/// Reference solution of Lorenz63 at `t = 1` by RK4 with tiny time-step
+fn reference() -> Array1<f64> {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 1e-5);
+    adaptor::iterate(&mut teo, arr1(&[1.0, 0.0, 0.0]), 100_000)
+}
+
+/// Integrate Lorenz63 to `t = 1` exactly by shortening the last step
+fn integrate<Sc>(teo: &mut Sc) -> Array1<f64>
+where
+    Sc: TimeEvolution<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    let mut t = 0.0;
+    while t < 1.0 {
+        if t + teo.get_dt() > 1.0 {
+            teo.set_dt(1.0 - t);
+        }
+        teo.iterate(&mut x);
+        t += teo.last_dt();
+    }
+    x
+}
+
+fn check_tolerance<Sc>(mut teo: Sc, rtol: f64)
+where
+    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64> + AdaptiveTimeStep,
+{
+    teo.set_tolerance(rtol * 1e-2, rtol);
+    let x = integrate(&mut teo);
+    let dev = (&x - &reference()).norm_l2() / x.norm_l2();
+    assert!(dev < 1e2 * rtol, "dev = {}", dev);
+    assert!(teo.accepted_steps() > 0);
+}
+
+#[test]
+fn dormand_prince() {
+    check_tolerance(
+        adaptive::DormandPrince::new(ode::Lorenz63::default(), 0.1),
+        1e-8,
+    );
+}
+
+#[test]
+fn cash_karp() {
+    check_tolerance(adaptive::CashKarp::new(ode::Lorenz63::default(), 0.1), 1e-8);
+}
+
+#[test]
+fn bogacki_shampine() {
+    check_tolerance(
+        adaptive::BogackiShampine::new(ode::Lorenz63::default(), 0.1),
+        1e-6,
+    );
+}
+
+#[test]
+fn reject_large_step() {
+    let mut teo = adaptive::DormandPrince::new(ode::Lorenz63::default(), 10.0);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    teo.iterate(&mut x);
+    assert_eq!(teo.accepted_steps(), 1);
+    assert!(teo.rejected_steps() > 0);
+    assert!(teo.last_dt() < 10.0);
+}
+
+#[test]
+fn timed_series() {
+    let mut teo = adaptive::DormandPrince::new(ode::Lorenz63::default(), 0.01);
+    let ts: Vec<_> = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo)
+        .timed()
+        .take(100)
+        .collect();
+    let dts: Vec<f64> = ts.windows(2).map(|w| w[1].0 - w[0].0).collect();
+    assert!(dts.iter().all(|&dt| dt > 0.0));
+    let dt_min = dts.iter().cloned().fold(f64::INFINITY, f64::min);
+    let dt_max = dts.iter().cloned().fold(0.0, f64::max);
+    assert!(dt_max > 1.1 * dt_min);
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "serde")]
+
+use ndarray::*;
+use ndarray_linalg::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use eom::checkpoint::Checkpoint;
+use eom::traits::*;
+use eom::*;
+
+/// Run `n` steps, save a checkpoint through JSON, resume `n` steps,
+/// and compare with the uninterrupted run of `2n` steps
+fn resume<TEO>(teo: TEO, x0: Array<TEO::Scalar, TEO::Dim>, n: usize)
+where
+    TEO: TimeEvolution,
+    TEO::Time: PartialEq + std::fmt::Debug,
+    Checkpoint<TEO>: Serialize + DeserializeOwned,
+{
+    let mut uninterrupted = teo.clone();
+    let mut x = x0.clone();
+    uninterrupted.iterate_n(&mut x, 2 * n);
+
+    let mut teo = teo;
+    let mut y = x0;
+    teo.iterate_n(&mut y, n);
+    let json = serde_json::to_string(&Checkpoint::new(&teo, &y)).unwrap();
+    drop(teo);
+    let cp: Checkpoint<TEO> = serde_json::from_str(&json).unwrap();
+    let (mut teo, mut y) = cp.restore();
+    teo.iterate_n(&mut y, n);
+
+    assert_eq!(x, y);
+    assert_eq!(uninterrupted.time(), teo.time());
+    assert_eq!(uninterrupted.get_dt(), teo.get_dt());
+}
+
+#[test]
+fn explicit() {
+    let f = ode::Lorenz96::default();
+    let x0 = Array::from_shape_fn(f.n, |i| (i as f64).sin());
+    resume(explicit::RK4::new(f, 0.01), x0, 500);
+}
+
+#[test]
+fn adaptive() {
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    resume(
+        adaptive::DormandPrince::new(ode::Lorenz63::default(), 0.01),
+        x0,
+        100,
+    );
+}
+
+#[test]
+fn semi_implicit() {
+    let mut x0 = Array::from_elem(27, c64::new(0.0, 0.0));
+    for i in 2..7 {
+        x0[i] = c64::new(1.0, 0.0);
+    }
+    resume(
+        semi_implicit::DiagRK4::new(ode::GoyShell::default(), 1e-4),
+        x0,
+        500,
+    );
+}
+
+/// BDF keeps the history of states, which must be restored too
+#[test]
+fn implicit() {
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    resume(
+        implicit::BDF3::<_>::new(ode::Robertson::default(), 1e-3),
+        x0,
+        20,
+    );
+}
+
+/// The generator is restored at the same position in the noise stream
+#[test]
+fn stochastic() {
+    use eom::stochastic::*;
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let f = AdditiveNoise::new(ode::Lorenz63::default(), 1.0);
+    resume(EulerMaruyama::<_>::new(f, 0.01), x0.clone(), 101);
+    resume(StochasticHeun::<_>::new(f, 0.01), x0.clone(), 101);
+    let f = MultiplicativeNoise::new(ode::Lorenz63::default(), 0.1);
+    let mut teo = Milstein::<_>::new(f, 0.01);
+    teo.seed(7);
+    resume(teo, x0, 101);
+}
+
+#[test]
+fn checkpoint() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    let mut teo2 = teo.clone();
+    teo2.iterate_n(&mut x, 10);
+    let cp = Checkpoint::new(&teo2, &x);
+    assert!((cp.time() - 0.1).abs() < 1e-12);
+    assert_eq!(cp.dt(), 0.01);
+    assert_eq!(cp.state, x);
+    let value = serde_json::to_value(&cp.scheme).unwrap();
+    assert_eq!(value["dt"], 0.01);
+    assert_eq!(value["f"]["p"], 10.0);
+}
@@ -0,0 +1,135 @@
+use ndarray::*;
+use std::io::Cursor;
+
+use eom::lyapunov::*;
+use eom::traits::*;
+use eom::*;
+
+fn assert_same(expected: &[CLV<f64>], actual: Vec<CLV<f64>>) {
+    assert_eq!(expected.len(), actual.len());
+    for (e, a) in expected.iter().zip(actual.iter()) {
+        assert_eq!(e, a);
+    }
+}
+
+#[test]
+fn same_as_vectors() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let duration = 200;
+    let expected = vectors(teo.clone(), x0.clone(), 1e-7, duration);
+    // segments dividing the duration or not, and longer than the whole run
+    for &segment in &[1, 7, 20, 1000] {
+        let buffer = Cursor::new(Vec::new());
+        let clv = vectors_checkpointed(teo.clone(), x0.clone(), 1e-7, duration, segment, buffer)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_same(&expected, clv);
+    }
+}
+
+/// Path in the temporary directory, which is removed when dropped even if the test panics
+struct TempFile(std::path::PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        let file = format!("eom-{}-{}.bin", name, std::process::id());
+        TempFile(std::env::temp_dir().join(file))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn same_as_vectors_exact() {
+    let teo = explicit::RK4::new(ode::Lorenz96 { f: 8.0, n: 6 }, 0.01);
+    let x0 = Array::from_shape_fn(6, |i| (i as f64).sin());
+    let duration = 100;
+    let expected = vectors_exact(teo.clone(), x0.clone(), duration);
+    let tmp = TempFile::new("clv");
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&tmp.0)
+        .unwrap();
+    let clv = CheckpointedVectors::new(Series::exact(teo, x0), duration, 16, file)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>();
+    assert_same(&expected, clv.unwrap());
+}
+
+#[test]
+fn principal_angles_of_planes() {
+    let theta: f64 = 0.3;
+    let a = arr2(&[[1.0, 0.0], [0.0, 1.0], [0.0, 0.0]]);
+    let b = arr2(&[[theta.cos()], [0.0], [theta.sin()]]);
+    let angles = principal_angles(&a, &b);
+    assert_eq!(angles.len(), 1);
+    assert!((angles[0] - theta).abs() < 1e-12);
+    // same subspace with different bases
+    let c = arr2(&[[1.0, 1.0], [1.0, -1.0], [0.0, 0.0]]);
+    assert!(principal_angles(&a, &c).iter().all(|x| x.abs() < 1e-7));
+    assert_eq!(principal_angles(&a, &Array2::<f64>::zeros((3, 0))).len(), 0);
+
+    let v = arr2(&[[1.0, 1.0], [0.0, -1.0]]);
+    let angles: Array2<f64> = pairwise_angles(&v);
+    assert!((angles[(0, 1)] - std::f64::consts::FRAC_PI_4).abs() < 1e-12);
+    assert!(angles[(0, 0)].abs() < 1e-7);
+}
+
+#[test]
+fn subspace_angles_of_lorenz63() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let clv = vectors(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7, 1000);
+    let splitting = Splitting::from_exponents(&arr1(&[0.9056, 0.001, -14.5723]), 0.01);
+    assert_eq!(
+        splitting,
+        Splitting {
+            unstable: 1,
+            neutral: 1,
+            stable: 1
+        }
+    );
+    let angles = subspace_angles(&clv, splitting);
+    assert_eq!(angles.len(), 1000);
+    for a in &angles {
+        let cu_s = a.center_unstable_stable.unwrap();
+        // the minimal angle to the stable subspace is not larger than those of each subspace
+        assert!(cu_s <= a.unstable_stable.unwrap() + 1e-12);
+        assert!(cu_s <= a.neutral_stable.unwrap() + 1e-12);
+        assert!(a.unstable_neutral.unwrap() >= 0.0);
+    }
+    let h = angle_histogram(angles.iter().filter_map(|a| a.center_unstable_stable), 10);
+    assert_eq!(h.counts.sum(), 1000);
+
+    let unstable_only = Splitting {
+        unstable: 3,
+        neutral: 0,
+        stable: 0,
+    };
+    let a = SubspaceAngles::new(&clv[0].1, unstable_only);
+    assert_eq!(a.unstable_stable, None);
+}
+
+#[test]
+fn domination() {
+    let x = Array1::zeros(3);
+    let v = Array2::eye(3);
+    let mut clv: Vec<CLV<f64>> = (0..10)
+        .map(|_| (x.clone(), v.clone(), arr1(&[2.0, 1.0, 0.5])))
+        .collect();
+    // the second CLV grows faster than the first only within the third window
+    clv[4].2 = arr1(&[1.0, 8.0, 0.5]);
+    let dos = Domination::new(&clv, 2);
+    assert_eq!(dos.windows, 5);
+    assert_eq!(dos.violation, arr1(&[0.2, 0.0]));
+    assert!(!dos.is_dominated(0));
+    assert!(dos.is_dominated(1));
+}
@@ -0,0 +1,106 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::traits::*;
+use eom::*;
+
+fn x0() -> Array1<f64> {
+    arr1(&[1.0, 1.0, 20.0])
+}
+
+/// Reference solution at `t` by RK4 with a small time-step
+fn reference(t: f64) -> Array1<f64> {
+    let n = 1000;
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), t / n as f64);
+    adaptor::iterate(&mut teo, x0(), n)
+}
+
+/// Error of the interpolation at the middle of the first step
+fn mid_error<Sc>(teo: &mut Sc, dt: f64) -> f64
+where
+    Sc: DenseOutput<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    teo.set_dt(dt);
+    teo.set_time(0.0);
+    let mut x = x0();
+    teo.iterate(&mut x);
+    let mut y = x.clone();
+    teo.interpolate(0.5 * dt, &mut y);
+    (y - reference(0.5 * dt)).norm_l2()
+}
+
+/// The local error of the interpolation is `O(dt^{p+1})` for the dense output of order `p`
+fn order<Sc>(mut teo: Sc) -> f64
+where
+    Sc: DenseOutput<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    let e0 = mid_error(&mut teo, 0.01);
+    let e1 = mid_error(&mut teo, 0.005);
+    (e0 / e1).log2() - 1.0
+}
+
+#[test]
+fn orders() {
+    let f = ode::Lorenz63::default();
+    let p = order(explicit::Euler::new(f, 0.01));
+    assert!((p - 1.0).abs() < 0.1, "order = {}", p);
+    let p = order(explicit::Heun::new(f, 0.01));
+    assert!((p - 2.0).abs() < 0.1, "order = {}", p);
+    let p = order(explicit::RK4::new(f, 0.01));
+    assert!((p - 3.0).abs() < 0.1, "order = {}", p);
+}
+
+/// The interpolation coincides with the scheme at both ends of the step
+fn ends<Sc>(mut teo: Sc)
+where
+    Sc: DenseOutput<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    let mut x = x0();
+    teo.iterate(&mut x);
+    let t0 = teo.time();
+    let x1 = x.clone();
+    teo.iterate(&mut x);
+    let mut y = x.clone();
+    teo.interpolate(t0, &mut y);
+    assert!((&y - &x1).norm_l2() < 1e-12, "{} {}", y, x1);
+    teo.interpolate(teo.time(), &mut y);
+    assert!((&y - &x).norm_l2() < 1e-12, "{} {}", y, x);
+}
+
+#[test]
+fn interpolation_ends() {
+    let f = ode::Lorenz63::default();
+    ends(explicit::Euler::new(f, 0.01));
+    ends(explicit::Heun::new(f, 0.01));
+    ends(explicit::RK4::new(f, 0.01));
+    ends(adaptive::DormandPrince::new(f, 0.01));
+    ends(adaptive::CashKarp::new(f, 0.01));
+    ends(adaptive::BogackiShampine::new(f, 0.01));
+}
+
+#[test]
+fn sample_irregular() {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let times = vec![0.0, 0.004, 0.01, 0.0137, 0.0137, 0.1234, 0.2];
+    let samples: Vec<_> = adaptor::sample(x0(), &mut teo, times.clone()).collect();
+    assert_eq!(samples.len(), times.len());
+    assert_eq!(samples[0].1, x0());
+    for ((t, x), &t_) in samples.iter().zip(times.iter()) {
+        assert_eq!(*t, t_);
+        let e = (x - &reference(*t)).norm_l2();
+        assert!(e < 1e-5, "t = {}, error = {}", t, e);
+    }
+
+    let mut teo = adaptive::DormandPrince::new(ode::Lorenz63::default(), 0.01);
+    for (t, x) in adaptor::sample(x0(), &mut teo, times) {
+        let e = (x - &reference(t)).norm_l2();
+        assert!(e < 1e-4, "t = {}, error = {}", t, e);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Time-step must be positive")]
+fn sample_nonpositive_dt() {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.0);
+    let _ = adaptor::sample(x0(), &mut teo, vec![0.0, 0.1]);
+}
@@ -0,0 +1,78 @@
+use ndarray::*;
+
+use eom::dynamic::*;
+use eom::error::Error;
+use eom::traits::*;
+use eom::*;
+
+/// Boxed schemes must give the same result as the concrete schemes
+fn check<TEO>(name: &str, mut teo: TEO)
+where
+    TEO: TimeEvolution<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    let f = ode::Lorenz63::default();
+    let registry = Registry::new().with_explicit().with_semi_implicit();
+    let mut boxed = registry.build(name, f, 0.01).unwrap();
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let x = adaptor::iterate(&mut boxed, x0.clone(), 100);
+    let y = adaptor::iterate(&mut teo, x0, 100);
+    assert_eq!(x, y, "{}", name);
+    assert_eq!(boxed.time(), teo.time(), "{}", name);
+    assert_eq!(boxed.last_dt(), teo.last_dt(), "{}", name);
+}
+
+#[test]
+fn same_as_concrete() {
+    let f = ode::Lorenz63::default();
+    check("euler", explicit::Euler::new(f, 0.01));
+    check("rk4", explicit::RK4::new(f, 0.01));
+    check("dormand_prince", adaptive::DormandPrince::new(f, 0.01));
+    check("etdrk4", semi_implicit::ETDRK4::new(f, 0.01));
+}
+
+#[test]
+fn names() {
+    let f = ode::HenonHeiles::default();
+    let mut registry = Registry::new().with_symplectic();
+    let names: Vec<_> = registry.names().collect();
+    assert_eq!(
+        names,
+        ["stormer_verlet", "yoshida4", "yoshida6", "forest_ruth"]
+    );
+    match registry.build("rk4", f, 0.1) {
+        Err(Error::UnknownScheme(name)) => assert_eq!(name, "rk4"),
+        _ => panic!("Must fail"),
+    }
+
+    // replace and add entries
+    registry
+        .register("stormer_verlet", |f, dt| {
+            Box::new(explicit::Heun::new(f, dt))
+        })
+        .register("rk4", |f, dt| Box::new(explicit::RK4::new(f, dt)));
+    assert_eq!(registry.names().count(), 5);
+    let x0 = arr1(&[0.1, 0.1, 0.3, 0.2]);
+    let mut teo = registry.build("stormer_verlet", f, 0.1).unwrap();
+    let x = adaptor::iterate(&mut teo, x0.clone(), 10);
+    let y = adaptor::iterate(&mut explicit::Heun::new(f, 0.1), x0, 10);
+    assert_eq!(x, y);
+}
+
+/// Boxed schemes can be cloned, and keep the fallible stepping of implicit schemes
+#[test]
+fn implicit() {
+    let f = ode::Robertson::default();
+    let registry = Registry::new().with_implicit();
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    for name in &["backward_euler", "bdf2", "bdf5", "radau_iia"] {
+        let mut teo = registry.build(name, f, 1e-3).unwrap();
+        let mut x = x0.clone();
+        teo.try_iterate_n(&mut x, 10).unwrap();
+        let mut teo2 = teo.clone();
+        let mut y = x.clone();
+        teo.iterate_n(&mut x, 10);
+        teo2.iterate_n(&mut y, 10);
+        assert_eq!(x, y, "{}", name);
+        assert_eq!(teo.time(), teo2.time());
+    }
+}
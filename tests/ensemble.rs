@@ -0,0 +1,67 @@
+use ndarray::*;
+
+use eom::ensemble::{self, Ensemble};
+use eom::traits::*;
+use eom::*;
+
+/// Members must evolve as the individual trajectories
+#[test]
+fn iterate() {
+    let teo = explicit::RK4::new(ode::Lorenz96::default(), 0.01);
+    let n = teo.model_size();
+    let x0 = Array::from_shape_fn((10, n), |(m, i)| (i as f64).sin() + 0.1 * m as f64);
+    let mut ens = Ensemble::new(teo.clone(), x0.clone());
+    assert_eq!(ens.len(), 10);
+    ens.iterate();
+    ens.iterate_n(99);
+    assert!((ens.time() - 1.0).abs() < 1e-12);
+    for (m, x) in x0.outer_iter().enumerate() {
+        let x = adaptor::iterate(&mut teo.clone(), x.to_owned(), 100);
+        assert_eq!(ens.member(m), x);
+    }
+}
+
+/// States given in a non-standard layout
+#[test]
+fn layout() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let x0 = arr2(&[
+        [1.0, 2.0, 3.0, 4.0],
+        [0.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 2.0, 3.0],
+    ]);
+    let mut ens = Ensemble::new(teo.clone(), x0.t().to_owned());
+    ens.iterate_n(10);
+    for (m, x) in x0.t().outer_iter().enumerate() {
+        let x = adaptor::iterate(&mut teo.clone(), x.to_owned(), 10);
+        assert_eq!(ens.member(m), x);
+    }
+}
+
+#[test]
+fn statistics() {
+    let x = arr2(&[[1.0, 2.0], [3.0, 6.0]]);
+    assert_eq!(ensemble::mean(&x), arr1(&[2.0, 4.0]));
+    assert_eq!(ensemble::covariance(&x), arr2(&[[2.0, 4.0], [4.0, 8.0]]));
+    let spread: f64 = ensemble::spread(&x);
+    assert!((spread - 5.0_f64.sqrt()).abs() < 1e-12);
+
+    let teo = explicit::RK4::new(ode::Lorenz96::default(), 0.01);
+    let n = teo.model_size();
+    let x0 = Array::from_shape_fn((20, n), |(m, i)| ((i * m) as f64).cos());
+    let mut ens = Ensemble::new(teo, x0);
+    ens.iterate_n(10);
+    let cov = ens.covariance();
+    assert_eq!(cov.dim(), (n, n));
+    assert_eq!(cov, cov.t());
+    let spread = (cov.diag().sum() / n as f64).sqrt();
+    assert!((ens.spread() - spread).abs() < 1e-12);
+}
+
+#[test]
+#[should_panic(expected = "one member at least")]
+fn empty() {
+    let teo = explicit::RK4::new(ode::Lorenz96::default(), 0.01);
+    let n = teo.model_size();
+    Ensemble::new(teo, Array2::zeros((0, n)));
+}
@@ -0,0 +1,66 @@
+use ndarray::*;
+use std::error::Error as _;
+
+use eom::error::Error;
+use eom::traits::*;
+use eom::*;
+
+/// Euler scheme with a too large time-step, which blows up
+fn diverging() -> explicit::Euler<ode::Lorenz63> {
+    explicit::Euler::new(ode::Lorenz63::default(), 0.5)
+}
+
+#[test]
+fn try_iterate() {
+    let mut teo = diverging();
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    match teo.try_iterate_n(&mut x, 10000) {
+        Err(Error::NonFinite { time }) => assert_eq!(time, teo.time()),
+        _ => panic!("Must fail"),
+    }
+
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    let mut y = x.clone();
+    teo.try_iterate_n(&mut x, 100).unwrap();
+    teo.set_time(0.0);
+    teo.iterate_n(&mut y, 100);
+    assert_eq!(x, y);
+}
+
+#[test]
+fn try_accuracy() {
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let res = adaptor::try_accuracy(diverging(), x0.clone(), 0.5, 10000, 2);
+    assert!(matches!(res, Err(Error::NonFinite { .. })));
+
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let acc = adaptor::accuracy(teo.clone(), x0.clone(), 0.01, 100, 3);
+    let try_acc = adaptor::try_accuracy(teo, x0, 0.01, 100, 3).unwrap();
+    assert_eq!(acc, try_acc);
+}
+
+#[test]
+fn try_lyapunov() {
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let res = lyapunov::try_exponents(diverging(), x0.clone(), 1e-7, 1000);
+    let err = res.unwrap_err();
+    assert!(matches!(err, Error::NonFinite { .. }));
+    assert!(err.to_string().contains("not finite"));
+    assert!(err.source().is_none());
+    assert!(lyapunov::try_vectors(diverging(), x0.clone(), 1e-7, 100).is_err());
+
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let l = lyapunov::exponents(teo.clone(), x0.clone(), 1e-7, 1000);
+    let try_l = lyapunov::try_exponents(teo.clone(), x0.clone(), 1e-7, 1000).unwrap();
+    assert_eq!(l, try_l);
+
+    let clv = lyapunov::vectors(teo.clone(), x0.clone(), 1e-7, 100);
+    let try_clv = lyapunov::try_vectors(teo, x0, 1e-7, 100).unwrap();
+    assert_eq!(clv.len(), try_clv.len());
+    for (a, b) in clv.iter().zip(try_clv.iter()) {
+        assert_eq!(a.0, b.0);
+        assert_eq!(a.1, b.1);
+        assert_eq!(a.2, b.2);
+    }
+}
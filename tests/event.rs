@@ -0,0 +1,126 @@
+use ndarray::*;
+use std::f64::consts::PI;
+
+use eom::event::*;
+use eom::traits::*;
+use eom::*;
+
+/// Harmonic oscillator `x'' = -x`
+#[derive(Clone, Copy, Debug)]
+struct Harmonic;
+
+impl ModelSpec for Harmonic {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        2
+    }
+}
+
+impl Explicit for Harmonic {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        let (q, p) = (x[0], x[1]);
+        x[0] = p;
+        x[1] = -q;
+        x
+    }
+}
+
+/// `x = cos(t)` crosses zero at `t = pi/2 + k pi`
+fn position<'g>() -> Event<'g, f64, Ix1> {
+    Event::new(|_t, x: ArrayView1<f64>| x[0])
+}
+
+#[test]
+fn crossing_times() {
+    let mut teo = explicit::RK4::new(Harmonic, 0.1);
+    let oc: Vec<_> = event::detect(arr1(&[1.0, 0.0]), &mut teo, vec![position()])
+        .until(10.0)
+        .collect();
+    assert_eq!(oc.len(), 3);
+    for (k, oc) in oc.iter().enumerate() {
+        let t = PI / 2.0 + k as f64 * PI;
+        assert_eq!(oc.event, 0);
+        assert!((oc.t - t).abs() < 1e-4, "t = {}", oc.t);
+        assert!(oc.x[0].abs() < 1e-10, "x = {}", oc.x);
+        assert!((oc.x[1] + t.sin()).abs() < 1e-4, "x = {}", oc.x);
+    }
+}
+
+#[test]
+fn states_only() {
+    // implicit scheme through the state-only interpolant
+    let mut teo = implicit::RadauIIA::<_>::new(Harmonic, 0.1);
+    let mut det = event::detect_states(arr1(&[1.0, 0.0]), &mut teo, vec![position()]).until(10.0);
+    let oc: Vec<_> = det.by_ref().collect();
+    assert_eq!(oc.len(), 3);
+    for (k, oc) in oc.iter().enumerate() {
+        let t = PI / 2.0 + k as f64 * PI;
+        assert!((oc.t - t).abs() < 1e-4, "t = {}", oc.t);
+        assert!(oc.x[0].abs() < 1e-4, "x = {}", oc.x);
+    }
+    assert!(det.is_finished());
+}
+
+#[test]
+fn direction() {
+    let mut teo = explicit::RK4::new(Harmonic, 0.1);
+    let events = vec![
+        position().direction(Direction::Rising),
+        position().direction(Direction::Falling),
+    ];
+    let oc: Vec<_> = event::detect(arr1(&[1.0, 0.0]), &mut teo, events)
+        .until(10.0)
+        .map(|oc| (oc.event, oc.t))
+        .collect();
+    let index: Vec<_> = oc.iter().map(|oc| oc.0).collect();
+    assert_eq!(index, vec![1, 0, 1]);
+    assert!((oc[1].1 - 1.5 * PI).abs() < 1e-4);
+}
+
+#[test]
+fn terminal() {
+    let mut teo = explicit::RK4::new(Harmonic, 0.1);
+    let events = vec![
+        Event::new(|t, _x: ArrayView1<f64>| t - 1.0),
+        position().direction(Direction::Rising).terminal(true),
+        position(),
+    ];
+    let mut det = event::detect(arr1(&[1.0, 0.0]), &mut teo, events);
+    let oc: Vec<_> = det.by_ref().map(|oc| (oc.event, oc.t)).collect();
+    let index: Vec<_> = oc.iter().map(|oc| oc.0).collect();
+    // the terminal event and the other one at the same time are both reported
+    assert_eq!(index.len(), 4);
+    assert_eq!(&index[..2], &[0, 2]);
+    assert!(index[2..].contains(&1));
+    assert!(det.is_finished());
+    let t = det.time();
+    assert!((t - 1.5 * PI).abs() < 1e-4);
+    assert!(det.state()[0].abs() < 1e-10);
+    assert_eq!(teo.time(), t);
+}
+
+#[test]
+fn nan_inside_step() {
+    let mut teo = explicit::RK4::new(Harmonic, 0.1);
+    let events = vec![
+        // finite only at the ends of the step [1.0, 1.1]
+        Event::new(|t: f64, _x: ArrayView1<f64>| {
+            if t > 1.01 && t < 1.09 {
+                f64::NAN
+            } else {
+                t - 1.05
+            }
+        }),
+        Event::new(|t, _x: ArrayView1<f64>| t - 1.02),
+    ];
+    let oc: Vec<_> = event::detect(arr1(&[1.0, 0.0]), &mut teo, events)
+        .until(1.5)
+        .collect();
+    assert_eq!(oc.len(), 1);
+    assert_eq!(oc[0].event, 1);
+    assert!((oc[0].t - 1.02).abs() < 1e-10);
+}
@@ -0,0 +1,93 @@
+use ndarray::*;
+use rand::SeedableRng;
+
+use eom::fixed_point::*;
+use eom::implicit::AnalyticJacobian;
+use eom::*;
+
+fn assert_close(a: &Array1<f64>, b: &Array1<f64>, tol: f64) {
+    assert!((a - b).iter().all(|d| d.abs() < tol), "{} != {}", a, b);
+}
+
+#[test]
+fn lorenz63_equilibria() {
+    let (r, b) = (28.0, 8.0 / 3.0);
+    let mut solver = Solver::<_, AnalyticJacobian>::new(ode::Lorenz63::new(10.0, r, b));
+
+    let origin = solver.solve(arr1(&[0.1, -0.1, 0.2])).unwrap();
+    assert_close(&origin.x, &arr1(&[0.0, 0.0, 0.0]), 1e-10);
+    assert_eq!(origin.stability, Stability::Saddle);
+    assert_eq!(origin.unstable_dim(), 1);
+
+    let c = (b * (r - 1.0)).sqrt();
+    let fp = solver.solve(arr1(&[8.0, 8.0, 25.0])).unwrap();
+    assert_close(&fp.x, &arr1(&[c, c, r - 1.0]), 1e-10);
+    // r = 28 is above the Hopf bifurcation at r = 24.74
+    assert_eq!(fp.stability, Stability::SaddleFocus);
+    assert_eq!(fp.unstable_dim(), 2);
+}
+
+#[test]
+fn lorenz63_stable() {
+    let mut solver = Solver::<_>::new(ode::Lorenz63::new(10.0, 0.5, 8.0 / 3.0));
+    let fp = solver.solve(arr1(&[1.0, 1.0, 1.0])).unwrap();
+    assert_close(&fp.x, &arr1(&[0.0, 0.0, 0.0]), 1e-8);
+    assert_eq!(fp.stability, Stability::StableNode);
+    assert!(fp.stability.is_stable());
+
+    // stable foci for 1.35 < r < 24.74
+    let mut solver = Solver::<_>::new(ode::Lorenz63::new(10.0, 10.0, 8.0 / 3.0));
+    let fp = solver.solve(arr1(&[5.0, 5.0, 9.0])).unwrap();
+    assert_eq!(fp.stability, Stability::StableFocus);
+}
+
+#[test]
+fn van_der_pol_unstable() {
+    let mut solver = Solver::<_>::new(ode::VanDerPol::new(1.0, 0.0, 1.0));
+    let fp = solver.solve(arr1(&[0.1, 0.1])).unwrap();
+    assert_eq!(fp.stability, Stability::UnstableFocus);
+    let mut solver = Solver::<_>::new(ode::VanDerPol::new(3.0, 0.0, 1.0));
+    let fp = solver.solve(arr1(&[0.1, 0.1])).unwrap();
+    assert_eq!(fp.stability, Stability::UnstableNode);
+}
+
+#[test]
+fn numerical_jacobian() {
+    let f = ode::Lorenz96::default();
+    let x0 = Array1::from_shape_fn(f.n, |i| 8.0 + 0.1 * (i as f64).sin());
+    let fp = Solver::<_>::new(f).solve(x0.clone()).unwrap();
+    let fp_exact = Solver::<_, AnalyticJacobian>::new(f).solve(x0).unwrap();
+    assert_close(&fp.x, &Array1::from_elem(f.n, 8.0), 1e-8);
+    assert_close(&fp_exact.x, &Array1::from_elem(f.n, 8.0), 1e-10);
+    assert_eq!(fp.stability, fp_exact.stability);
+    assert_eq!(fp.unstable_dim(), fp_exact.unstable_dim());
+}
+
+#[test]
+fn random_starts() {
+    let mut solver = Solver::<_, AnalyticJacobian>::new(ode::Lorenz63::default());
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut found = solver.random_starts(50, &arr1(&[0.0, 0.0, 20.0]), 20.0, &mut rng);
+    assert_eq!(found.len(), 3);
+    found.sort_by(|a, b| a.x[0].partial_cmp(&b.x[0]).unwrap());
+    assert_eq!(found[0].stability, Stability::SaddleFocus);
+    assert_eq!(found[1].stability, Stability::Saddle);
+    assert_eq!(found[2].stability, Stability::SaddleFocus);
+    assert!(found[0].x[0] < -8.0 && found[2].x[0] > 8.0);
+}
+
+#[test]
+fn not_converged() {
+    let mut solver = Solver::<_>::new(ode::Lorenz63::default());
+    solver.set_newton(1e-10, 1);
+    assert!(solver.solve(arr1(&[8.0, 8.0, 25.0])).is_err());
+}
+
+#[test]
+fn non_finite() {
+    let mut solver = Solver::<_>::new(ode::Lorenz63::default());
+    match solver.solve(arr1(&[f64::NAN, 0.0, 0.0])) {
+        Err(error::Error::NonFinite { .. }) => {}
+        r => panic!("{:?}", r.map(|fp| fp.x)),
+    }
+}
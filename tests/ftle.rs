@@ -0,0 +1,147 @@
+use ndarray::*;
+
+use eom::lyapunov::*;
+use eom::traits::*;
+use eom::*;
+
+fn series() -> Series<f64, FiniteDifference<explicit::RK4<ode::Lorenz63>>> {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    Series::new(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7)
+}
+
+#[test]
+fn window_is_mean_of_local_rates() {
+    let window = 50;
+    let ftle: Vec<_> = series().finite_time(window).take(4).collect();
+    let rates: Vec<_> = series()
+        .local_rates()
+        .map(|(_x, l)| l)
+        .take(4 * window)
+        .collect();
+    for (k, l) in ftle.iter().enumerate() {
+        let mut mean = Array1::<f64>::zeros(3);
+        for r in &rates[k * window..(k + 1) * window] {
+            mean += r;
+        }
+        mean /= window as f64;
+        assert!(
+            (l - &mean).iter().all(|d| d.abs() < 1e-9),
+            "{} != {}",
+            l,
+            mean
+        );
+    }
+}
+
+#[test]
+fn converge_to_exponents() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let ftle = finite_time_exponents(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7, 500, 40);
+    assert_eq!(ftle.dim(), (40, 3));
+    let stat = ExponentStatistics::new(&ftle);
+    assert_eq!(stat.count, 40);
+    // http://sprott.physics.wisc.edu/chaos/lorenzle.htm
+    let expected = arr1(&[0.9056, 0.0, -14.5723]);
+    for (((&mean, &expected), &min), &max) in stat
+        .mean
+        .iter()
+        .zip(&expected)
+        .zip(&stat.min)
+        .zip(&stat.max)
+    {
+        assert!((mean - expected).abs() < 0.05, "{}", stat.mean);
+        assert!(min <= mean && mean <= max);
+    }
+    // FTLEs fluctuate around the asymptotic values
+    assert!(stat.variance[0] > 0.0);
+    assert!((stat.std()[0] - stat.variance[0].sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn statistics() {
+    let samples = arr2(&[[1.0, -1.0], [2.0, -2.0], [3.0, -6.0]]);
+    let stat = ExponentStatistics::new(&samples);
+    assert_eq!(stat.mean, arr1(&[2.0, -3.0]));
+    assert_eq!(stat.variance, arr1(&[1.0, 7.0]));
+    assert_eq!(stat.min, arr1(&[1.0, -6.0]));
+    assert_eq!(stat.max, arr1(&[3.0, -1.0]));
+}
+
+#[test]
+fn histogram() {
+    let data = arr1(&[0.0, 0.1, 0.5, 0.9, 1.0, 1.5, -0.1, f64::NAN]);
+    let h = Histogram::new(&data, 2, (0.0, 1.0));
+    assert_eq!(h.edges, arr1(&[0.0, 0.5, 1.0]));
+    assert_eq!(h.counts, arr1(&[2, 3]));
+    assert_eq!(h.centers(), arr1(&[0.25, 0.75]));
+    assert_eq!(h.density(), arr1(&[0.8, 1.2]));
+
+    let h = Histogram::auto(&data, 4);
+    assert_eq!(h.edges[0], -0.1);
+    assert_eq!(h.edges[4], 1.5);
+    assert_eq!(h.counts.sum(), 7);
+}
+
+#[test]
+fn running_estimate() {
+    let (t, l) = series().running().nth(99).unwrap();
+    assert!((t - 1.0).abs() < 1e-12);
+    let ftle = series().finite_time(100).next().unwrap();
+    assert_eq!(l, ftle);
+}
+
+#[test]
+fn kaplan_yorke() {
+    assert_eq!(kaplan_yorke_dimension(&arr1(&[1.0, 0.0, -2.0])), 2.5);
+    assert_eq!(kaplan_yorke_dimension(&arr1(&[-1.0, 1.0, -0.5])), 2.5);
+    assert_eq!(kaplan_yorke_dimension(&arr1(&[1.0, 0.5])), 2.0);
+    assert_eq!(kaplan_yorke_dimension(&arr1(&[-1.0, -2.0])), 0.0);
+    assert_eq!(ks_entropy(&arr1(&[1.0, 0.5, 0.0, -2.0]), None), 1.5);
+    assert_eq!(ks_entropy(&arr1(&[1.0, 0.5, 0.0, -2.0]), Some(1)), 1.0);
+    assert!(kaplan_yorke_dimension(&arr1(&[1.0, f64::NAN, -2.0])).is_nan());
+}
+
+#[test]
+fn spectrum_of_lorenz63() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let sp = spectrum(teo, arr1(&[1.0, 0.0, 0.0]), 1e-7, 500, 40);
+    assert_eq!(sp.blocks, 40);
+    // http://sprott.physics.wisc.edu/chaos/lorenzle.htm
+    let expected = arr1(&[0.9056, 0.0, -14.5723]);
+    let (lower, upper) = sp.confidence_interval(4.0);
+    for (((&l, &u), &e), &se) in lower.iter().zip(&upper).zip(&expected).zip(&sp.std_error) {
+        assert!(se > 0.0);
+        assert!(l < e && e < u);
+    }
+    assert_eq!(sp.neutral(4.0), Some(1));
+    assert!((sp.kaplan_yorke_dimension() - 2.0621).abs() < 0.01);
+    assert!((sp.ks_entropy(4.0) - 0.9056).abs() < 0.05);
+}
+
+#[test]
+fn no_neutral_exponent() {
+    let blocks = arr2(&[[1.0, -1.0], [1.1, -1.1], [0.9, -0.9]]);
+    let sp = Spectrum::from_blocks(&blocks);
+    assert_eq!(sp.neutral(3.0), None);
+    assert!((sp.std_error[0] - 0.1 / 3.0f64.sqrt()).abs() < 1e-12);
+}
+
+/// The neutral exponent estimated slightly positive does not contribute to the KS entropy
+#[test]
+fn positive_neutral_exponent() {
+    let blocks: Array2<f64> = arr2(&[[1.0, 0.01, -2.0], [1.1, -0.005, -2.1], [0.9, 0.02, -1.9]]);
+    let sp = Spectrum::from_blocks(&blocks);
+    assert!(sp.exponents[1] > 0.0);
+    assert_eq!(sp.neutral(2.0), Some(1));
+    assert!((sp.ks_entropy(2.0) - 1.0).abs() < 1e-12);
+    // without the neutral exponent detected
+    assert!((sp.ks_entropy(0.0) - 1.0 - sp.exponents[1]).abs() < 1e-12);
+}
+
+#[test]
+fn nan_exponents() {
+    let blocks = arr2(&[[1.0, f64::NAN], [1.1, f64::NAN], [0.9, f64::NAN]]);
+    let sp = Spectrum::from_blocks(&blocks);
+    assert_eq!(sp.neutral(3.0), None);
+    assert!(sp.kaplan_yorke_dimension().is_nan());
+}
@@ -0,0 +1,91 @@
+use ndarray::*;
+
+use eom::error::Error;
+use eom::implicit::*;
+use eom::traits::*;
+use eom::*;
+
+/// Estimate convergence order from the deviations between successive halving of time-step
+fn order<Sc>(mut teo: Sc) -> f64
+where
+    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64> + NewtonIteration,
+{
+    // the default tolerance bounds the error from below
+    teo.set_newton(1e-14, 50);
+    let acc = adaptor::accuracy(teo, arr1(&[1.0, 0.0, 0.0]), 0.01, 50, 6);
+    let (_, dev0) = acc[acc.len() - 2];
+    let (_, dev1) = acc[acc.len() - 1];
+    (dev0 / dev1).log2()
+}
+
+#[test]
+fn orders() {
+    let f = ode::Lorenz63::default();
+    let p = order(BackwardEuler::<_>::new(f, 1.0));
+    assert!((p - 1.0).abs() < 0.2, "order = {}", p);
+    let p = order(BDF2::<_>::new(f, 1.0));
+    assert!((p - 2.0).abs() < 0.2, "order = {}", p);
+    let p = order(BDF3::<_>::new(f, 1.0));
+    assert!((p - 3.0).abs() < 0.3, "order = {}", p);
+    let p = order(BDF4::<_>::new(f, 1.0));
+    assert!((p - 4.0).abs() < 0.3, "order = {}", p);
+    let p = order(BDF5::<_>::new(f, 1.0));
+    assert!((p - 5.0).abs() < 0.5, "order = {}", p);
+    let p = order(RadauIIA::<_>::new(f, 1.0));
+    assert!((p - 5.0).abs() < 0.5, "order = {}", p);
+}
+
+/// Reference values at `t = 40` from Hairer and Wanner
+fn check_robertson<Sc>(mut teo: Sc)
+where
+    Sc: TimeEvolution<Scalar = f64, Dim = Ix1>,
+{
+    let x = adaptor::iterate(&mut teo, arr1(&[1.0, 0.0, 0.0]), 400);
+    assert!((x.sum() - 1.0).abs() < 1e-10, "x = {}", x);
+    assert!((x[0] - 0.7158271).abs() < 1e-4, "x = {}", x);
+    assert!((x[1] - 9.185535e-6).abs() < 1e-8, "x = {}", x);
+    assert!((x[2] - 0.2841637).abs() < 1e-4, "x = {}", x);
+}
+
+#[test]
+fn robertson() {
+    let f = ode::Robertson::default();
+    check_robertson(RadauIIA::<_>::new(f, 0.1));
+    check_robertson(BDF5::<_>::new(f, 0.1));
+    check_robertson(RadauIIA::<_, AnalyticJacobian>::new(f, 0.1));
+    check_robertson(BDF2::<_, AnalyticJacobian>::new(f, 0.1));
+}
+
+#[test]
+fn not_converged() {
+    let f = ode::Lorenz63::default();
+    let mut teo = BackwardEuler::<_>::new(f, 1.0);
+    teo.set_newton(1e-10, 1);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    match teo.try_iterate(&mut x) {
+        Err(Error::Newton(NewtonError::NotConverged { iterations })) => assert_eq!(iterations, 2),
+        _ => panic!("Newton iteration must fail"),
+    }
+    assert_eq!(x, arr1(&[1.0, 0.0, 0.0]));
+    assert_eq!(teo.time(), 0.0);
+
+    teo.iterate(&mut x);
+    assert!(teo.last_error().is_some());
+    assert!(x.iter().all(|x| x.is_nan()));
+
+    teo.set_newton(1e-10, 50);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    teo.iterate(&mut x);
+    assert!(teo.last_error().is_none());
+}
+
+#[test]
+fn restart_history() {
+    let f = ode::Lorenz63::default();
+    let mut teo = BDF4::<_>::new(f, 0.01);
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let x = adaptor::iterate(&mut teo, x0.clone(), 100);
+    teo.set_time(0.0);
+    let y = adaptor::iterate(&mut teo, x0, 100);
+    assert_eq!(x, y);
+}
@@ -0,0 +1,61 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::traits::*;
+use eom::*;
+
+#[test]
+fn fixed_step() {
+    let f = ode::Lorenz63::default();
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+
+    let mut teo = explicit::RK4::new(f, 0.03);
+    let mut x = x0.clone();
+    let report = adaptor::integrate_to(&mut teo, &mut x, 1.0);
+    assert_eq!(report.time, 1.0);
+    assert_eq!(report.steps, 34);
+    assert_eq!(teo.time(), 1.0);
+    assert_eq!(teo.get_dt(), 0.03);
+
+    // 33 full steps and the remaining 0.01
+    let mut ref_teo = explicit::RK4::new(f, 0.03);
+    let mut y = adaptor::iterate(&mut ref_teo, x0, 33);
+    ref_teo.set_dt(1.0 - ref_teo.time());
+    ref_teo.iterate(&mut y);
+    assert!((&x - &y).norm_max() < 1e-12, "x = {}, y = {}", x, y);
+
+    // no leftover step by the rounding error of the time
+    let mut teo = explicit::RK4::new(f, 0.1);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    let report = adaptor::integrate_to(&mut teo, &mut x, 1.0);
+    assert_eq!(report.steps, 10);
+
+    // already at the final time
+    let report = adaptor::integrate_to(&mut teo, &mut x, 1.0);
+    assert_eq!(report.steps, 0);
+}
+
+#[test]
+fn adaptive() {
+    let f = ode::Lorenz63::default();
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let mut teo = adaptive::DormandPrince::new(f, 0.01);
+    teo.set_tolerance(1e-10, 1e-10);
+    let mut x = x0.clone();
+    let report = adaptor::integrate_to(&mut teo, &mut x, 1.0);
+    assert_eq!(report.time, 1.0);
+    assert_eq!(teo.time(), 1.0);
+    assert_eq!(report.steps, teo.accepted_steps());
+
+    let mut ref_teo = explicit::RK4::new(f, 1e-3);
+    let y = adaptor::iterate(&mut ref_teo, x0, 1000);
+    assert!((&x - &y).norm_max() < 1e-8, "x = {}, y = {}", x, y);
+}
+
+#[test]
+#[should_panic(expected = "Time-step must be positive")]
+fn nonpositive_dt() {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.0);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    adaptor::integrate_to(&mut teo, &mut x, 1.0);
+}
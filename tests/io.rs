@@ -0,0 +1,209 @@
+use ndarray::*;
+use ndarray_linalg::*;
+use std::io::Cursor;
+
+use eom::io::*;
+use eom::traits::*;
+use eom::*;
+
+/// Time series of Lorenz 63 model
+fn lorenz63(n: usize) -> Vec<(f64, Array1<f64>)> {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let ts = adaptor::time_series(arr1(&[1.0, 0.0, 0.0]), &mut teo);
+    ts.timed().take(n).collect()
+}
+
+/// Parse NPY into the header dictionary and the data
+fn parse_npy(npy: &[u8]) -> (String, &[u8]) {
+    assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+    let len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+    assert_eq!((10 + len) % 64, 0);
+    assert_eq!(npy[10 + len - 1], b'\n');
+    let dict = String::from_utf8(npy[10..10 + len].to_vec()).unwrap();
+    (dict.trim_end().to_string(), &npy[10 + len..])
+}
+
+fn f64s(data: &[u8]) -> Vec<f64> {
+    data.chunks(8)
+        .map(|b| {
+            let mut buf = [0; 8];
+            buf.copy_from_slice(b);
+            f64::from_le_bytes(buf)
+        })
+        .collect()
+}
+
+fn u16_at(data: &[u8], i: usize) -> usize {
+    u16::from_le_bytes([data[i], data[i + 1]]) as usize
+}
+
+fn u32_at(data: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]])
+}
+
+/// Parse the entries of a stored ZIP archive through its central directory
+fn parse_zip(zip: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let end = zip.len() - 22;
+    assert_eq!(u32_at(zip, end), 0x0605_4b50);
+    let n = u16_at(zip, end + 10);
+    let mut cd = u32_at(zip, end + 16) as usize;
+    let mut entries = Vec::new();
+    for _ in 0..n {
+        assert_eq!(u32_at(zip, cd), 0x0201_4b50);
+        let crc = u32_at(zip, cd + 16);
+        let size = u32_at(zip, cd + 24) as usize;
+        let name_len = u16_at(zip, cd + 28);
+        let offset = u32_at(zip, cd + 42) as usize;
+        let name = String::from_utf8(zip[cd + 46..cd + 46 + name_len].to_vec()).unwrap();
+        // local header must agree with the central directory
+        assert_eq!(u32_at(zip, offset), 0x0403_4b50);
+        assert_eq!(u32_at(zip, offset + 14), crc);
+        assert_eq!(u32_at(zip, offset + 22) as usize, size);
+        let start = offset + 30 + u16_at(zip, offset + 26) + u16_at(zip, offset + 28);
+        let data = zip[start..start + size].to_vec();
+        assert_eq!(crc32fast::hash(&data), crc, "{}", name);
+        entries.push((name, data));
+        cd += 46 + name_len;
+    }
+    entries
+}
+
+#[test]
+fn csv() {
+    let series = lorenz63(10);
+    let mut csv = Csv::new(Vec::new()).header("time", &["x", "y", "z"]);
+    assert_eq!(csv.write_all(series.clone()).unwrap(), 10);
+    let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 11);
+    assert_eq!(lines[0], "time,x,y,z");
+    for (line, (t, x)) in lines[1..].iter().zip(series.iter()) {
+        let v: Vec<f64> = line.split(',').map(|s| s.parse().unwrap()).collect();
+        assert_eq!(v, [*t, x[0], x[1], x[2]]);
+    }
+
+    let mut csv = Csv::new(Vec::new());
+    csv.write_state(0.5, &arr1(&[c64::new(1.0, 2.0)])).unwrap();
+    let err = csv.write_state(1.0, &arr1(&[c64::new(1.0, 2.0); 2]));
+    assert_eq!(err.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+    assert_eq!(text, "t,x0.re,x0.im\n0.5,1,2\n");
+}
+
+#[test]
+fn csv_columns() {
+    let x = arr1(&[c64::new(1.0, 2.0), c64::new(3.0, 4.0)]);
+    let mut csv = Csv::new(Vec::new()).columns("time", &["r0", "c0", "r1", "c1"]);
+    csv.write_state(0.5, &x).unwrap();
+    let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+    assert_eq!(text, "time,r0,c0,r1,c1\n0.5,1,2,3,4\n");
+
+    let mut csv = Csv::new(Vec::new()).columns("time", &["r0", "c0"]);
+    let err = csv.write_state(0.5, &x);
+    assert_eq!(err.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn npy() {
+    let series = lorenz63(100);
+    let mut npy = Npy::new(Cursor::new(Vec::new())).unwrap();
+    npy.write_all(series.clone()).unwrap();
+    let npy = npy.finish().unwrap().into_inner();
+    let (dict, data) = parse_npy(&npy);
+    assert_eq!(
+        dict,
+        "{'descr': [('t', '<f8'), ('x', '<f8', (3,))], 'fortran_order': False, 'shape': (100,), }"
+    );
+    let data = f64s(data);
+    assert_eq!(data.len(), 100 * 4);
+    for (record, (t, x)) in data.chunks(4).zip(series.iter()) {
+        assert_eq!(record, [*t, x[0], x[1], x[2]]);
+    }
+}
+
+/// Header is completed also on drop
+#[test]
+fn npy_drop() {
+    let mut buf = Cursor::new(Vec::new());
+    {
+        let mut npy = Npy::new(&mut buf).unwrap();
+        npy.write_state(0.0, &Array::<c64, _>::zeros((2, 3)))
+            .unwrap();
+    }
+    let (dict, data) = parse_npy(buf.get_ref());
+    assert_eq!(
+        dict,
+        "{'descr': [('t', '<f8'), ('x', '<c16', (2, 3))], 'fortran_order': False, 'shape': (1,), }"
+    );
+    assert_eq!(data.len(), 8 + 16 * 6);
+}
+
+#[test]
+fn npz() {
+    let dt = 1e-4;
+    let mut x0 = Array::from_elem(27, c64::new(0.0, 0.0));
+    for i in 2..7 {
+        x0[i] = c64::new(1.0, 0.0);
+    }
+    let mut teo = semi_implicit::DiagRK4::new(ode::GoyShell::default(), dt);
+    let series: Vec<_> = adaptor::time_series(x0, &mut teo)
+        .timed()
+        .take(50)
+        .collect();
+
+    let mut npz = Npz::new(Cursor::new(Vec::new())).unwrap();
+    npz.write_all(series.clone()).unwrap();
+    let npz = npz.finish().unwrap().into_inner();
+    let entries = parse_zip(&npz);
+    assert_eq!(entries.len(), 2);
+
+    let (name, x) = &entries[0];
+    assert_eq!(name, "x.npy");
+    let (dict, data) = parse_npy(x);
+    assert_eq!(
+        dict,
+        "{'descr': '<c16', 'fortran_order': False, 'shape': (50, 27), }"
+    );
+    let data = f64s(data);
+    let expected: Vec<f64> = series
+        .iter()
+        .flat_map(|(_, x)| x.iter().flat_map(|c| vec![c.re, c.im]).collect::<Vec<_>>())
+        .collect();
+    assert_eq!(data, expected);
+
+    let (name, t) = &entries[1];
+    assert_eq!(name, "t.npy");
+    let (dict, data) = parse_npy(t);
+    assert_eq!(
+        dict,
+        "{'descr': '<f8', 'fortran_order': False, 'shape': (50,), }"
+    );
+    let times: Vec<f64> = series.iter().map(|(t, _)| *t).collect();
+    assert_eq!(f64s(data), times);
+}
+
+#[test]
+fn chunked() {
+    let series = lorenz63(250);
+    let mut w = Chunked::new(Vec::new()).chunk_size(100);
+    w.write_all(series.clone()).unwrap();
+    let buf = w.finish().unwrap();
+
+    let reader = ChunkedReader::<f64, _>::new(buf.as_slice()).unwrap();
+    assert_eq!(reader.shape(), [3]);
+    let read: Vec<_> = reader.map(|r| r.unwrap()).collect();
+    assert_eq!(read.len(), 250);
+    for ((t, x), (t0, x0)) in read.iter().zip(series.iter()) {
+        assert_eq!(t, t0);
+        assert_eq!(x, &x0.clone().into_dyn());
+    }
+
+    // the states before the truncation are readable
+    let truncated = &buf[..buf.len() - 10];
+    let read: Vec<_> = ChunkedReader::<f64, _>::new(truncated).unwrap().collect();
+    assert_eq!(read.iter().filter(|r| r.is_ok()).count(), 249);
+    assert!(read.last().unwrap().is_err());
+
+    let err = ChunkedReader::<c64, _>::new(buf.as_slice()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,110 @@
+use ndarray::*;
+
+use eom::traits::*;
+use eom::*;
+
+/// `x' = -x + cos(t)`
+#[derive(Clone, Copy, Debug)]
+struct Forced;
+
+impl ModelSpec for Forced {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl ExplicitT for Forced {
+    fn rhs_t<'a, S>(&mut self, t: f64, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x[0] = -x[0] + t.cos();
+        x
+    }
+}
+
+impl SemiImplicitT for Forced {
+    fn nlin_t<'a, S>(&mut self, t: f64, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x[0] = t.cos();
+        x
+    }
+    fn diag_t(&self) -> Array1<f64> {
+        arr1(&[-1.0])
+    }
+}
+
+fn exact(t: f64) -> f64 {
+    0.5 * (t.cos() + t.sin()) + 0.5 * (-t).exp()
+}
+
+/// Error at `t = 2` starting from `x(1) = exact(1)`
+fn error<Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64>>(teo: &mut Sc, dt: f64) -> f64 {
+    let n = (1.0 / dt).round() as usize;
+    teo.set_dt(dt);
+    teo.set_time(1.0);
+    let x = adaptor::iterate(teo, arr1(&[exact(1.0)]), n);
+    assert!((teo.time() - 2.0).abs() < 1e-10);
+    (x[0] - exact(2.0)).abs()
+}
+
+fn check_order<Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64>>(mut teo: Sc, order: f64) {
+    let e0 = error(&mut teo, 0.02);
+    let e1 = error(&mut teo, 0.01);
+    let p = (e0 / e1).log2();
+    assert!((p - order).abs() < 0.3, "order = {}", p);
+}
+
+#[test]
+fn explicit_stage_time() {
+    check_order(explicit::Euler::new(Forced, 0.1), 1.0);
+    check_order(explicit::Heun::new(Forced, 0.1), 2.0);
+    check_order(explicit::RK4::new(Forced, 0.1), 4.0);
+}
+
+#[test]
+fn semi_implicit_stage_time() {
+    check_order(semi_implicit::DiagRK4::new(Forced, 0.1), 4.0);
+    check_order(semi_implicit::ETD1::new(Forced, 0.1), 1.0);
+    check_order(semi_implicit::ETDRK2::new(Forced, 0.1), 2.0);
+    check_order(semi_implicit::ETDRK4::new(Forced, 0.1), 4.0);
+}
+
+#[test]
+fn adaptive_stage_time() {
+    let mut teo = adaptive::DormandPrince::new(Forced, 0.1);
+    teo.set_tolerance(1e-12, 1e-10);
+    let mut x = arr1(&[exact(0.0)]);
+    while teo.time() < 5.0 {
+        teo.iterate(&mut x);
+    }
+    assert!((x[0] - exact(teo.time())).abs() < 1e-8);
+}
+
+#[test]
+fn time_tracking() {
+    let mut teo = adaptor::nstep(explicit::RK4::new(ode::Lorenz63::default(), 0.01), 10);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    teo.iterate_n(&mut x, 10);
+    assert!((teo.time() - 1.0).abs() < 1e-12);
+    let ts: Vec<_> = adaptor::time_series(x, &mut teo).timed().take(3).collect();
+    assert!((ts[2].0 - 1.3).abs() < 1e-12);
+}
+
+#[test]
+fn duffing() {
+    let mut teo = explicit::RK4::new(ode::Duffing::default(), 0.01);
+    let x = adaptor::iterate(&mut teo, arr1(&[0.1, 0.0]), 10_000);
+    assert!(x.iter().all(|v| v.is_finite() && v.abs() < 10.0));
+}
+
+#[test]
+fn van_der_pol() {
+    let mut teo = explicit::RK4::new(ode::VanDerPol::default(), 0.001);
+    let x = adaptor::iterate(&mut teo, arr1(&[1.0, 0.0]), 100_000);
+    assert!(x.iter().all(|v| v.is_finite() && v.abs() < 100.0));
+}
@@ -0,0 +1,104 @@
+use ndarray::*;
+
+use eom::adaptor::*;
+use eom::traits::*;
+use eom::*;
+
+#[test]
+fn record() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let mut obs = adaptor::observe(teo.clone(), Record::every(5));
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let mut x = x0.clone();
+    assert_eq!(obs.run(&mut x, 100), 100);
+    assert_eq!(obs.step(), 100);
+
+    let mut teo = teo;
+    let ts: Vec<_> = adaptor::time_series(x0, &mut teo).take(100).collect();
+    let data = obs.observer().data();
+    assert_eq!(data.len(), 20);
+    for (i, (t, v)) in data.iter().enumerate() {
+        let step = 5 * (i + 1);
+        assert!((t - 0.01 * step as f64).abs() < 1e-12);
+        assert_eq!(v, &ts[step - 1]);
+    }
+    assert_eq!(&data[19].1, &x);
+}
+
+#[test]
+fn statistics() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let mut obs = adaptor::observe(teo, (Statistics::new(), Record::new()));
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    obs.run(&mut x, 1000);
+    let (stat, rec) = obs.observer();
+    assert_eq!(stat.count(), 1000);
+
+    let n = rec.data().len() as f64;
+    let mean = rec
+        .data()
+        .iter()
+        .fold(Array1::<f64>::zeros(3), |m, (_, x)| m + x)
+        / n;
+    let var = rec
+        .data()
+        .iter()
+        .fold(Array1::<f64>::zeros(3), |v, (_, x)| {
+            v + (x - &mean).mapv(|d| d * d)
+        })
+        / (n - 1.0);
+    let dm = (stat.mean().unwrap() - &mean).mapv(f64::abs);
+    let dv = (stat.variance().unwrap() - &var).mapv(f64::abs);
+    assert!(dm.iter().all(|&d| d < 1e-10), "{}", dm);
+    assert!(dv.iter().all(|&d| d < 1e-8), "{}", dv);
+}
+
+#[test]
+fn stop_if() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let stop = StopIf::new(|t: f64, _x: ArrayView1<f64>| t > 1.0 - 1e-9);
+    let mut obs = adaptor::observe(teo, stop);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    assert_eq!(obs.run(&mut x, 1000), 100);
+    assert!(obs.is_stopped());
+    assert_eq!(obs.observer().stopped_at(), Some(100));
+
+    // stopped evolution does not change the state
+    let y = x.clone();
+    obs.iterate(&mut x);
+    assert_eq!(x, y);
+    assert_eq!(obs.step(), 100);
+
+    obs.resume();
+    obs.iterate(&mut x);
+    assert_eq!(obs.step(), 101);
+}
+
+#[test]
+fn non_finite() {
+    // the explicit Euler scheme blows up with a too large time-step
+    let teo = explicit::Euler::new(ode::Lorenz63::default(), 0.5);
+    let mut obs = adaptor::observe(teo, vec![NonFinite::new()]);
+    let mut x = arr1(&[1.0, 0.0, 0.0]);
+    let n = obs.run(&mut x, 10000);
+    assert!(n < 10000);
+    assert_eq!(obs.observer()[0].stopped_at(), Some(n));
+    assert!(x.iter().any(|x| !x.is_finite()));
+}
+
+#[test]
+fn closure() {
+    // closures capturing `&mut` are not `Clone`, but can be used with `run`
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let mut steps = Vec::new();
+    {
+        let count = |_t: f64, step: usize, _x: ArrayView1<f64>| {
+            steps.push(step);
+            Control::Continue
+        };
+        let mut obs = adaptor::observe(teo, count);
+        let mut x = arr1(&[1.0, 0.0, 0.0]);
+        obs.run(&mut x, 10);
+    }
+    assert_eq!(steps, (1..=10).collect::<Vec<_>>());
+}
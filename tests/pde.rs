@@ -0,0 +1,69 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::traits::*;
+use eom::*;
+
+#[test]
+fn fft_roundtrip() {
+    let n = 64;
+    let mut pair = pde::Pair::new(n);
+    let r: Array1<f64> = generate::random(n);
+    pair.r.assign(&r);
+    pair.r2c();
+    assert!((pair.c[0].re - r.mean().unwrap()).abs() < 1e-12);
+    pair.c2r();
+    assert!((&pair.r - &r).norm_max() < 1e-12);
+}
+
+#[test]
+fn fft_mode() {
+    let n = 32;
+    let mut pair = pde::Pair::new(n);
+    pair.r = Array::from_shape_fn(n, |m| {
+        (2.0 * std::f64::consts::PI * 3.0 * m as f64 / n as f64).cos()
+    });
+    pair.r2c();
+    for (j, c) in pair.c.iter().enumerate() {
+        let expected = if j == 3 { 0.5 } else { 0.0 };
+        assert!((c.re - expected).abs() < 1e-12 && c.im.abs() < 1e-12);
+    }
+}
+
+#[test]
+fn kse_bounded() {
+    let n = 64;
+    let length = 50.0;
+    let mut teo = semi_implicit::DiagRK4::new(pde::KSE::new(n, length), 0.01);
+    let mut pair = pde::Pair::new(n);
+    pair.r = Array::from_shape_fn(n, |m| {
+        (2.0 * std::f64::consts::PI * m as f64 / n as f64).cos()
+    });
+    pair.r2c();
+    let mut x = pair.c.clone();
+    teo.iterate_n(&mut x, 10_000);
+    // mean is conserved
+    assert!(x[0].norm() < 1e-10);
+    pair.c.assign(&x);
+    pair.c2r();
+    let u_max = pair.r.norm_max();
+    assert!(u_max > 0.1 && u_max < 10.0, "u_max = {}", u_max);
+}
+
+#[test]
+fn she_stationary() {
+    let n = 64;
+    let mut teo =
+        semi_implicit::DiagRK4::new(pde::SHE::new(n, 16.0 * std::f64::consts::PI, 0.2), 0.05);
+    let mut pair = pde::Pair::new(n);
+    pair.r = Array::from_shape_fn(n, |m| {
+        0.1 * (16.0 * std::f64::consts::PI * m as f64 / n as f64).sin()
+    });
+    pair.r2c();
+    let mut x = pair.c.clone();
+    teo.iterate_n(&mut x, 10_000);
+    let mut y = x.clone();
+    teo.iterate(&mut y);
+    assert!((&y - &x).norm_max() < 1e-10);
+    assert!(x.norm_max() > 0.1);
+}
@@ -0,0 +1,150 @@
+use ndarray::*;
+use ndarray_linalg::*;
+
+use eom::periodic_orbit::*;
+use eom::traits::*;
+use eom::*;
+
+/// Stuart-Landau oscillator `z' = (1 + i w) z - |z|^2 z` with the limit cycle `|z| = 1`
+#[derive(Clone, Copy, Debug)]
+struct StuartLandau {
+    w: f64,
+}
+
+impl ModelSpec for StuartLandau {
+    type Scalar = c64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl Explicit for StuartLandau {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = c64>,
+    {
+        let z = x[0];
+        x[0] = c64::new(1.0, self.w) * z - z.norm_sqr() * z;
+        x
+    }
+}
+
+/// Periodic orbits of autonomous flows have the multiplier 1 along the flow,
+/// which distinguishes them from the equilibria satisfying `phi_T(x) = x` for any `T`
+fn has_unit_multiplier(orbit: &PeriodicOrbit<f64>) -> bool {
+    orbit.multipliers.iter().any(|mu| (mu - 1.0).abs() < 1e-3)
+}
+
+fn lorenz63() -> (explicit::RK4<ode::Lorenz63>, Vec<Recurrence<f64>>) {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let x0 = teo.iterate_n(&mut arr1(&[1.0, 0.0, 0.0]), 1000).clone();
+    let guesses = recurrences(x0, &mut teo, 5000, (50, 400), 10);
+    (teo, guesses)
+}
+
+#[test]
+fn lorenz63_orbits() {
+    let (teo, guesses) = lorenz63();
+    assert_eq!(guesses.len(), 10);
+    for pair in guesses.windows(2) {
+        assert!(pair[0].distance <= pair[1].distance);
+    }
+    let mut solver = Solver::new(teo);
+    solver.set_newton(1e-10, 50);
+    let orbits: Vec<_> = guesses
+        .into_iter()
+        .filter_map(|g| solver.solve(g.x, g.period).ok())
+        .filter(has_unit_multiplier)
+        .collect();
+    // the three-loop orbit AAB and four-loop orbit AAAB
+    for &period in &[2.30591, 3.02359] {
+        assert!(
+            orbits.iter().any(|o| (o.period - period).abs() < 1e-4),
+            "T = {} is not found",
+            period
+        );
+    }
+    for orbit in &orbits {
+        assert!(orbit.residual <= 1e-10 * (1.0 + orbit.x.norm_l2()));
+        let y = solver.flow(&orbit.x, orbit.period).unwrap();
+        assert!((&y - &orbit.x).norm_l2() < 1e-8);
+        // unstable orbits
+        assert!(orbit.multipliers[0].re > 1.0);
+        assert!(orbit.exponents()[0] > 0.0);
+    }
+}
+
+#[test]
+fn gmres() {
+    let (teo, guesses) = lorenz63();
+    let guess = guesses
+        .into_iter()
+        .find(|g| (g.period - 2.3).abs() < 0.1)
+        .unwrap();
+    let mut solver = Solver::new(teo);
+    let direct = solver.solve(guess.x.clone(), guess.period).unwrap();
+    solver.set_linear(Linear::Gmres {
+        dim: 4,
+        restarts: 5,
+    });
+    let krylov = solver.solve(guess.x, guess.period).unwrap();
+    assert!((direct.period - krylov.period).abs() < 1e-6);
+    assert!((&direct.x - &krylov.x).norm_l2() < 1e-5);
+    assert!((direct.multipliers[0] - krylov.multipliers[0]).abs() < 1e-3);
+}
+
+#[test]
+fn roessler_period_one() {
+    let mut teo = explicit::RK4::new(ode::Roessler::default(), 0.01);
+    let x0 = teo.iterate_n(&mut arr1(&[1.0, 0.0, 0.0]), 1000).clone();
+    let guess = recurrences(x0, &mut teo, 10000, (500, 700), 1)
+        .pop()
+        .unwrap();
+    let solver = Solver::new(teo);
+    let orbit = solver.solve(guess.x, guess.period).unwrap();
+    assert!((orbit.period - 5.8811).abs() < 1e-3, "{}", orbit.period);
+    // flip saddle
+    assert!(orbit.multipliers[0].re < -1.0);
+    assert!((orbit.multipliers[1] - 1.0).abs() < 1e-4);
+
+    let points = 100;
+    let traj = solver.trajectory(&orbit, points).unwrap();
+    assert_eq!(traj.dim(), (points, 3));
+    assert_eq!(traj.row(0), orbit.x);
+    let last = traj.row(points - 1).to_owned();
+    let y = solver.flow(&last, orbit.period / points as f64).unwrap();
+    assert!((&y - &orbit.x).norm_l2() < 1e-8);
+}
+
+#[test]
+fn complex_limit_cycle() {
+    let w = 2.0;
+    let teo = explicit::RK4::new(StuartLandau { w }, 0.001);
+    let mut solver = Solver::new(teo);
+    let period = 2.0 * std::f64::consts::PI / w;
+    for &linear in &[
+        Linear::Direct,
+        Linear::Gmres {
+            dim: 2,
+            restarts: 5,
+        },
+    ] {
+        solver.set_linear(linear);
+        let orbit = solver.solve(arr1(&[c64::new(1.1, 0.1)]), 3.0).unwrap();
+        assert!((orbit.period - period).abs() < 1e-8, "{}", orbit.period);
+        assert!((orbit.x[0].norm() - 1.0).abs() < 1e-7);
+        // real system of two dimensions
+        assert_eq!(orbit.multipliers.len(), 2);
+        assert!((orbit.multipliers[0] - 1.0).abs() < 1e-5);
+        assert!((orbit.multipliers[1] - (-2.0 * period).exp()).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn not_converged() {
+    let teo = explicit::RK4::new(ode::Roessler::default(), 0.01);
+    let mut solver = Solver::new(teo);
+    solver.set_newton(1e-10, 1);
+    assert!(solver.solve(arr1(&[1.0, 0.0, 0.0]), 5.0).is_err());
+}
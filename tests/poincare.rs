@@ -0,0 +1,75 @@
+use ndarray::*;
+
+use eom::event::{self, Direction, Event};
+use eom::poincare::*;
+use eom::traits::*;
+use eom::*;
+
+fn x0() -> Array1<f64> {
+    arr1(&[1.0, 0.0, 0.0])
+}
+
+// Both interpolate the same discrete trajectory, and agree to the fourth order of the step size
+#[test]
+fn section_matches_events() {
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.001);
+    let plane = Hyperplane::axis(3, 2, 27.0).direction(Direction::Rising);
+    let crossings: Vec<_> = section(x0(), &mut teo, plane).until(20.0).collect();
+
+    let mut teo = explicit::RK4::new(ode::Lorenz63::default(), 0.001);
+    let e = Event::new(|_t, x: ArrayView1<f64>| x[2] - 27.0).direction(Direction::Rising);
+    let occurrences: Vec<_> = event::detect(x0(), &mut teo, vec![e]).until(20.0).collect();
+
+    assert!(crossings.len() > 10);
+    assert_eq!(crossings.len(), occurrences.len());
+    for (c, o) in crossings.iter().zip(occurrences.iter()) {
+        assert!((c.x[2] - 27.0).abs() < 1e-9);
+        assert!((c.t - o.t).abs() < 1e-8);
+        assert!((&c.x - &o.x).iter().all(|d| d.abs() < 1e-6));
+    }
+}
+
+#[test]
+fn section_of_any_scheme() {
+    // semi-implicit scheme and oblique plane crossed downward
+    let mut teo = semi_implicit::DiagRK4::new(ode::Lorenz63::default(), 0.01);
+    let plane = Hyperplane::new(arr1(&[1.0, -1.0, 0.0]), 0.0).direction(Direction::Falling);
+    let mut last = 0.0;
+    for c in section(x0(), &mut teo, plane).take(20) {
+        assert!((c.x[0] - c.x[1]).abs() < 1e-9);
+        assert!(c.t > last);
+        last = c.t;
+    }
+}
+
+#[test]
+fn lorenz_map() {
+    let p = ode::Lorenz63::default();
+    let mut teo = explicit::RK4::new(p, 0.001);
+    let z_max: Vec<_> = maxima(x0(), &mut teo, |x| x[2]).until(20.0).collect();
+
+    // maxima of z are the falling zeros of dz/dt
+    let mut teo = explicit::RK4::new(p, 0.001);
+    let e = Event::new(move |_t, x: ArrayView1<f64>| x[0] * x[1] - p.b * x[2])
+        .direction(Direction::Falling);
+    let occurrences: Vec<_> = event::detect(x0(), &mut teo, vec![e]).until(20.0).collect();
+
+    assert!(z_max.len() > 10);
+    assert_eq!(z_max.len(), occurrences.len());
+    for (&(t, z), o) in z_max.iter().zip(occurrences.iter()) {
+        assert!((t - o.t).abs() < 1e-6);
+        assert!((z - o.x[2]).abs() < 1e-6);
+    }
+
+    let map: Vec<_> = return_map(z_max.iter().map(|&(_t, z)| z)).collect();
+    assert_eq!(map.len(), z_max.len() - 1);
+    assert_eq!(map[0].1, map[1].0);
+}
+
+#[test]
+fn return_map_pairs() {
+    let map: Vec<_> = return_map(vec![1, 2, 3]).collect();
+    assert_eq!(map, vec![(1, 2), (2, 3)]);
+    assert_eq!(return_map(vec![1]).count(), 0);
+    assert_eq!(return_map(Vec::<i32>::new()).count(), 0);
+}
@@ -0,0 +1,49 @@
+use ndarray::*;
+use ndarray_linalg::*;
+use num_complex::Complex64 as c64;
+
+use eom::traits::*;
+use eom::*;
+
+/// Estimate convergence order from the deviations between successive halving of time-step
+fn order<Sc>(teo: Sc) -> f64
+where
+    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    let acc = adaptor::accuracy(teo, arr1(&[1.0, 0.0, 0.0]), 0.01, 100, 7);
+    let (_, dev0) = acc[acc.len() - 2];
+    let (_, dev1) = acc[acc.len() - 1];
+    (dev0 / dev1).log2()
+}
+
+#[test]
+fn etd1_order() {
+    let p = order(semi_implicit::ETD1::new(ode::Lorenz63::default(), 1.0));
+    assert!((p - 1.0).abs() < 0.2, "order = {}", p);
+}
+
+#[test]
+fn etdrk2_order() {
+    let p = order(semi_implicit::ETDRK2::new(ode::Lorenz63::default(), 1.0));
+    assert!((p - 2.0).abs() < 0.2, "order = {}", p);
+}
+
+#[test]
+fn etdrk4_order() {
+    let p = order(semi_implicit::ETDRK4::new(ode::Lorenz63::default(), 1.0));
+    assert!((p - 4.0).abs() < 0.3, "order = {}", p);
+}
+
+#[test]
+fn etdrk4_goy_shell() {
+    let dt = 1e-4;
+    let mut x0 = Array::from_elem(27, c64::new(0.0, 0.0));
+    for i in 2..7 {
+        x0[i] = c64::new(1.0, 0.0);
+    }
+    let mut rk4 = semi_implicit::DiagRK4::new(ode::GoyShell::default(), dt);
+    let mut etd = semi_implicit::ETDRK4::new(ode::GoyShell::default(), dt);
+    let x = adaptor::iterate(&mut rk4, x0.clone(), 1000);
+    let y = adaptor::iterate(&mut etd, x0, 1000);
+    assert!((&x - &y).norm_l2() / x.norm_l2() < 1e-6);
+}
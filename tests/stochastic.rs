@@ -0,0 +1,161 @@
+use ndarray::*;
+
+use eom::stochastic::*;
+use eom::traits::*;
+use eom::*;
+
+/// `x' = mu x`
+#[derive(Clone, Copy, Debug)]
+struct Linear {
+    mu: f64,
+}
+
+impl ModelSpec for Linear {
+    type Scalar = f64;
+    type Dim = Ix1;
+    fn model_size(&self) -> usize {
+        1
+    }
+}
+
+impl Explicit for Linear {
+    fn rhs<'a, S>(&mut self, x: &'a mut ArrayBase<S, Ix1>) -> &'a mut ArrayBase<S, Ix1>
+    where
+        S: DataMut<Elem = f64>,
+    {
+        x[0] *= self.mu;
+        x
+    }
+}
+
+#[test]
+fn reproducible() {
+    let f = AdditiveNoise::new(ode::Lorenz63::default(), 1.0);
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let x1 = adaptor::iterate(&mut EulerMaruyama::<_>::new(f, 0.01), x0.clone(), 100);
+    let x2 = adaptor::iterate(&mut EulerMaruyama::<_>::new(f, 0.01), x0.clone(), 100);
+    assert_eq!(x1, x2);
+    let mut teo = EulerMaruyama::<_>::new(f, 0.01);
+    teo.seed(1);
+    let x3 = adaptor::iterate(&mut teo, x0, 100);
+    assert_ne!(x1, x3);
+}
+
+/// The default generator draws the same stream as `StdRng`
+#[test]
+fn std_rng_stream() {
+    use rand::{rngs::StdRng, SeedableRng};
+    let f = AdditiveNoise::new(ode::Lorenz63::default(), 1.0);
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let mut teo = EulerMaruyama::<_>::new(f, 0.01);
+    teo.seed(3);
+    let x1 = adaptor::iterate(&mut teo, x0.clone(), 100);
+    let mut teo = EulerMaruyama::with_rng(f, 0.01, StdRng::seed_from_u64(3));
+    let x2 = adaptor::iterate(&mut teo, x0, 100);
+    assert_eq!(x1, x2);
+}
+
+#[test]
+fn zero_noise() {
+    let l63 = ode::Lorenz63::default();
+    let x0 = arr1(&[1.0, 0.0, 0.0]);
+    let f = AdditiveNoise::new(l63, 0.0);
+    let x = adaptor::iterate(&mut explicit::Euler::new(l63, 0.01), x0.clone(), 100);
+    let y = adaptor::iterate(&mut EulerMaruyama::<_>::new(f, 0.01), x0.clone(), 100);
+    assert_eq!(x, y);
+    let y = adaptor::iterate(&mut Milstein::<_>::new(f, 0.01), x0.clone(), 100);
+    assert_eq!(x, y);
+    let x = adaptor::iterate(&mut explicit::Heun::new(l63, 0.01), x0.clone(), 100);
+    let y = adaptor::iterate(&mut StochasticHeun::<_>::new(f, 0.01), x0, 100);
+    assert!((x - y).iter().all(|d| d.abs() < 1e-12));
+}
+
+/// Stationary variance of the Ornstein-Uhlenbeck process `dx = -x dt + dW` is `1/2`
+#[test]
+fn ornstein_uhlenbeck() {
+    let f = AdditiveNoise::new(Linear { mu: -1.0 }, 1.0);
+    let mut teo = StochasticHeun::<_>::new(f, 0.01);
+    let ts = adaptor::time_series(arr1(&[0.0]), &mut teo);
+    let var = ts
+        .skip(1000)
+        .take(200_000)
+        .map(|x| x[0] * x[0])
+        .sum::<f64>()
+        / 200_000.0;
+    assert!((var - 0.5).abs() < 0.05, "var = {}", var);
+}
+
+/// Mean strong error at `t = 1` for the geometric Brownian motion `dx = mu x dt + sigma x dW`
+fn gbm_error<Sc>(new: impl Fn(u64) -> Sc, increment: impl Fn(&Sc) -> f64, stratonovich: bool) -> f64
+where
+    Sc: TimeEvolution<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    let (mu, sigma) = (1.5, 1.0);
+    let n = 64;
+    let paths = 100;
+    let mut err = 0.0;
+    for seed in 0..paths {
+        let mut teo = new(seed);
+        let mut x = arr1(&[1.0]);
+        let mut w = 0.0;
+        for _ in 0..n {
+            teo.iterate(&mut x);
+            w += increment(&teo);
+        }
+        let drift = if stratonovich {
+            mu
+        } else {
+            mu - 0.5 * sigma * sigma
+        };
+        let exact = (drift + sigma * w).exp();
+        err += (x[0] - exact).abs();
+    }
+    err / paths as f64
+}
+
+#[test]
+fn gbm_strong_error() {
+    let f = MultiplicativeNoise::new(Linear { mu: 1.5 }, 1.0);
+    let dt = 1.0 / 64.0;
+    let em = gbm_error(
+        |seed| {
+            let mut teo = EulerMaruyama::<_>::new(f, dt);
+            teo.seed(seed);
+            teo
+        },
+        |teo| teo.last_increment()[0],
+        false,
+    );
+    let mil = gbm_error(
+        |seed| {
+            let mut teo = Milstein::<_>::new(f, dt);
+            teo.seed(seed);
+            teo
+        },
+        |teo| teo.last_increment()[0],
+        false,
+    );
+    let heun = gbm_error(
+        |seed| {
+            let mut teo = StochasticHeun::<_>::new(f, dt);
+            teo.seed(seed);
+            teo
+        },
+        |teo| teo.last_increment()[0],
+        true,
+    );
+    assert!(mil < 0.5 * em, "EM = {}, Milstein = {}", em, mil);
+    assert!(heun < 0.5 * em, "EM = {}, Heun = {}", em, heun);
+}
+
+#[test]
+fn nstep_series() {
+    let f = AdditiveNoise::new(ode::Lorenz96::default(), 0.1);
+    let teo = EulerMaruyama::<_>::new(f, 0.01);
+    let mut teo = adaptor::nstep(teo, 10);
+    let ts: Vec<_> = adaptor::time_series(Array::zeros(40), &mut teo)
+        .take(10)
+        .collect();
+    assert_eq!(ts.len(), 10);
+    assert!((teo.time() - 1.0).abs() < 1e-12);
+}
@@ -0,0 +1,88 @@
+use ndarray::*;
+
+use eom::symplectic::*;
+use eom::traits::*;
+use eom::*;
+
+/// Estimate convergence order from the deviations between successive halving of time-step
+fn order<Sc>(teo: Sc) -> f64
+where
+    Sc: Scheme<Scalar = f64, Dim = Ix1, Time = f64>,
+{
+    let acc = adaptor::accuracy(teo, arr1(&[0.1, 0.1, 0.3, 0.2]), 0.1, 100, 5);
+    let (_, dev0) = acc[acc.len() - 2];
+    let (_, dev1) = acc[acc.len() - 1];
+    (dev0 / dev1).log2()
+}
+
+#[test]
+fn orders() {
+    let f = ode::HenonHeiles::default();
+    let p = order(StormerVerlet::new(f, 1.0));
+    assert!((p - 2.0).abs() < 0.2, "order = {}", p);
+    let p = order(Yoshida4::new(f, 1.0));
+    assert!((p - 4.0).abs() < 0.3, "order = {}", p);
+    let p = order(ForestRuth::new(f, 1.0));
+    assert!((p - 4.0).abs() < 0.3, "order = {}", p);
+    let p = order(Yoshida6::new(f, 1.0));
+    assert!((p - 6.0).abs() < 0.5, "order = {}", p);
+}
+
+/// Maximum deviation of energy in `steps`
+fn energy_error<Sc>(mut teo: Sc, x0: Array1<f64>, steps: usize) -> f64
+where
+    Sc: Scheme<Scalar = f64, Dim = Ix1>,
+    Sc::Core: Hamiltonian<Scalar = f64>,
+{
+    let e0 = teo.core().energy(&x0);
+    let mut x = x0;
+    let mut err: f64 = 0.0;
+    for _ in 0..steps {
+        teo.iterate(&mut x);
+        err = err.max((teo.core().energy(&x) - e0).abs());
+    }
+    err
+}
+
+#[test]
+fn henon_heiles_energy() {
+    let f = ode::HenonHeiles::default();
+    let x0 = arr1(&[0.1, 0.1, 0.3, 0.2]);
+    let dt = 0.05;
+    let e = energy_error(StormerVerlet::new(f, dt), x0.clone(), 1_000_000);
+    assert!(e < 1e-4, "energy error = {}", e);
+}
+
+#[test]
+fn henon_heiles_energy_higher_order() {
+    let f = ode::HenonHeiles::default();
+    let x0 = arr1(&[0.1, 0.1, 0.3, 0.2]);
+    let dt = 0.05;
+    let e = energy_error(Yoshida4::new(f, dt), x0.clone(), 100_000);
+    assert!(e < 1e-6, "energy error = {}", e);
+    let e = energy_error(ForestRuth::new(f, dt), x0.clone(), 100_000);
+    assert!(e < 1e-6, "energy error = {}", e);
+    let e = energy_error(Yoshida6::new(f, dt), x0, 100_000);
+    assert!(e < 1e-8, "energy error = {}", e);
+}
+
+#[test]
+fn fput_energy() {
+    let f = ode::FPUT::new(4, 0.25, 0.1);
+    let mut x0 = Array::zeros(8);
+    for i in 0..4 {
+        x0[i] = (std::f64::consts::PI * (i + 1) as f64 / 5.0).sin();
+    }
+    let e = energy_error(StormerVerlet::new(f, 0.05), x0, 1_000_000);
+    assert!(e < 1e-3, "energy error = {}", e);
+}
+
+/// Hamiltonian models also work with explicit schemes
+#[test]
+fn explicit_rhs() {
+    let f = ode::FPUT::default();
+    let x0: Array1<f64> = Array::from_shape_fn(64, |i| 0.01 * i as f64);
+    let x = adaptor::iterate(&mut Yoshida6::new(f, 0.001), x0.clone(), 100);
+    let y = adaptor::iterate(&mut explicit::RK4::new(f, 0.001), x0, 100);
+    assert!((x - y).iter().all(|d| d.abs() < 1e-10));
+}
@@ -0,0 +1,149 @@
+use ndarray::*;
+use ndarray_linalg::*;
+use num_complex::Complex64 as c64;
+
+use eom::lyapunov::*;
+use eom::tangent::*;
+use eom::traits::*;
+use eom::*;
+
+/// Compare `rhs_tangent` with the central difference of `rhs_t`
+fn check_rhs_tangent<F>(mut f: F, t: f64)
+where
+    F: ExplicitJacobian<Scalar = f64, Dim = Ix1>,
+{
+    let n = f.model_size();
+    let x: Array1<f64> = generate::random(n);
+    let dx: Array1<f64> = generate::random(n);
+    let h = 1e-6;
+    let mut xp = &x + &(h * &dx);
+    let mut xm = &x - &(h * &dx);
+    f.rhs_t(t, &mut xp);
+    f.rhs_t(t, &mut xm);
+    let fd = (xp - xm) / (2.0 * h);
+    let mut jdx = dx.clone();
+    f.rhs_tangent(t, &x, &mut jdx);
+    let dev = (&jdx - &fd).norm_l2() / fd.norm_l2();
+    assert!(dev < 1e-6, "dev = {}", dev);
+}
+
+#[test]
+fn rhs_tangent() {
+    check_rhs_tangent(ode::Lorenz63::default(), 0.0);
+    check_rhs_tangent(ode::Lorenz96::default(), 0.0);
+    check_rhs_tangent(ode::Roessler::default(), 0.0);
+    check_rhs_tangent(ode::Duffing::default(), 0.3);
+    check_rhs_tangent(ode::VanDerPol::default(), 0.3);
+}
+
+#[test]
+fn nlin_tangent() {
+    let mut f = ode::Lorenz63::default();
+    let x: Array1<f64> = generate::random(3);
+    let dx: Array1<f64> = generate::random(3);
+    let h = 1e-6;
+    let mut xp = &x + &(h * &dx);
+    let mut xm = &x - &(h * &dx);
+    f.nlin_t(0.0, &mut xp);
+    f.nlin_t(0.0, &mut xm);
+    let fd = (xp - xm) / (2.0 * h);
+    let mut jdx = dx.clone();
+    f.nlin_tangent(0.0, &x, &mut jdx);
+    assert!((&jdx - &fd).norm_l2() < 1e-6 * fd.norm_l2());
+}
+
+#[test]
+fn goy_shell_tangent() {
+    let mut f = ode::GoyShell::default();
+    let n = f.model_size();
+    let x: Array1<c64> = generate::random(n);
+    let dx: Array1<c64> = generate::random(n);
+    let h = 1e-6;
+    let mut xp = &x + &dx.mapv(|d| d * h);
+    let mut xm = &x - &dx.mapv(|d| d * h);
+    f.nlin_t(0.0, &mut xp);
+    f.nlin_t(0.0, &mut xm);
+    let fd = (xp - xm).mapv(|d| d / (2.0 * h));
+    let mut jdx = dx.clone();
+    f.nlin_tangent(0.0, &x, &mut jdx);
+    assert!((&jdx - &fd).norm_l2() < 1e-6 * fd.norm_l2());
+}
+
+/// The tangent scheme gives the Jacobian of the discrete time-evolution
+fn check_scheme<Sc>(teo: Sc)
+where
+    Sc: TangentScheme<Scalar = f64, Time = f64> + Clone,
+    Sc::Core: Clone,
+{
+    let x = arr1(&[1.0, 2.0, 20.0]);
+    let mut tangent = teo.tangent(3);
+    let mut xq = Array2::zeros((3, 4));
+    xq.column_mut(0).assign(&x);
+    xq.slice_mut(s![.., 1..]).assign(&Array2::eye(3));
+    tangent.iterate(&mut xq);
+
+    let mut teo_x = teo.clone();
+    let mut fx = x.clone();
+    teo_x.iterate(&mut fx);
+    assert!((&xq.column(0) - &fx).norm_l2() < 1e-12);
+
+    let h = 1e-6;
+    for j in 0..3 {
+        let mut xp = x.clone();
+        let mut xm = x.clone();
+        xp[j] += h;
+        xm[j] -= h;
+        teo.clone().iterate(&mut xp);
+        teo.clone().iterate(&mut xm);
+        let fd = (xp - xm) / (2.0 * h);
+        let dev = (&xq.column(j + 1) - &fd).norm_l2() / fd.norm_l2();
+        assert!(dev < 1e-6, "dev = {}", dev);
+    }
+}
+
+#[test]
+fn tangent_scheme() {
+    let f = ode::Lorenz63::default();
+    check_scheme(explicit::Euler::new(f, 0.01));
+    check_scheme(explicit::Heun::new(f, 0.01));
+    check_scheme(explicit::RK4::new(f, 0.01));
+    check_scheme(semi_implicit::DiagRK4::new(f, 0.01));
+    check_scheme(semi_implicit::ETD1::new(f, 0.01));
+    check_scheme(semi_implicit::ETDRK2::new(f, 0.01));
+    check_scheme(semi_implicit::ETDRK4::new(f, 0.01));
+}
+
+#[test]
+fn variational_size() {
+    let v = Variational::new(ode::Lorenz96::default(), 2);
+    assert_eq!(v.model_size(), (40, 3));
+    assert_eq!(v.tangent_size(), 2);
+}
+
+#[test]
+fn exponents_lorenz63() {
+    let teo = explicit::RK4::new(ode::Lorenz63::default(), 0.01);
+    let x0 = adaptor::iterate(&mut teo.clone(), arr1(&[1.0, 0.0, 0.0]), 1000);
+    let l = exponents_exact(teo.clone(), x0.clone(), 20000);
+    assert!((l[0] - 0.906).abs() < 0.1, "l = {}", l);
+    assert!(l[1].abs() < 0.05, "l = {}", l);
+    assert!((l[2] + 14.57).abs() < 0.1, "l = {}", l);
+    // exponents of the Jacobian sum up to the trace `-(p + 1 + b)`
+    assert!((l.sum() + 41.0 / 3.0).abs() < 1e-3, "l = {}", l);
+
+    let l_fd = exponents(teo, x0, 1e-7, 20000);
+    assert!((&l - &l_fd).norm_l2() < 0.05, "l = {}, l_fd = {}", l, l_fd);
+}
+
+#[test]
+fn vectors_exact_lorenz63() {
+    let teo = semi_implicit::DiagRK4::new(ode::Lorenz63::default(), 0.01);
+    let x0 = adaptor::iterate(&mut teo.clone(), arr1(&[1.0, 0.0, 0.0]), 1000);
+    let clv = vectors_exact(teo, x0, 1000);
+    assert_eq!(clv.len(), 1000);
+    for (_x, v, _f) in &clv {
+        for col in v.axis_iter(Axis(1)) {
+            assert!((col.norm_l2() - 1.0).abs() < 1e-8);
+        }
+    }
+}